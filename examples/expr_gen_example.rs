@@ -1,6 +1,7 @@
 use achilles::expr_gen::expr_gen::generate_raw_expr;
 use nes_rust_client::{prelude::*, query::stringify::stringify_expr};
 use nes_types::{IntType, NesType};
+use rand::{rngs::StdRng, SeedableRng};
 
 fn main() {
     simple_logger::init_with_level(log::Level::Trace).expect("Simple_logger should not fail!");
@@ -15,8 +16,9 @@ fn main() {
         // Field::typed("BooleanValue", NesType::Bool),
     ];
 
+    let mut rng = StdRng::seed_from_u64(42);
     for i in 0..32 {
-        let expr = match generate_raw_expr(2, &fields, NesType::Bool) {
+        let expr = match generate_raw_expr(&mut rng, 2, &fields, NesType::Bool) {
             Ok(ok) => ok,
             Err(e) => {
                 log::error!("{e}");
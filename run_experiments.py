@@ -11,7 +11,7 @@ def run_cargo_for_files(directory):
         file_path = os.path.join(directory, filename)
         
         if os.path.isfile(file_path):
-            command = ["cargo", "run", "--", file_path]
+            command = ["cargo", "run", "--", "run", "--config", file_path]
             print(f"Running command: {' '.join(command)}")
 
             try:
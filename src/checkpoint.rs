@@ -0,0 +1,155 @@
+use std::{collections::HashMap, ffi::OsStr, fs};
+
+use crate::{
+    stages::Stages,
+    test_case_exec::TestSetExec,
+    yaml_util::{load_yaml_array, store_yaml_array},
+    AchillesError, LancerConfig,
+};
+
+/// Persists the execution of a single test set, so that an interrupted QueryExec stage can resume
+/// without executing it again. The checkpoint is written to a temporary file and renamed, a crash
+/// while writing never leaves a truncated checkpoint.
+pub fn store_test_set_checkpoint(
+    test_run_id: u32,
+    config: &LancerConfig,
+    test_set_exec: &TestSetExec,
+) -> Result<(), AchillesError> {
+    let dir = config.path_config.checkpoints(test_run_id);
+    fs::create_dir_all(&dir).map_err(|err| AchillesError::io(&dir, err))?;
+    let path = config
+        .path_config
+        .test_set_checkpoint(test_run_id, test_set_exec.id);
+    let tmp_path = path.with_extension("yml.tmp");
+    store_yaml_array(&tmp_path, std::slice::from_ref(test_set_exec))?;
+    fs::rename(&tmp_path, &path).map_err(|err| AchillesError::io(&path, err))
+}
+
+/// Loads all checkpoints of a test run by test set id. Unreadable checkpoints are skipped, their
+/// test sets are executed again.
+pub fn load_test_set_checkpoints(
+    test_run_id: u32,
+    config: &LancerConfig,
+) -> HashMap<u32, TestSetExec> {
+    let mut checkpoints = HashMap::new();
+    let dir = config.path_config.checkpoints(test_run_id);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return checkpoints;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension() != Some(OsStr::new("yml")) {
+            continue;
+        }
+        match load_yaml_array::<TestSetExec>(&path) {
+            Ok(execs) => {
+                for exec in execs {
+                    checkpoints.insert(exec.id, exec);
+                }
+            }
+            Err(err) => log::warn!("Ignoring checkpoint: {err}"),
+        }
+    }
+    checkpoints
+}
+
+pub fn clear_test_set_checkpoints(
+    test_run_id: u32,
+    config: &LancerConfig,
+) -> Result<(), AchillesError> {
+    let dir = config.path_config.checkpoints(test_run_id);
+    if !dir.exists() {
+        return Ok(());
+    }
+    fs::remove_dir_all(&dir).map_err(|err| AchillesError::io(&dir, err))
+}
+
+/// Returns the last stage a test run completed, `None` if it never completed a stage or was
+/// interrupted.
+pub fn read_completed_stage(test_run_id: u32, config: &LancerConfig) -> Option<Stages> {
+    let path = config.path_config.completed_stage(test_run_id);
+    let content = fs::read_to_string(path).ok()?;
+    content.trim().parse().ok()
+}
+
+pub fn write_completed_stage(
+    test_run_id: u32,
+    config: &LancerConfig,
+    stage: Stages,
+) -> Result<(), AchillesError> {
+    let path = config.path_config.completed_stage(test_run_id);
+    fs::write(&path, stage.to_string()).map_err(|err| AchillesError::io(&path, err))
+}
+
+pub fn clear_completed_stage(test_run_id: u32, config: &LancerConfig) -> Result<(), AchillesError> {
+    let path = config.path_config.completed_stage(test_run_id);
+    if !path.exists() {
+        return Ok(());
+    }
+    fs::remove_file(&path).map_err(|err| AchillesError::io(&path, err))
+}
+
+#[cfg(test)]
+mod checkpoint_tests {
+    use nes_rust_client::prelude::*;
+
+    use crate::{
+        test_case_exec::{TestCaseExec, TestCaseExecStatus},
+        test_case_gen::{oracle::QueryGenStrategy, query_id::TestCaseId, test_case::TestCase},
+    };
+
+    use super::*;
+
+    fn test_config(name: &str) -> LancerConfig {
+        let mut config = LancerConfig::default();
+        config.path_config.base = std::env::temp_dir().join(format!("achilles-checkpoint-{name}"));
+        let _ = fs::remove_dir_all(config.path_config.base());
+        fs::create_dir_all(config.path_config.test_run(0)).unwrap();
+        config
+    }
+
+    fn test_set_exec(config: &LancerConfig, id: u32) -> TestSetExec {
+        let path = config
+            .path_config
+            .result(0)
+            .join(format!("test-set{id}-origin.csv"));
+        let query = QueryBuilder::from_source("numbers").sink(Sink::csv_file(&path, false));
+        let origin = TestCase::new(TestCaseId::Origin, query, path);
+        TestSetExec {
+            id,
+            strategy: QueryGenStrategy::Filter,
+            origin: TestCaseExec::from_with(origin, TestCaseExecStatus::TimedOut),
+            others: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn checkpoints_are_reloaded() {
+        let config = test_config("reload");
+        store_test_set_checkpoint(0, &config, &test_set_exec(&config, 3)).unwrap();
+        store_test_set_checkpoint(0, &config, &test_set_exec(&config, 5)).unwrap();
+        // truncated checkpoints are ignored and leftover temporary files are not read
+        let dir = config.path_config.checkpoints(0);
+        fs::write(dir.join("broken.yml"), "- id: [").unwrap();
+        fs::write(dir.join("test-set7.yml.tmp"), "").unwrap();
+
+        let checkpoints = load_test_set_checkpoints(0, &config);
+        let mut ids = checkpoints.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, [3, 5]);
+        assert_eq!(checkpoints[&3].origin.status, TestCaseExecStatus::TimedOut);
+
+        clear_test_set_checkpoints(0, &config).unwrap();
+        assert!(load_test_set_checkpoints(0, &config).is_empty());
+    }
+
+    #[test]
+    fn completed_stage_is_persisted() {
+        let config = test_config("stage");
+        assert_eq!(read_completed_stage(0, &config), None);
+        write_completed_stage(0, &config, Stages::QueryExec).unwrap();
+        assert_eq!(read_completed_stage(0, &config), Some(Stages::QueryExec));
+        clear_completed_stage(0, &config).unwrap();
+        assert_eq!(read_completed_stage(0, &config), None);
+    }
+}
@@ -0,0 +1,419 @@
+use std::{path::PathBuf, str::FromStr, time::Duration};
+
+use crate::{
+    reducer::reduce_test_set::ReduceTestSet, replay_exec::ReplayExec, stages::Stages,
+    test_case_gen::query_id::TestCaseId, LancerConfig,
+};
+
+pub const USAGE: &str = "\
+Usage: achilles <COMMAND> [OPTIONS]
+
+Commands:
+  run              Run all stages of every test run (default)
+  summary          Print a summary of the results of every test run
+                     [--export <DIR>] also writes summary.json, summary.csv and junit.xml
+  replay           Replay a test set or a single test case
+                     --run <N> --set <M> [--case <Origin|OtherK>]
+  reduce           Reduce a test case with a Diff to a minimal reproducer in ./reduced
+                     --run <N> --set <M> [--case <OtherK>] [--max-attempts <N>]
+  extract-diffs    Copy the result files of all diffs into ./extract-diffs
+  generate-only    Only run the StreamGen and QueryGen stages
+  eval-only        Only run the Evaluation stage
+
+Options:
+  --config <PATH>                  Load the LancerConfig from a yaml file
+  --base <PATH>                    Override path_config.base
+  --seed <N>                       Override test_config.seed
+  --test-run-count <N>             Override test_config.test_run_count
+  --oracle-reps <N>                Override test_config.oracle_reps
+  --test-case-count <N>            Override test_config.test_case_count
+  --field-count <N>                Override test_config.field_count
+  --record-count <N>               Override test_config.record_count
+  --physical-source-count <N>      Override test_config.physical_source_count
+  --predicate-depth <N>            Override test_config.predicate_depth
+  --reference-check <BOOL>         Override test_config.reference_check
+  --cluster-count <N>              Override net_config.cluster_count
+  --oracles <A,B,..>               Override test_config.oracles
+  --test-case-timeout <SECS>       Override test_case_timeout
+  --skip-to-stage <STAGE>          Override skip_to_stage
+  --resume                         Resume an interrupted campaign instead of deleting its files
+  --coordinator-exec-path <PATH>   Override runner_config.coordinator_exec_path
+  --worker-exec-path <PATH>        Override runner_config.worker_exec_path
+  -h, --help                       Print this message";
+
+#[derive(Default, Clone)]
+pub enum OperationMode {
+    #[default]
+    Default,
+    ReplayExec(ReplayExec),
+    Reduce(ReduceTestSet),
+    /// Optionally exports the summary to the directory.
+    Summary(Option<PathBuf>),
+    ExtractDiffs,
+}
+
+/// The `Command` selected on the command line. Most commands map directly onto an `OperationMode`,
+/// `GenerateOnly` and `EvalOnly` run the `Default` operation restricted to a range of `Stages`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Run,
+    Summary,
+    Replay,
+    Reduce,
+    ExtractDiffs,
+    GenerateOnly,
+    EvalOnly,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "run" => Ok(Command::Run),
+            "summary" => Ok(Command::Summary),
+            "replay" => Ok(Command::Replay),
+            "reduce" => Ok(Command::Reduce),
+            "extract-diffs" => Ok(Command::ExtractDiffs),
+            "generate-only" => Ok(Command::GenerateOnly),
+            "eval-only" => Ok(Command::EvalOnly),
+            err => Err(format!("Unknown command: {err}")),
+        }
+    }
+}
+
+pub struct CliArgs {
+    command: Command,
+    pub config_path: Option<PathBuf>,
+    replay_run: Option<u32>,
+    replay_set: Option<u32>,
+    replay_case: Option<TestCaseId>,
+    max_attempts: Option<u32>,
+    export_dir: Option<PathBuf>,
+    resume: bool,
+    /// `LancerConfig` overrides as (flag, value) pairs in the order they were given.
+    overrides: Vec<(String, String)>,
+}
+
+/// Result of parsing the command line. `Help` signals that the usage should be printed.
+pub enum CliParseResult {
+    Args(CliArgs),
+    Help,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<CliParseResult, String> {
+    let mut iter = args.into_iter().peekable();
+    let command = match iter.peek() {
+        Some(arg) if !arg.starts_with('-') => {
+            let command = arg.parse()?;
+            iter.next();
+            command
+        }
+        _ => Command::Run,
+    };
+
+    let mut cli_args = CliArgs {
+        command,
+        config_path: None,
+        replay_run: None,
+        replay_set: None,
+        replay_case: None,
+        max_attempts: None,
+        export_dir: None,
+        resume: false,
+        overrides: Vec::new(),
+    };
+
+    while let Some(flag) = iter.next() {
+        if flag == "-h" || flag == "--help" {
+            return Ok(CliParseResult::Help);
+        }
+        if flag == "--resume" {
+            cli_args.resume = true;
+            continue;
+        }
+        let Some(name) = flag.strip_prefix("--") else {
+            return Err(format!("Unexpected argument: {flag}"));
+        };
+        let Some(value) = iter.next() else {
+            return Err(format!("Missing value for {flag}"));
+        };
+        match name {
+            "config" => cli_args.config_path = Some(PathBuf::from(value)),
+            "run" => cli_args.replay_run = Some(parse_value(name, &value)?),
+            "set" => cli_args.replay_set = Some(parse_value(name, &value)?),
+            "case" => cli_args.replay_case = Some(parse_value(name, &value)?),
+            "max-attempts" => cli_args.max_attempts = Some(parse_value(name, &value)?),
+            "export" => cli_args.export_dir = Some(PathBuf::from(value)),
+            _ => cli_args.overrides.push((name.to_string(), value)),
+        }
+    }
+
+    let is_location_command = command == Command::Replay || command == Command::Reduce;
+    if !is_location_command
+        && (cli_args.replay_run.is_some()
+            || cli_args.replay_set.is_some()
+            || cli_args.replay_case.is_some())
+    {
+        return Err(
+            "--run, --set and --case are only valid for the replay and reduce commands.".into(),
+        );
+    }
+    if command != Command::Reduce && cli_args.max_attempts.is_some() {
+        return Err("--max-attempts is only valid for the reduce command.".into());
+    }
+    if command != Command::Summary && cli_args.export_dir.is_some() {
+        return Err("--export is only valid for the summary command.".into());
+    }
+    let is_stage_command = matches!(command, Command::GenerateOnly | Command::EvalOnly);
+    if is_stage_command
+        && cli_args
+            .overrides
+            .iter()
+            .any(|(name, _)| name == "skip-to-stage")
+    {
+        return Err(
+            "--skip-to-stage is not valid for the generate-only and eval-only commands.".into(),
+        );
+    }
+    let is_run_command = matches!(
+        command,
+        Command::Run | Command::GenerateOnly | Command::EvalOnly
+    );
+    if !is_run_command && cli_args.resume {
+        return Err(
+            "--resume is only valid for the run, generate-only and eval-only commands.".into(),
+        );
+    }
+    Ok(CliParseResult::Args(cli_args))
+}
+
+impl CliArgs {
+    /// Applies the command line overrides to `config` and returns the selected `OperationMode`.
+    pub fn apply(&self, config: &mut LancerConfig) -> Result<OperationMode, String> {
+        for (name, value) in &self.overrides {
+            apply_override(config, name, value)?;
+        }
+        if self.resume {
+            config.resume = true;
+        }
+        let operation_mode = match self.command {
+            Command::Run => OperationMode::Default,
+            Command::Summary => OperationMode::Summary(self.export_dir.clone()),
+            Command::ExtractDiffs => OperationMode::ExtractDiffs,
+            Command::GenerateOnly => {
+                config.skip_to_stage = Stages::StreamGen;
+                config.stop_after_stage = Stages::QueryGen;
+                OperationMode::Default
+            }
+            Command::EvalOnly => {
+                config.skip_to_stage = Stages::Evaluation;
+                config.stop_after_stage = Stages::Evaluation;
+                OperationMode::Default
+            }
+            Command::Replay => {
+                let Some(run_id) = self.replay_run else {
+                    return Err("replay requires --run <N>".into());
+                };
+                let Some(test_set_id) = self.replay_set else {
+                    return Err("replay requires --set <M>".into());
+                };
+                let replay = match self.replay_case {
+                    Some(test_case_id) => ReplayExec::test_case(run_id, test_set_id, test_case_id),
+                    None => ReplayExec::test_set(run_id, test_set_id),
+                };
+                OperationMode::ReplayExec(replay)
+            }
+            Command::Reduce => {
+                let Some(run_id) = self.replay_run else {
+                    return Err("reduce requires --run <N>".into());
+                };
+                let Some(test_set_id) = self.replay_set else {
+                    return Err("reduce requires --set <M>".into());
+                };
+                OperationMode::Reduce(ReduceTestSet::new(
+                    run_id,
+                    test_set_id,
+                    self.replay_case,
+                    self.max_attempts,
+                ))
+            }
+        };
+        Ok(operation_mode)
+    }
+}
+
+fn apply_override(config: &mut LancerConfig, name: &str, value: &str) -> Result<(), String> {
+    match name {
+        "base" => config.path_config.base = PathBuf::from(value),
+        "seed" => config.test_config.seed = parse_value(name, value)?,
+        "test-run-count" => config.test_config.test_run_count = parse_value(name, value)?,
+        "oracle-reps" => config.test_config.oracle_reps = parse_value(name, value)?,
+        "test-case-count" => config.test_config.test_case_count = parse_value(name, value)?,
+        "field-count" => config.test_config.field_count = parse_value(name, value)?,
+        "record-count" => config.test_config.record_count = parse_value(name, value)?,
+        "physical-source-count" => {
+            config.test_config.physical_source_count = parse_value(name, value)?
+        }
+        "predicate-depth" => config.test_config.predicate_depth = parse_value(name, value)?,
+        "reference-check" => config.test_config.reference_check = parse_value(name, value)?,
+        "cluster-count" => config.net_config.cluster_count = parse_value(name, value)?,
+        "oracles" => {
+            config.test_config.oracles = value
+                .split(',')
+                .map(|oracle| oracle.trim().parse())
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        "test-case-timeout" => {
+            config.test_case_timeout = Duration::from_secs(parse_value(name, value)?)
+        }
+        "skip-to-stage" => config.skip_to_stage = parse_value(name, value)?,
+        "coordinator-exec-path" => {
+            config.runner_config.coordinator_exec_path = PathBuf::from(value)
+        }
+        "worker-exec-path" => config.runner_config.worker_exec_path = PathBuf::from(value),
+        err => return Err(format!("Unknown option: --{err}")),
+    }
+    Ok(())
+}
+
+fn parse_value<T>(name: &str, value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|err| format!("Invalid value {value:?} for --{name}: {err}"))
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use crate::{stages::Stages, test_case_gen::oracle::QueryGenStrategy, LancerConfig};
+
+    use super::{parse_args, CliArgs, CliParseResult, OperationMode};
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        match parse_args(args.iter().map(|arg| arg.to_string()))? {
+            CliParseResult::Args(args) => Ok(args),
+            CliParseResult::Help => Err("help".into()),
+        }
+    }
+
+    #[test]
+    fn no_args_defaults_to_run() {
+        let mut config = LancerConfig::default();
+        let mode = parse(&[]).unwrap().apply(&mut config).unwrap();
+        assert!(matches!(mode, OperationMode::Default));
+        assert_eq!(config.skip_to_stage, Stages::StreamGen);
+        assert_eq!(config.stop_after_stage, Stages::Evaluation);
+    }
+
+    #[test]
+    fn overrides_are_applied() {
+        let mut config = LancerConfig::default();
+        let args = parse(&[
+            "run",
+            "--config",
+            "configs/config0.yml",
+            "--test-run-count",
+            "7",
+            "--oracles",
+            "Filter,AggAvg",
+        ])
+        .unwrap();
+        args.apply(&mut config).unwrap();
+        assert_eq!(
+            args.config_path.unwrap().to_str(),
+            Some("configs/config0.yml")
+        );
+        assert_eq!(config.test_config.test_run_count, 7);
+        assert_eq!(
+            config.test_config.oracles,
+            vec![QueryGenStrategy::Filter, QueryGenStrategy::AggAvg]
+        );
+    }
+
+    #[test]
+    fn stage_commands() {
+        let mut config = LancerConfig::default();
+        parse(&["generate-only"])
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(config.stop_after_stage, Stages::QueryGen);
+
+        let mut config = LancerConfig::default();
+        parse(&["eval-only"]).unwrap().apply(&mut config).unwrap();
+        assert_eq!(config.skip_to_stage, Stages::Evaluation);
+
+        assert!(parse(&["eval-only", "--skip-to-stage", "QueryExec"]).is_err());
+        assert!(parse(&["generate-only", "--skip-to-stage", "QueryGen"]).is_err());
+    }
+
+    #[test]
+    fn replay_requires_location() {
+        let mut config = LancerConfig::default();
+        let mode = parse(&["replay", "--run", "0", "--set", "2", "--case", "Other3"])
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert!(matches!(mode, OperationMode::ReplayExec(_)));
+        assert!(parse(&["replay", "--run", "0"])
+            .unwrap()
+            .apply(&mut config)
+            .is_err());
+        assert!(parse(&["summary", "--run", "0"]).is_err());
+    }
+
+    #[test]
+    fn reduce_requires_location() {
+        let mut config = LancerConfig::default();
+        let mode = parse(&["reduce", "--run", "1", "--set", "0", "--max-attempts", "10"])
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert!(matches!(mode, OperationMode::Reduce(_)));
+        assert!(parse(&["reduce", "--set", "0"])
+            .unwrap()
+            .apply(&mut config)
+            .is_err());
+        assert!(parse(&["replay", "--max-attempts", "10"]).is_err());
+    }
+
+    #[test]
+    fn resume_flag() {
+        let mut config = LancerConfig::default();
+        parse(&["run", "--resume", "--test-run-count", "3"])
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert!(config.resume);
+        assert_eq!(config.test_config.test_run_count, 3);
+        assert!(parse(&["summary", "--resume"]).is_err());
+    }
+
+    #[test]
+    fn summary_export() {
+        let mut config = LancerConfig::default();
+        let mode = parse(&["summary", "--export", "summary"])
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert!(
+            matches!(mode, OperationMode::Summary(Some(dir)) if dir.to_str() == Some("summary"))
+        );
+        assert!(parse(&["run", "--export", "summary"]).is_err());
+    }
+
+    #[test]
+    fn invalid_args() {
+        assert!(parse(&["unknown"]).is_err());
+        assert!(parse(&["run", "--test-run-count"]).is_err());
+        let mut config = LancerConfig::default();
+        assert!(parse(&["run", "--no-such-flag", "1"])
+            .unwrap()
+            .apply(&mut config)
+            .is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! In-process stand-in for a NebulaStream cluster, so that the QueryExec stage can be tested
+//! without a NebulaStream build. Queries are executed by the reference interpreter and a responder
+//! decides per test case whether the cluster crashes, hangs or rejects the query instead.
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use crate::{
+    error::AchillesError,
+    reference::{
+        interpreter::{execute, Table},
+        plan::RefOperator,
+    },
+    runner::runner_status::{ProcessStatus, RunnerStatus},
+    test_case_gen::test_case::TestCase,
+    LancerConfig,
+};
+
+use super::{ClusterFactory, ClusterManager, QueryRuntime};
+
+/// What happens to a test case on a `FakeCluster`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FakeOutcome {
+    /// The query writes its result and stops.
+    Stop,
+    /// Registering the query fails with the reason.
+    RegistrationFailure(String),
+    /// The coordinator crashes after registering the query.
+    Crash,
+    /// The status of the processes cannot be read after registering the query.
+    HealthCheckError,
+    /// The query never stops.
+    Hang,
+}
+
+type Responder = dyn Fn(&TestCase) -> FakeOutcome + Send + Sync;
+
+struct Shared {
+    responder: Box<Responder>,
+    sources: Option<HashMap<String, Table>>,
+    startup_failures: AtomicU32,
+    startups: AtomicU32,
+}
+
+/// Factory of fake clusters. By default every query stops without writing a result.
+pub struct FakeCluster {
+    shared: Arc<Shared>,
+}
+
+impl Default for FakeCluster {
+    fn default() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                responder: Box::new(|_| FakeOutcome::Stop),
+                sources: None,
+                startup_failures: AtomicU32::new(0),
+                startups: AtomicU32::new(0),
+            }),
+        }
+    }
+}
+
+impl FakeCluster {
+    /// Decides the outcome of every test case.
+    pub fn with_responder(
+        self,
+        responder: impl Fn(&TestCase) -> FakeOutcome + Send + Sync + 'static,
+    ) -> Self {
+        self.update(|shared| shared.responder = Box::new(responder))
+    }
+
+    /// Stopped queries write the result of the reference interpreter on `sources` to their result
+    /// path. Queries the reference interpreter does not support fail to register.
+    pub fn with_reference_sources(self, sources: HashMap<String, Table>) -> Self {
+        self.update(|shared| shared.sources = Some(sources))
+    }
+
+    /// The first `count` startups of any cluster fail.
+    pub fn with_startup_failures(self, count: u32) -> Self {
+        self.update(|shared| *shared.startup_failures.get_mut() = count)
+    }
+
+    /// Number of attempts to start a cluster, including the failed ones.
+    pub fn startup_count(&self) -> u32 {
+        self.shared.startups.load(Ordering::SeqCst)
+    }
+
+    fn update(mut self, update: impl FnOnce(&mut Shared)) -> Self {
+        let shared = Arc::get_mut(&mut self.shared)
+            .expect("A FakeCluster should not be configured after creating clusters!");
+        update(shared);
+        self
+    }
+}
+
+impl ClusterFactory for FakeCluster {
+    type Manager = FakeManager;
+    type Runtime = FakeRuntime;
+
+    fn cluster(
+        &self,
+        _test_run_id: u32,
+        config: &LancerConfig,
+        cluster_id: u16,
+    ) -> Result<(FakeManager, FakeRuntime), AchillesError> {
+        // the fake cluster uses no ports, but rejects the same configs as a real cluster
+        config
+            .net_config
+            .cluster(cluster_id)
+            .map_err(AchillesError::Config)?;
+        let process = Rc::new(RefCell::new(FakeProcess::default()));
+        let manager = FakeManager {
+            shared: self.shared.clone(),
+            process: process.clone(),
+        };
+        let runtime = FakeRuntime {
+            shared: self.shared.clone(),
+            process,
+        };
+        Ok((manager, runtime))
+    }
+}
+
+/// State of the simulated coordinator, shared by the manager and the runtime of a cluster.
+#[derive(Default)]
+struct FakeProcess {
+    is_running: bool,
+    crash: Option<String>,
+    is_health_check_error: bool,
+    queries: Vec<FakeOutcome>,
+}
+
+pub struct FakeManager {
+    shared: Arc<Shared>,
+    process: Rc<RefCell<FakeProcess>>,
+}
+
+impl ClusterManager for FakeManager {
+    fn start_all(&mut self) -> Result<(), AchillesError> {
+        self.shared.startups.fetch_add(1, Ordering::SeqCst);
+        let is_failure = self
+            .shared
+            .startup_failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok();
+        if is_failure {
+            return Err(AchillesError::Runner("Simulated startup failure.".into()));
+        }
+        *self.process.borrow_mut() = FakeProcess {
+            is_running: true,
+            ..Default::default()
+        };
+        Ok(())
+    }
+
+    fn stop_all(&mut self) {
+        self.process.borrow_mut().is_running = false;
+    }
+
+    fn health_check(&mut self) -> io::Result<RunnerStatus> {
+        let process = self.process.borrow();
+        if process.is_health_check_error {
+            return Err(io::Error::other("Simulated health check error."));
+        }
+        let coordinator_status = match (&process.crash, process.is_running) {
+            (Some(crash), _) => ProcessStatus::Error(crash.clone()),
+            (None, true) => ProcessStatus::Running,
+            (None, false) => ProcessStatus::Error("Coordinator is not started.".into()),
+        };
+        Ok(RunnerStatus {
+            coordinator_status,
+            worker_status: Vec::new(),
+        })
+    }
+}
+
+pub struct FakeRuntime {
+    shared: Arc<Shared>,
+    process: Rc<RefCell<FakeProcess>>,
+}
+
+impl QueryRuntime for FakeRuntime {
+    async fn is_connected(&self) -> bool {
+        let process = self.process.borrow();
+        process.is_running && process.crash.is_none()
+    }
+
+    async fn register_query(&self, test_case: &TestCase) -> Result<i64, String> {
+        if !self.is_connected().await {
+            return Err("Connection refused.".into());
+        }
+        let outcome = (self.shared.responder)(test_case);
+        match &outcome {
+            FakeOutcome::RegistrationFailure(reason) => return Err(reason.clone()),
+            FakeOutcome::Crash => {
+                self.process.borrow_mut().crash = Some("Simulated crash.".into());
+            }
+            FakeOutcome::HealthCheckError => {
+                self.process.borrow_mut().is_health_check_error = true;
+            }
+            FakeOutcome::Stop => {
+                if let Some(sources) = &self.shared.sources {
+                    write_reference_result(test_case, sources)?;
+                }
+            }
+            FakeOutcome::Hang => (),
+        }
+        let mut process = self.process.borrow_mut();
+        process.queries.push(outcome);
+        Ok(process.queries.len() as i64 - 1)
+    }
+
+    async fn is_query_stopped(&self, query_id: i64) -> Result<bool, String> {
+        let process = self.process.borrow();
+        if process.crash.is_some() {
+            return Err("Connection refused.".into());
+        }
+        let outcome = usize::try_from(query_id)
+            .ok()
+            .and_then(|index| process.queries.get(index))
+            .ok_or_else(|| format!("Unknown query id {query_id}."))?;
+        Ok(*outcome == FakeOutcome::Stop)
+    }
+}
+
+fn write_reference_result(
+    test_case: &TestCase,
+    sources: &HashMap<String, Table>,
+) -> Result<(), String> {
+    let plan = RefOperator::try_from(&test_case.query)?;
+    execute(&plan, sources)?.write_csv(&test_case.result_path)
+}
@@ -0,0 +1,49 @@
+//! Abstraction of the cluster the QueryExec stage runs the test cases on. `NesCluster` starts a
+//! real NebulaStream cluster, `FakeCluster` is an in-process stand-in for tests.
+pub mod fake_cluster;
+pub mod nes_cluster;
+
+use std::io;
+
+use crate::{
+    error::AchillesError, runner::runner_status::RunnerStatus, test_case_gen::test_case::TestCase,
+    LancerConfig,
+};
+
+/// Starts, stops and monitors the processes of a cluster.
+pub trait ClusterManager {
+    /// Starts all processes and waits until they are ready. Stops everything if the startup fails.
+    fn start_all(&mut self) -> Result<(), AchillesError>;
+
+    fn stop_all(&mut self);
+
+    fn health_check(&mut self) -> io::Result<RunnerStatus>;
+}
+
+/// Registers queries at a started cluster and polls their state.
+#[allow(async_fn_in_trait)]
+pub trait QueryRuntime {
+    /// Returns true if the cluster accepts requests.
+    async fn is_connected(&self) -> bool;
+
+    /// Registers the query of `test_case` and returns its query id.
+    async fn register_query(&self, test_case: &TestCase) -> Result<i64, String>;
+
+    /// Returns true once the query has stopped, i.e. written its whole result.
+    async fn is_query_stopped(&self, query_id: i64) -> Result<bool, String>;
+}
+
+/// Creates the cluster manager and the query runtime of every cluster of a test run. The factory is
+/// shared by the threads of all clusters. Fails if the cluster cannot be configured, e.g. because
+/// its ports exceed the port range.
+pub trait ClusterFactory: Sync {
+    type Manager: ClusterManager;
+    type Runtime: QueryRuntime;
+
+    fn cluster(
+        &self,
+        test_run_id: u32,
+        config: &LancerConfig,
+        cluster_id: u16,
+    ) -> Result<(Self::Manager, Self::Runtime), AchillesError>;
+}
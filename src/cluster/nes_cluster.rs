@@ -0,0 +1,104 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use nes_rust_client::runtime::{
+    nebula_stream_runtime::{NebulaStreamRuntime, PlacementStrategy},
+    query_state::QueryState,
+};
+
+use crate::{
+    error::AchillesError,
+    runner::{runner::Runner, runner_status::RunnerStatus},
+    test_case_gen::test_case::TestCase,
+    LancerConfig,
+};
+
+use super::{ClusterFactory, ClusterManager, QueryRuntime};
+
+/// NebulaStream clusters started from the configs of the StreamGen stage.
+pub struct NesCluster;
+
+impl ClusterFactory for NesCluster {
+    type Manager = Runner;
+    type Runtime = NebulaStreamRuntime;
+
+    fn cluster(
+        &self,
+        test_run_id: u32,
+        config: &LancerConfig,
+        cluster_id: u16,
+    ) -> Result<(Runner, NebulaStreamRuntime), AchillesError> {
+        let net_config = config
+            .net_config
+            .cluster(cluster_id)
+            .map_err(AchillesError::Config)?;
+        let path_config = &config.path_config;
+        let mut runner_config = config.runner_config.clone();
+        runner_config.coordinator_config_path =
+            Some(path_config.cluster_coordinator_config(test_run_id, cluster_id));
+        runner_config.worker_config_path =
+            files_in_dir(&path_config.cluster_worker_configs(test_run_id, cluster_id));
+        runner_config.coordinator_rest_address = Some(net_config.coordinator_rest_address());
+        runner_config.coordinator_rpc_address = Some(net_config.coordinator_rpc_address());
+        let runtime = NebulaStreamRuntime::new(
+            net_config.coord_ip.to_string(),
+            net_config.coord_rest_port.into(),
+        );
+        Ok((Runner::new(runner_config), runtime))
+    }
+}
+
+impl ClusterManager for Runner {
+    fn start_all(&mut self) -> Result<(), AchillesError> {
+        Runner::start_all(self)
+    }
+
+    fn stop_all(&mut self) {
+        Runner::stop_all(self)
+    }
+
+    fn health_check(&mut self) -> io::Result<RunnerStatus> {
+        Runner::health_check(self)
+    }
+}
+
+impl QueryRuntime for NebulaStreamRuntime {
+    async fn is_connected(&self) -> bool {
+        self.check_connection().await
+    }
+
+    async fn register_query(&self, test_case: &TestCase) -> Result<i64, String> {
+        self.execute_query(&test_case.query, PlacementStrategy::BottomUp)
+            .await
+            .map_err(|err| err.to_string())
+    }
+
+    async fn is_query_stopped(&self, query_id: i64) -> Result<bool, String> {
+        match self.query_status(query_id).await {
+            Ok(Some(status)) => Ok(status == QueryState::Stopped),
+            Ok(None) => Err(format!("Unknown query id {query_id}.")),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
+
+fn files_in_dir(path: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if !path.is_dir() {
+        log::warn!("{:?} is not a dir.", path);
+        return files;
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        log::warn!("Failed to read {:?}", path);
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files
+}
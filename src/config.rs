@@ -1,18 +1,25 @@
 use std::{
-    net::Ipv4Addr,
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
     time::Duration,
 };
 
 use crate::{
+    eval::{evaluator::ColumnAliases, tolerance::Tolerance},
+    expr_gen::expr_gen::{DataValues, ExprGenConfig, OperatorWeights},
     nes_opt_config::NesOptConfig,
     nes_query_comp_config::NesQueryCompilerConfig,
-    runner::runner_config::{OutputIO, RunnerConfig},
+    runner::runner_config::{OutputIO, ReadinessConfig, RunnerConfig},
     stages::Stages,
+    stream_gen::data_generator::TimeStampDisorder,
     test_case_gen::oracle::QueryGenStrategy,
 };
 
+#[derive(Clone)]
 pub struct TestConfig {
+    /// Master seed from which the seeds of all random generators are derived.
+    pub seed: u64,
     pub oracles: Vec<QueryGenStrategy>,
     pub test_run_count: u32,
     pub oracle_reps: u32,
@@ -20,14 +27,50 @@ pub struct TestConfig {
     pub field_count: u32,
     pub record_count: u32,
     pub physical_source_count: u32,
+    /// Number of random logical sources if there is no `schema_file`.
+    pub source_count: u32,
+    /// Yaml file with the logical sources, their fields and field generators. Without a schema
+    /// file `source_count` random sources are generated.
+    pub schema_file: Option<PathBuf>,
     pub predicate_depth: u32,
+    /// Relative weights of the operators in generated expressions.
+    pub operator_weights: OperatorWeights,
+    /// Avoid integer division by zero and integer `Power` in generated expressions.
+    pub guard_undefined_ops: bool,
+    /// Probability that the operands of a binary operation have different types.
+    pub mixed_type_ratio: f64,
+    /// Probability that a generated field value or literal is an edge value of its type, like 0,
+    /// MAX or NaN.
+    pub edge_value_ratio: f64,
+    /// Probability that a literal is a value of a field in the generated data.
+    pub data_literal_ratio: f64,
+    /// Out of order, late and duplicate time stamps in the generated data.
+    pub time_stamp_disorder: TimeStampDisorder,
+    /// Compare every result with the result of the reference interpreter in the Evaluation stage.
+    pub reference_check: bool,
+    /// Field names of the other results that are compared with a differently named field of the
+    /// origin result, per oracle.
+    pub column_aliases: HashMap<QueryGenStrategy, ColumnAliases>,
+    /// Tolerance of float columns in the Evaluation stage.
+    pub tolerance: Tolerance,
+    /// Tolerance of float columns per oracle, instead of `tolerance`.
+    pub tolerance_overrides: HashMap<QueryGenStrategy, Tolerance>,
 }
 
+/// Oracles whose tolerance is `Tolerance::average` of the `tolerance` unless it is overridden.
+pub const AVERAGE_ORACLES: [QueryGenStrategy; 2] =
+    [QueryGenStrategy::AggAvg, QueryGenStrategy::KeyAggAvg];
+
+#[derive(Clone)]
 pub struct LancerConfig {
     pub path_config: FilePathConfig,
     pub test_case_timeout: Duration,
     pub runner_config: RunnerConfig,
     pub skip_to_stage: Stages,
+    pub stop_after_stage: Stages,
+    /// Keep the files of an interrupted campaign, skip finished test runs and only execute the test
+    /// sets without a checkpoint.
+    pub resume: bool,
     pub test_config: TestConfig,
     pub net_config: NetworkConfig,
     pub opt_config: NesOptConfig,
@@ -39,6 +82,11 @@ pub struct NetworkConfig {
     pub coord_ip: Ipv4Addr,
     pub coord_rest_port: u16,
     pub coord_rpc_port: u16,
+    /// Number of NebulaStream clusters that execute test sets concurrently.
+    pub cluster_count: u16,
+    /// Distance between the ports of two clusters. Cluster `c` uses the configured ports plus
+    /// `c * cluster_port_stride`.
+    pub cluster_port_stride: u16,
 }
 
 impl Default for NetworkConfig {
@@ -47,15 +95,88 @@ impl Default for NetworkConfig {
             coord_ip: Ipv4Addr::LOCALHOST,
             coord_rest_port: 8000,
             coord_rpc_port: 4000,
+            cluster_count: 1,
+            cluster_port_stride: 100,
         }
     }
 }
 
+impl NetworkConfig {
+    /// Returns the network config of cluster `cluster_id`. Cluster 0 uses the configured ports.
+    /// Fails if the ports of the cluster exceed the port range.
+    pub fn cluster(&self, cluster_id: u16) -> Result<NetworkConfig, String> {
+        let port = |port: u16| {
+            cluster_id
+                .checked_mul(self.cluster_port_stride)
+                .and_then(|offset| port.checked_add(offset))
+                .ok_or_else(|| format!("The ports of cluster {cluster_id} exceed the port range."))
+        };
+        Ok(NetworkConfig {
+            coord_rest_port: port(self.coord_rest_port)?,
+            coord_rpc_port: port(self.coord_rpc_port)?,
+            ..self.clone()
+        })
+    }
+
+    pub fn coordinator_rest_address(&self) -> SocketAddr {
+        SocketAddr::new(self.coord_ip.into(), self.coord_rest_port)
+    }
+
+    pub fn coordinator_rpc_address(&self) -> SocketAddr {
+        SocketAddr::new(self.coord_ip.into(), self.coord_rpc_port)
+    }
+
+    /// Returns the RPC and data port of worker `worker_id`. Worker ports follow directly after the
+    /// RPC port of the coordinator.
+    pub fn worker_ports(&self, worker_id: u16) -> (u16, u16) {
+        let rpc_port = self.coord_rpc_port + 1 + 2 * worker_id;
+        (rpc_port, rpc_port + 1)
+    }
+
+    /// Checks that the REST port and the RPC, worker RPC and data ports of `worker_count` workers
+    /// of every cluster neither overlap with any port of any cluster nor exceed the port range.
+    pub fn check_ports(&self, worker_count: usize) -> Result<(), String> {
+        let rpc_port_count = 2 * worker_count + 1;
+        let mut ranges = Vec::with_capacity(2 * usize::from(self.cluster_count));
+        for cluster_id in 0..self.cluster_count {
+            let offset = usize::from(cluster_id) * usize::from(self.cluster_port_stride);
+            let rest_port = usize::from(self.coord_rest_port) + offset;
+            let rpc_port = usize::from(self.coord_rpc_port) + offset;
+            ranges.push((rest_port, rest_port, cluster_id, "REST"));
+            ranges.push((rpc_port, rpc_port + rpc_port_count - 1, cluster_id, "RPC"));
+        }
+        ranges.sort();
+        if let Some((_, last_port, cluster_id, kind)) = ranges.iter().max_by_key(|range| range.1) {
+            if *last_port > u16::MAX.into() {
+                return Err(format!(
+                    "The {kind} ports of cluster {cluster_id} exceed the port range."
+                ));
+            }
+        }
+        // sorted by their first port, ranges are disjoint if every range ends before the next
+        for pair in ranges.windows(2) {
+            let [(_, end, lhs_id, lhs_kind), (start, _, rhs_id, rhs_kind)] = pair else {
+                unreachable!("windows(2) yields pairs");
+            };
+            if start <= end {
+                return Err(format!(
+                    "The {lhs_kind} ports of cluster {lhs_id} overlap the {rhs_kind} ports of \
+                     cluster {rhs_id} at port {start} with {worker_count} workers per cluster."
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Default for TestConfig {
     fn default() -> Self {
         TestConfig {
+            seed: rand::random(),
             oracles: vec![
                 // QueryGenStrategy::Filter,
+                // QueryGenStrategy::FilterRewrite,
+                // QueryGenStrategy::FilterTernary,
                 // QueryGenStrategy::Map,
                 // QueryGenStrategy::AggMin,
                 // QueryGenStrategy::AggMax,
@@ -76,10 +197,52 @@ impl Default for TestConfig {
             field_count: 10,
             record_count: 500,
             physical_source_count: 5,
+            source_count: 5,
+            schema_file: None,
             test_run_count: 1,
             oracle_reps: 2,
             test_case_count: 5,
             predicate_depth: 3,
+            operator_weights: OperatorWeights::default(),
+            guard_undefined_ops: true,
+            mixed_type_ratio: 0.5,
+            edge_value_ratio: 0.1,
+            data_literal_ratio: 0.5,
+            time_stamp_disorder: TimeStampDisorder::default(),
+            reference_check: true,
+            column_aliases: HashMap::new(),
+            tolerance: Tolerance::default(),
+            tolerance_overrides: HashMap::from(
+                AVERAGE_ORACLES.map(|oracle| (oracle, Tolerance::default().average())),
+            ),
+        }
+    }
+}
+
+impl TestConfig {
+    pub fn column_aliases(&self, strategy: QueryGenStrategy) -> ColumnAliases {
+        self.column_aliases
+            .get(&strategy)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn tolerance(&self, strategy: QueryGenStrategy) -> Tolerance {
+        self.tolerance_overrides
+            .get(&strategy)
+            .copied()
+            .unwrap_or(self.tolerance)
+    }
+
+    pub fn expr_gen_config(&self) -> ExprGenConfig {
+        ExprGenConfig {
+            predicate_depth: self.predicate_depth,
+            operator_weights: self.operator_weights.clone(),
+            guard_undefined: self.guard_undefined_ops,
+            mixed_type_ratio: self.mixed_type_ratio,
+            edge_value_ratio: self.edge_value_ratio,
+            data_literal_ratio: self.data_literal_ratio,
+            data_values: DataValues::default(),
         }
     }
 }
@@ -92,6 +255,9 @@ impl Default for LancerConfig {
             coordinator_config_path: None,
             worker_config_path: Vec::new(),
             output_io: OutputIO::Null,
+            coordinator_rest_address: None,
+            coordinator_rpc_address: None,
+            readiness: ReadinessConfig::default(),
         };
 
         LancerConfig {
@@ -100,6 +266,8 @@ impl Default for LancerConfig {
             test_case_timeout: Duration::from_secs(20),
             runner_config,
             skip_to_stage: Stages::default(),
+            stop_after_stage: Stages::Evaluation,
+            resume: false,
             test_config: TestConfig::default(),
             net_config: NetworkConfig::default(),
             opt_config: NesOptConfig::default(),
@@ -108,6 +276,14 @@ impl Default for LancerConfig {
     }
 }
 
+impl LancerConfig {
+    /// Returns true if `stage` lies between `skip_to_stage` and `stop_after_stage`.
+    pub fn is_stage_enabled(&self, stage: Stages) -> bool {
+        self.skip_to_stage <= stage && stage <= self.stop_after_stage
+    }
+}
+
+#[derive(Clone)]
 pub struct FilePathConfig {
     pub base: PathBuf,
     pub test_run: PathBuf,
@@ -115,9 +291,14 @@ pub struct FilePathConfig {
     pub results: PathBuf,
     pub coordinator_config_file: PathBuf,
     pub worker_configs: PathBuf,
+    pub clusters: PathBuf,
+    pub checkpoints: PathBuf,
+    pub completed_stage_file: PathBuf,
+    pub seed_file: PathBuf,
     pub test_sets_file: PathBuf,
     pub test_set_execs_file: PathBuf,
     pub test_set_results_file: PathBuf,
+    pub diff_reports_file: PathBuf,
 }
 
 impl Default for FilePathConfig {
@@ -129,9 +310,14 @@ impl Default for FilePathConfig {
             results: PathBuf::from("results"),
             coordinator_config_file: PathBuf::from("coordinator.yml"),
             worker_configs: PathBuf::from("workers"),
+            clusters: PathBuf::from("clusters"),
+            checkpoints: PathBuf::from("checkpoints"),
+            completed_stage_file: PathBuf::from("completed_stage"),
+            seed_file: PathBuf::from("seed"),
             test_sets_file: PathBuf::from("test_sets.yml"),
             test_set_execs_file: PathBuf::from("test_set_execs.yml"),
             test_set_results_file: PathBuf::from("test_set_results.yml"),
+            diff_reports_file: PathBuf::from("diff_reports.yml"),
         }
     }
 }
@@ -141,6 +327,11 @@ impl FilePathConfig {
         &self.base
     }
 
+    /// File with the master seed of the campaign.
+    pub fn seed(&self) -> PathBuf {
+        self.base.join(&self.seed_file)
+    }
+
     pub fn test_run(&self, test_run_id: u32) -> PathBuf {
         let name = format!("{}-{}", self.test_run.display(), test_run_id);
         self.base.join(name)
@@ -159,6 +350,43 @@ impl FilePathConfig {
             .join(&self.coordinator_config_file)
     }
 
+    /// Directory with the coordinator and worker configs of cluster `cluster_id`. Cluster 0 uses
+    /// the configs in the test run directory.
+    pub fn cluster(&self, test_run_id: u32, cluster_id: u16) -> PathBuf {
+        let test_run = self.test_run(test_run_id);
+        if cluster_id == 0 {
+            return test_run;
+        }
+        test_run
+            .join(&self.clusters)
+            .join(format!("cluster-{cluster_id}"))
+    }
+
+    pub fn cluster_worker_configs(&self, test_run_id: u32, cluster_id: u16) -> PathBuf {
+        self.cluster(test_run_id, cluster_id)
+            .join(&self.worker_configs)
+    }
+
+    pub fn cluster_coordinator_config(&self, test_run_id: u32, cluster_id: u16) -> PathBuf {
+        self.cluster(test_run_id, cluster_id)
+            .join(&self.coordinator_config_file)
+    }
+
+    /// Directory with one `TestSetExec` checkpoint per executed test set.
+    pub fn checkpoints(&self, test_run_id: u32) -> PathBuf {
+        self.test_run(test_run_id).join(&self.checkpoints)
+    }
+
+    pub fn test_set_checkpoint(&self, test_run_id: u32, test_set_id: u32) -> PathBuf {
+        self.checkpoints(test_run_id)
+            .join(format!("test-set{test_set_id}.yml"))
+    }
+
+    /// File with the last stage a test run completed.
+    pub fn completed_stage(&self, test_run_id: u32) -> PathBuf {
+        self.test_run(test_run_id).join(&self.completed_stage_file)
+    }
+
     pub fn test_sets(&self, test_run_id: u32) -> PathBuf {
         self.test_run(test_run_id).join(&self.test_sets_file)
     }
@@ -170,5 +398,9 @@ impl FilePathConfig {
     pub fn test_set_results(&self, test_run_id: u32) -> PathBuf {
         self.test_run(test_run_id).join(&self.test_set_results_file)
     }
-}
 
+    /// Row-level differences of the test cases with a `Diff`, next to the test set results.
+    pub fn diff_reports(&self, test_run_id: u32) -> PathBuf {
+        self.test_run(test_run_id).join(&self.diff_reports_file)
+    }
+}
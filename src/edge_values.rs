@@ -0,0 +1,102 @@
+//! Pool of interesting values per `NesType`, shared by the data generator and the literal generator.
+//! Values drawn uniformly from the whole range of a type almost never hit the boundaries of the
+//! type or special float values, although most overflow and comparison bugs hide there.
+use nes_types::{FloatType, IntType, NesType};
+
+/// Returns the edge values of `data_type` in the format of the data files and query literals. The
+/// pool is empty for types without literals.
+pub fn edge_values(data_type: NesType) -> Vec<String> {
+    match data_type {
+        NesType::Undefined | NesType::Char => Vec::new(),
+        NesType::Bool => vec!["true".to_string(), "false".to_string()],
+        NesType::Int(int_type) => int_edge_values(int_type),
+        NesType::Float(FloatType::Bit32) => float_edge_values([
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            f32::MIN,
+            f32::MAX,
+            f32::MIN_POSITIVE,
+            f32::EPSILON,
+            // smallest subnormal
+            f32::from_bits(1),
+            f32::NAN,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+        ]),
+        NesType::Float(FloatType::Bit64) => float_edge_values([
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            f64::MIN,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::EPSILON,
+            // smallest subnormal
+            f64::from_bits(1),
+            f64::NAN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ]),
+    }
+}
+
+fn int_edge_values(int_type: IntType) -> Vec<String> {
+    let values = match int_type {
+        IntType::Signed8 => int_edges(i8::MIN.into(), i8::MAX.into()),
+        IntType::Unsigned8 => int_edges(u8::MIN.into(), u8::MAX.into()),
+        IntType::Signed16 => int_edges(i16::MIN.into(), i16::MAX.into()),
+        IntType::Unsigned16 => int_edges(u16::MIN.into(), u16::MAX.into()),
+        IntType::Signed32 => int_edges(i32::MIN.into(), i32::MAX.into()),
+        IntType::Unsigned32 => int_edges(u32::MIN.into(), u32::MAX.into()),
+        IntType::Signed64 => int_edges(i64::MIN.into(), i64::MAX.into()),
+        IntType::Unsigned64 => int_edges(u64::MIN.into(), u64::MAX.into()),
+    };
+    values.into_iter().map(|value| value.to_string()).collect()
+}
+
+/// 0, ±1, ±2 and the boundaries of `min..=max` and their neighbours.
+fn int_edges(min: i128, max: i128) -> Vec<i128> {
+    let mut values = vec![0, 1, -1, 2, -2, min, min + 1, max, max - 1];
+    values.retain(|value| (min..=max).contains(value));
+    values.sort();
+    values.dedup();
+    values
+}
+
+fn float_edge_values<T: ToString>(values: impl IntoIterator<Item = T>) -> Vec<String> {
+    values.into_iter().map(|value| value.to_string()).collect()
+}
+
+#[cfg(test)]
+mod edge_values_tests {
+    use nes_types::NesType;
+
+    use crate::reference::value::Value;
+
+    use super::edge_values;
+
+    #[test]
+    fn edge_values_are_valid() {
+        let types = [
+            NesType::u8(),
+            NesType::i8(),
+            NesType::u64(),
+            NesType::i64(),
+            NesType::f32(),
+            NesType::f64(),
+        ];
+        for data_type in types {
+            let values = edge_values(data_type);
+            assert!(values.contains(&"0".to_string()));
+            for value in values {
+                assert!(Value::parse(&value, data_type).is_ok(), "{value}");
+            }
+        }
+        assert!(edge_values(NesType::i8()).contains(&"-128".to_string()));
+        assert!(!edge_values(NesType::u8()).contains(&"-1".to_string()));
+        assert!(edge_values(NesType::f64()).contains(&"NaN".to_string()));
+    }
+}
@@ -0,0 +1,69 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::eval::eval_error::EvalError;
+
+/// Error of a stage or operation. A failing stage aborts its test run, but not the campaign.
+#[derive(Debug)]
+pub enum AchillesError {
+    /// The configuration or a requested test set or test case is invalid.
+    Config(String),
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A file does not contain the expected yaml or yaml could not be emitted.
+    Yaml {
+        path: PathBuf,
+        reason: String,
+    },
+    /// NebulaStream could not be started.
+    Runner(String),
+    /// The REST API of NebulaStream is not reachable.
+    Runtime(String),
+    Eval(EvalError),
+    /// A test set could not be reduced.
+    Reduce(String),
+}
+
+impl AchillesError {
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        AchillesError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn yaml(path: &Path, reason: impl fmt::Display) -> Self {
+        AchillesError::Yaml {
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl From<EvalError> for AchillesError {
+    fn from(err: EvalError) -> AchillesError {
+        AchillesError::Eval(err)
+    }
+}
+
+impl fmt::Display for AchillesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AchillesError::Config(msg) => write!(f, "Config error: {}", msg),
+            AchillesError::Io { path, source } => write!(f, "IO error in {:?}: {}", path, source),
+            AchillesError::Yaml { path, reason } => {
+                write!(f, "Yaml error in {:?}: {}", path, reason)
+            }
+            AchillesError::Runner(msg) => write!(f, "Runner error: {}", msg),
+            AchillesError::Runtime(msg) => write!(f, "Runtime error: {}", msg),
+            AchillesError::Eval(err) => write!(f, "Eval error: {}", err),
+            AchillesError::Reduce(msg) => write!(f, "Reduce error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AchillesError {}
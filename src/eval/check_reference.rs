@@ -0,0 +1,112 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    reference::{
+        interpreter::{execute, load_source_tables, Table},
+        plan::RefOperator,
+    },
+    test_case_exec::{TestCaseExec, TestCaseExecStatus, TestSetExec},
+    LancerConfig,
+};
+
+use super::{
+    check_results::{ReferenceOutcome, TestSetResult},
+    evaluator::{compare_files_with, CompareOptions, ResultRelation},
+};
+
+/// Compares the result of every successfully executed test case with the result of the reference
+/// interpreter. This finds bugs that affect the origin and the other queries in the same way and
+/// are therefore invisible to the metamorphic oracles.
+pub fn check_references(
+    test_run_id: u32,
+    config: &LancerConfig,
+    test_set_execs: &[TestSetExec],
+    test_set_results: &mut [TestSetResult],
+) {
+    log::info!("Checking results against the reference interpreter:");
+    let sources = match load_source_tables(test_run_id, config) {
+        Ok(sources) => sources,
+        Err(err) => {
+            log::warn!("Skipping reference check: {err}");
+            return;
+        }
+    };
+    for test_set_result in test_set_results.iter_mut() {
+        let Some(test_set) = test_set_execs
+            .iter()
+            .find(|test_set| test_set.id == test_set_result.id)
+        else {
+            continue;
+        };
+        let options = CompareOptions {
+            tolerance: config.test_config.tolerance(test_set.strategy),
+            ..Default::default()
+        };
+        test_set_result.origin_reference =
+            check_reference(config, &options, &test_set.origin, &sources);
+        for test_case_result in test_set_result.test_cases.iter_mut() {
+            let Some(test_case) = test_set
+                .others
+                .iter()
+                .find(|other| other.id() == test_case_result.id)
+            else {
+                continue;
+            };
+            test_case_result.reference = check_reference(config, &options, test_case, &sources);
+        }
+    }
+}
+
+fn check_reference(
+    config: &LancerConfig,
+    options: &CompareOptions,
+    test_case: &TestCaseExec,
+    sources: &HashMap<String, Table>,
+) -> Option<ReferenceOutcome> {
+    if test_case.status != TestCaseExecStatus::Success {
+        return None;
+    }
+    let outcome = match reference_relation(config, options, test_case, sources) {
+        Ok(relation) => ReferenceOutcome::Checked(relation),
+        Err(reason) => ReferenceOutcome::Unsupported(reason),
+    };
+    match &outcome {
+        ReferenceOutcome::Checked(ResultRelation::Diff) => log::warn!(
+            "Result of test case {} differs from the reference result.",
+            test_case.id()
+        ),
+        ReferenceOutcome::Checked(_) => {}
+        ReferenceOutcome::Unsupported(reason) => log::info!(
+            "Reference check of test case {} is unsupported: {reason}",
+            test_case.id()
+        ),
+    }
+    Some(outcome)
+}
+
+fn reference_relation(
+    config: &LancerConfig,
+    options: &CompareOptions,
+    test_case: &TestCaseExec,
+    sources: &HashMap<String, Table>,
+) -> Result<ResultRelation, String> {
+    let plan = RefOperator::try_from(test_case.query())?;
+    if plan.has_window() && config.test_config.time_stamp_disorder.has_late_records() {
+        return Err("The reference interpreter does not drop late records in windows.".into());
+    }
+    let expected = execute(&plan, sources)?;
+    let path = reference_path(test_case.result_path());
+    expected.write_csv(&path)?;
+    compare_files_with(&path, test_case.result_path(), options)
+        .map(|comparison| comparison.relation)
+        .map_err(|err| err.to_string())
+}
+
+/// Path of the expected result file next to the result file, e.g. `test-set0-other1.csv` becomes
+/// `test-set0-other1.reference.csv`.
+pub fn reference_path(result_path: &Path) -> PathBuf {
+    result_path.with_extension("reference.csv")
+}
@@ -1,20 +1,20 @@
-use std::fs;
-use std::io::Write;
-
-use super::evaluator::*;
+use super::{evaluator::*, tolerance::FloatError};
 use nes_rust_client::query::stringify::stringify_query;
-use yaml_rust2::{yaml::Hash, Yaml, YamlEmitter, YamlLoader};
+use yaml_rust2::{yaml::Hash, Yaml};
 
 use crate::{
+    error::AchillesError,
     test_case_exec::{TestCaseExecStatus, TestSetExec},
     test_case_gen::{oracle::QueryGenStrategy, query_id::TestCaseId},
-    yaml_util::store_yaml_array,
+    yaml_util::{load_yaml_array, store_yaml_array},
     LancerConfig,
 };
 
 pub struct TestSetResult {
     pub id: u32,
     pub strategy: QueryGenStrategy,
+    /// Outcome of comparing the origin result with the result of the reference interpreter.
+    pub origin_reference: Option<ReferenceOutcome>,
     pub test_cases: Vec<TestCaseResult>,
 }
 
@@ -22,15 +22,69 @@ pub struct TestCaseResult {
     pub id: TestCaseId,
     pub query_string: String,
     pub relation: ResultRelation,
+    /// Outcome of comparing the result with the result of the reference interpreter.
+    pub reference: Option<ReferenceOutcome>,
+    /// Largest error of the float columns if the results match.
+    pub max_error: Option<FloatError>,
+}
+
+/// Outcome of the reference check of a single query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceOutcome {
+    /// Relation of the result to the result of the reference interpreter.
+    Checked(ResultRelation),
+    /// The reference interpreter cannot compute the result of the query, e.g. because it contains
+    /// an operator the interpreter does not implement.
+    Unsupported(String),
+}
+
+impl ReferenceOutcome {
+    pub fn is_diff(&self) -> bool {
+        *self == ReferenceOutcome::Checked(ResultRelation::Diff)
+    }
+
+    pub fn is_unsupported(&self) -> bool {
+        matches!(self, ReferenceOutcome::Unsupported(_))
+    }
 }
 
 // yaml
 
+impl Into<Yaml> for &ReferenceOutcome {
+    fn into(self) -> Yaml {
+        match self {
+            ReferenceOutcome::Checked(relation) => relation.into(),
+            ReferenceOutcome::Unsupported(reason) => {
+                let mut map: Hash = Hash::new();
+                map.insert(
+                    Yaml::String("unsupported".into()),
+                    Yaml::String(reason.clone()),
+                );
+                Yaml::Hash(map)
+            }
+        }
+    }
+}
+
+impl TryFrom<&Yaml> for ReferenceOutcome {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        match &value["unsupported"] {
+            Yaml::String(reason) => Ok(ReferenceOutcome::Unsupported(reason.clone())),
+            _ => ResultRelation::try_from(value).map(ReferenceOutcome::Checked),
+        }
+    }
+}
+
 impl Into<Yaml> for &TestSetResult {
     fn into(self) -> Yaml {
         let mut map: Hash = Hash::new();
         map.insert(Yaml::String("id".into()), Yaml::Integer(self.id.into()));
         map.insert(Yaml::String("strategy".into()), (&self.strategy).into());
+        if let Some(reference) = &self.origin_reference {
+            map.insert(Yaml::String("origin_reference".into()), reference.into());
+        }
         let queries = self.test_cases.iter().map(|q| q.into()).collect();
         map.insert(Yaml::String("test_cases".into()), Yaml::Array(queries));
         Yaml::Hash(map)
@@ -45,6 +99,7 @@ impl TryFrom<&Yaml> for TestSetResult {
             return Err("Failed to Parse TestCaseResult id.".to_string());
         };
         let strategy = (&value["strategy"]).try_into()?;
+        let origin_reference = parse_reference(&value["origin_reference"])?;
         let Yaml::Array(arr) = &value["test_cases"] else {
             return Err("Should be able to parse test_case field as Array.".to_string());
         };
@@ -55,6 +110,7 @@ impl TryFrom<&Yaml> for TestSetResult {
         Ok(TestSetResult {
             id: id as u32,
             strategy,
+            origin_reference,
             test_cases,
         })
     }
@@ -65,6 +121,12 @@ impl Into<Yaml> for &TestCaseResult {
         let mut map: Hash = Hash::new();
         map.insert(Yaml::String("id".into()), (&self.id).into());
         map.insert(Yaml::String("relation".into()), (&self.relation).into());
+        if let Some(reference) = &self.reference {
+            map.insert(Yaml::String("reference".into()), reference.into());
+        }
+        if let Some(max_error) = &self.max_error {
+            map.insert(Yaml::String("max_error".into()), max_error.into());
+        }
         map.insert(
             Yaml::String("query".into()),
             Yaml::String(self.query_string.clone()),
@@ -79,6 +141,11 @@ impl TryFrom<&Yaml> for TestCaseResult {
     fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
         let id = TestCaseId::try_from(&value["id"])?;
         let relation = ResultRelation::try_from(&value["relation"])?;
+        let reference = parse_reference(&value["reference"])?;
+        let max_error = match &value["max_error"] {
+            Yaml::BadValue => None,
+            max_error => Some(FloatError::try_from(max_error)?),
+        };
         let Yaml::String(query_string) = &value["query"] else {
             return Err("Unable to parse TestCaseResult: cannot read query.".into());
         };
@@ -86,51 +153,58 @@ impl TryFrom<&Yaml> for TestCaseResult {
             id,
             relation,
             query_string: query_string.to_string(),
+            reference,
+            max_error,
         })
     }
 }
 
+/// Reference outcomes are optional, they are missing if the reference check was disabled or the
+/// query was not executed successfully.
+fn parse_reference(value: &Yaml) -> Result<Option<ReferenceOutcome>, String> {
+    match value {
+        Yaml::BadValue => Ok(None),
+        value => ReferenceOutcome::try_from(value).map(Some),
+    }
+}
+
 pub fn write_test_set_results_to_file(
     test_run_id: u32,
     config: &LancerConfig,
     test_set_results: &[TestSetResult],
-) {
+) -> Result<(), AchillesError> {
     let path = config.path_config.test_set_results(test_run_id);
-    store_yaml_array(&path, test_set_results);
+    store_yaml_array(&path, test_set_results)
 }
 
 pub fn read_test_set_results_from_file(
     test_run_id: u32,
     config: &LancerConfig,
-) -> Vec<TestSetResult> {
-    let path = config.path_config.test_set_results(test_run_id);
-    let content = fs::read_to_string(path).expect("Should have been able to read the file!");
-    let docs = YamlLoader::load_from_str(&content).expect("Should have been able to parse Yaml.");
-    let doc = &docs.first().expect("Should have one element");
-    let Yaml::Array(arr) = doc else {
-        panic!("Should have been able to parse Yaml::Array.")
-    };
-    arr.iter()
-        .map(|yaml_obj| yaml_obj.try_into().expect("Should not fail to parse Yaml."))
-        .collect()
+) -> Result<Vec<TestSetResult>, AchillesError> {
+    load_yaml_array(&config.path_config.test_set_results(test_run_id))
 }
 
-pub fn check_test_sets(test_sets: &[TestSetExec]) -> Vec<TestSetResult> {
+pub fn check_test_sets(test_sets: &[TestSetExec], config: &LancerConfig) -> Vec<TestSetResult> {
     log::info!("Checking results for equivalence:");
     test_sets
         .iter()
         .map(|test_set| {
-            let test_cases = check_test_set(test_set);
+            let test_cases = check_test_set(test_set, config);
             TestSetResult {
                 id: test_set.id,
                 strategy: test_set.strategy,
+                origin_reference: None,
                 test_cases,
             }
         })
         .collect()
 }
 
-pub fn check_test_set(test_set: &TestSetExec) -> Vec<TestCaseResult> {
+pub fn check_test_set(test_set: &TestSetExec, config: &LancerConfig) -> Vec<TestCaseResult> {
+    let options = CompareOptions {
+        aliases: config.test_config.column_aliases(test_set.strategy),
+        tolerance: config.test_config.tolerance(test_set.strategy),
+    };
     let mut test_case_results = Vec::new();
     for test_case in test_set.others.iter() {
         if test_case.status != TestCaseExecStatus::Success {
@@ -146,28 +220,28 @@ pub fn check_test_set(test_set: &TestSetExec) -> Vec<TestCaseResult> {
             test_case.id(),
             stringify_query(test_case.query())
         );
-        let relation = match compare_files(test_set.origin.result_path(), test_case.result_path()) {
-            Ok(ResultRelation::Equal) => {
-                log::debug!("Result files are equal.");
-                ResultRelation::Equal
-            }
-            Ok(ResultRelation::Reordered) => {
-                log::debug!("Result files are reordered.");
-                ResultRelation::Reordered
-            }
-            Ok(ResultRelation::Diff) => {
-                log::warn!("Result files are not equal.");
-                ResultRelation::Diff
-            }
+        let comparison = match compare_files_with(
+            test_set.origin.result_path(),
+            test_case.result_path(),
+            &options,
+        ) {
+            Ok(comparison) => comparison,
             Err(err) => {
                 log::error!("{err}");
                 continue;
             }
         };
+        match comparison.relation {
+            ResultRelation::Equal => log::debug!("Result files are equal."),
+            ResultRelation::Reordered => log::debug!("Result files are reordered."),
+            ResultRelation::Diff => log::warn!("Result files are not equal."),
+        }
         let test_case_result = TestCaseResult {
             id: test_case.id(),
-            relation,
+            relation: comparison.relation,
             query_string: stringify_query(&test_case.query.query),
+            reference: None,
+            max_error: comparison.max_error,
         };
         test_case_results.push(test_case_result);
     }
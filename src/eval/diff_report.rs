@@ -0,0 +1,536 @@
+//! Row-level difference between the results of an origin and an other test case, so that a
+//! `ResultRelation::Diff` can be triaged without comparing the result files by hand.
+use std::{cmp::Ordering, path::Path};
+
+use csv::StringRecord;
+use yaml_rust2::{yaml::Hash, Yaml};
+
+use crate::{
+    test_case_exec::TestSetExec,
+    test_case_gen::query_id::TestCaseId,
+    yaml_util::{load_yaml_array, store_yaml_array},
+    AchillesError, LancerConfig,
+};
+
+use super::{
+    check_results::TestSetResult,
+    eval_error::EvalError,
+    evaluator::{
+        align_files, are_fields_equal, comp_typed_records, CompareOptions, EvalType, ResultRelation,
+    },
+    external_sort::{sort_records, SortedRecords, SORT_RUN_RECORDS},
+    tolerance::Tolerance,
+};
+
+/// Rows reported per kind of difference. The counts of a `RowDiff` cover all rows.
+const MAX_REPORTED_ROWS: usize = 100;
+
+/// Missing and extra rows that are searched for changed rows. Searching is quadratic in them.
+const MAX_CANDIDATE_ROWS: usize = 1_000;
+
+/// Row-level difference of a test case whose result relation is `Diff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffReport {
+    pub test_set_id: u32,
+    pub test_case_id: TestCaseId,
+    pub diff: RowDiff,
+}
+
+/// Multiset difference of the rows of the origin and the other result. Rows count as equal if all
+/// their fields are equal like in `compare_files`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RowDiff {
+    /// Rows of the origin result that are missing in the other result.
+    pub missing: Vec<Vec<String>>,
+    pub missing_count: usize,
+    /// Rows of the other result that are not in the origin result.
+    pub extra: Vec<Vec<String>>,
+    pub extra_count: usize,
+    pub changed: Vec<ChangedRow>,
+    pub changed_count: usize,
+}
+
+/// A missing and an extra row that agree in at least half of their columns, e.g. a window whose
+/// aggregate differs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedRow {
+    pub origin: Vec<String>,
+    pub other: Vec<String>,
+    pub columns: Vec<ColumnDiff>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDiff {
+    /// Field name from the header, e.g. `source$value`.
+    pub column: String,
+    pub origin: String,
+    pub other: String,
+    /// `other - origin` for numeric columns.
+    pub delta: Option<f64>,
+}
+
+/// Computes the multiset difference of the rows of two result files. Columns are matched by name
+/// after resolving the aliases like in `compare_files_with` and floats within the tolerance are
+/// equal. Rows of the other result are reported in the column order of the origin result.
+///
+/// Both results are sorted with the external merge sort and joined in sorted order, so memory
+/// stays bounded. Changed rows are only searched among the first `MAX_CANDIDATE_ROWS` missing and
+/// extra rows, the rows beyond are only counted.
+pub fn diff_files(
+    origin_path: &Path,
+    other_path: &Path,
+    options: &CompareOptions,
+) -> Result<RowDiff, EvalError> {
+    let alignment = align_files(origin_path, other_path, &options.aliases)?;
+    let types = &alignment.types;
+    let tolerance = &options.tolerance;
+    let columns = read_columns(origin_path)?;
+
+    let mut origin_records = sorted_records(origin_path, types, |record| record)?;
+    let mut other_records = sorted_records(other_path, types, |record| alignment.align(&record))?;
+    let mut missing = Unmatched::default();
+    let mut extra = Unmatched::default();
+    let mut origin_record = origin_records.next().transpose()?;
+    let mut other_record = other_records.next().transpose()?;
+    loop {
+        let ordering = match (&origin_record, &other_record) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(rec0), Some(rec1)) if records_equal(rec0, rec1, types, tolerance) => {
+                Ordering::Equal
+            }
+            (Some(rec0), Some(rec1)) => match comp_typed_records(rec0, rec1, types) {
+                Ordering::Greater => Ordering::Greater,
+                Ordering::Less | Ordering::Equal => Ordering::Less,
+            },
+        };
+        if ordering != Ordering::Greater {
+            if let (Ordering::Less, Some(record)) = (ordering, &origin_record) {
+                missing.push(to_row(record));
+            }
+            origin_record = origin_records.next().transpose()?;
+        }
+        if ordering != Ordering::Less {
+            if let (Ordering::Greater, Some(record)) = (ordering, &other_record) {
+                extra.push(to_row(record));
+            }
+            other_record = other_records.next().transpose()?;
+        }
+    }
+
+    // equal rows that are sorted apart, e.g. floats within the tolerance
+    let mut paired = 0;
+    missing.rows.retain(|row| {
+        let Some(index) = extra
+            .rows
+            .iter()
+            .position(|other| rows_equal(row, other, types, tolerance))
+        else {
+            return true;
+        };
+        extra.remove(index);
+        paired += 1;
+        false
+    });
+    let mut changed = Vec::new();
+    missing.rows.retain(|row| {
+        let best = extra
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, other)| other.len() == row.len())
+            .map(|(index, other)| (index, equal_column_count(row, other, types, tolerance)))
+            .filter(|&(_, equal)| 2 * equal >= row.len())
+            .max_by_key(|&(_, equal)| equal);
+        let Some((index, _)) = best else {
+            return true;
+        };
+        let other = extra.remove(index);
+        changed.push(changed_row(row.clone(), other, &columns, types, tolerance));
+        false
+    });
+
+    let changed_count = changed.len();
+    missing.count -= paired + changed_count;
+    missing.rows.truncate(MAX_REPORTED_ROWS);
+    extra.rows.truncate(MAX_REPORTED_ROWS);
+    changed.truncate(MAX_REPORTED_ROWS);
+    Ok(RowDiff {
+        missing: missing.rows,
+        missing_count: missing.count,
+        extra: extra.rows,
+        extra_count: extra.count,
+        changed,
+        changed_count,
+    })
+}
+
+/// Rows of one result without a match in the other result. Only the first `MAX_CANDIDATE_ROWS`
+/// are kept, `count` counts all of them.
+#[derive(Debug, Default)]
+struct Unmatched {
+    rows: Vec<Vec<String>>,
+    count: usize,
+}
+
+impl Unmatched {
+    fn push(&mut self, row: Vec<String>) {
+        self.count += 1;
+        if self.rows.len() < MAX_CANDIDATE_ROWS {
+            self.rows.push(row);
+        }
+    }
+
+    fn remove(&mut self, index: usize) -> Vec<String> {
+        self.count -= 1;
+        self.rows.remove(index)
+    }
+}
+
+/// Returns the column names of a result file.
+fn read_columns(path: &Path) -> Result<Vec<String>, EvalError> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let columns = rdr
+        .headers()?
+        .iter()
+        .map(|field| field.split(':').next().unwrap_or(field).to_string())
+        .collect();
+    Ok(columns)
+}
+
+/// Records of a result file, transformed by `map`, in the order of `comp_typed_records`.
+fn sorted_records(
+    path: &Path,
+    types: &[EvalType],
+    map: impl Fn(StringRecord) -> StringRecord,
+) -> Result<SortedRecords, EvalError> {
+    let records = csv::Reader::from_path(path)?
+        .into_records()
+        .map(move |record| record.map(&map));
+    sort_records(records, types, SORT_RUN_RECORDS)
+}
+
+fn to_row(record: &StringRecord) -> Vec<String> {
+    record.iter().map(str::to_string).collect()
+}
+
+/// Unparsable fields are only equal to the same string.
+fn fields_equal(field0: &str, field1: &str, data_type: EvalType, tolerance: &Tolerance) -> bool {
+    are_fields_equal(field0, field1, data_type, tolerance).unwrap_or(field0 == field1)
+}
+
+fn records_equal(
+    rec0: &StringRecord,
+    rec1: &StringRecord,
+    types: &[EvalType],
+    tolerance: &Tolerance,
+) -> bool {
+    rec0.len() == rec1.len()
+        && rec0
+            .iter()
+            .zip(rec1)
+            .zip(types)
+            .all(|((field0, field1), data_type)| {
+                fields_equal(field0, field1, *data_type, tolerance)
+            })
+}
+
+fn rows_equal(row0: &[String], row1: &[String], types: &[EvalType], tolerance: &Tolerance) -> bool {
+    row0.len() == row1.len() && equal_column_count(row0, row1, types, tolerance) == row0.len()
+}
+
+fn equal_column_count(
+    row0: &[String],
+    row1: &[String],
+    types: &[EvalType],
+    tolerance: &Tolerance,
+) -> usize {
+    row0.iter()
+        .zip(row1)
+        .zip(types)
+        .filter(|((field0, field1), data_type)| {
+            fields_equal(field0, field1, **data_type, tolerance)
+        })
+        .count()
+}
+
+fn changed_row(
+    origin: Vec<String>,
+    other: Vec<String>,
+    columns: &[String],
+    types: &[EvalType],
+    tolerance: &Tolerance,
+) -> ChangedRow {
+    let columns = origin
+        .iter()
+        .zip(&other)
+        .zip(columns.iter().zip(types))
+        .filter(|((field0, field1), (_, data_type))| {
+            !fields_equal(field0, field1, **data_type, tolerance)
+        })
+        .map(|((field0, field1), (column, &data_type))| ColumnDiff {
+            column: column.clone(),
+            origin: field0.clone(),
+            other: field1.clone(),
+            delta: delta(field0, field1, data_type),
+        })
+        .collect();
+    ChangedRow {
+        origin,
+        other,
+        columns,
+    }
+}
+
+fn delta(field0: &str, field1: &str, data_type: EvalType) -> Option<f64> {
+    match data_type {
+        EvalType::Int => {
+            let (a, b) = (field0.parse::<i128>().ok()?, field1.parse::<i128>().ok()?);
+            Some((b - a) as f64)
+        }
+        EvalType::Float32 | EvalType::Float64 => {
+            Some(field1.parse::<f64>().ok()? - field0.parse::<f64>().ok()?)
+        }
+        EvalType::Bool | EvalType::Text => None,
+    }
+}
+
+/// Computes the diff report of every test case whose result relation is `Diff`.
+pub fn diff_test_sets(
+    test_set_execs: &[TestSetExec],
+    test_set_results: &[TestSetResult],
+    config: &LancerConfig,
+) -> Vec<DiffReport> {
+    let mut reports = Vec::new();
+    for test_set_result in test_set_results {
+        let Some(test_set) = test_set_execs
+            .iter()
+            .find(|test_set| test_set.id == test_set_result.id)
+        else {
+            continue;
+        };
+        let options = CompareOptions {
+            aliases: config.test_config.column_aliases(test_set.strategy),
+            tolerance: config.test_config.tolerance(test_set.strategy),
+        };
+        for test_case_result in test_set_result.test_cases.iter() {
+            if test_case_result.relation != ResultRelation::Diff {
+                continue;
+            }
+            let Some(test_case) = test_set
+                .others
+                .iter()
+                .find(|other| other.id() == test_case_result.id)
+            else {
+                continue;
+            };
+            match diff_files(
+                test_set.origin.result_path(),
+                test_case.result_path(),
+                &options,
+            ) {
+                Ok(diff) => reports.push(DiffReport {
+                    test_set_id: test_set.id,
+                    test_case_id: test_case.id(),
+                    diff,
+                }),
+                Err(err) => log::warn!(
+                    "Unable to diff test case {} of test set {}: {err}",
+                    test_case.id(),
+                    test_set.id
+                ),
+            }
+        }
+    }
+    reports
+}
+
+pub fn write_diff_reports_to_file(
+    test_run_id: u32,
+    config: &LancerConfig,
+    diff_reports: &[DiffReport],
+) -> Result<(), AchillesError> {
+    store_yaml_array(&config.path_config.diff_reports(test_run_id), diff_reports)
+}
+
+pub fn read_diff_reports_from_file(
+    test_run_id: u32,
+    config: &LancerConfig,
+) -> Result<Vec<DiffReport>, AchillesError> {
+    load_yaml_array(&config.path_config.diff_reports(test_run_id))
+}
+
+// yaml
+
+fn key(name: &str) -> Yaml {
+    Yaml::String(name.into())
+}
+
+fn row_to_yaml(row: &[String]) -> Yaml {
+    Yaml::Array(
+        row.iter()
+            .map(|field| Yaml::String(field.clone()))
+            .collect(),
+    )
+}
+
+fn rows_to_yaml(rows: &[Vec<String>]) -> Yaml {
+    Yaml::Array(rows.iter().map(|row| row_to_yaml(row)).collect())
+}
+
+fn parse_string(value: &Yaml, name: &str) -> Result<String, String> {
+    match value {
+        Yaml::String(str) => Ok(str.clone()),
+        Yaml::Integer(int) => Ok(int.to_string()),
+        Yaml::Real(real) => Ok(real.clone()),
+        Yaml::Boolean(bool) => Ok(bool.to_string()),
+        _ => Err(format!(
+            "Failed to parse DiffReport: {name} should be a string."
+        )),
+    }
+}
+
+fn parse_count(value: &Yaml, name: &str) -> Result<usize, String> {
+    value
+        .as_i64()
+        .and_then(|count| usize::try_from(count).ok())
+        .ok_or_else(|| format!("Failed to parse DiffReport: {name} should be a count."))
+}
+
+fn parse_row(value: &Yaml, name: &str) -> Result<Vec<String>, String> {
+    let Yaml::Array(fields) = value else {
+        return Err(format!(
+            "Failed to parse DiffReport: {name} should be a row."
+        ));
+    };
+    fields
+        .iter()
+        .map(|field| parse_string(field, name))
+        .collect()
+}
+
+fn parse_rows(value: &Yaml, name: &str) -> Result<Vec<Vec<String>>, String> {
+    let Yaml::Array(rows) = value else {
+        return Err(format!(
+            "Failed to parse DiffReport: {name} should be an array."
+        ));
+    };
+    rows.iter().map(|row| parse_row(row, name)).collect()
+}
+
+impl Into<Yaml> for &DiffReport {
+    fn into(self) -> Yaml {
+        let diff = &self.diff;
+        let mut map = Hash::new();
+        map.insert(key("test_set"), Yaml::Integer(self.test_set_id.into()));
+        map.insert(key("test_case"), (&self.test_case_id).into());
+        map.insert(
+            key("missing_count"),
+            Yaml::Integer(diff.missing_count as i64),
+        );
+        map.insert(key("extra_count"), Yaml::Integer(diff.extra_count as i64));
+        map.insert(
+            key("changed_count"),
+            Yaml::Integer(diff.changed_count as i64),
+        );
+        map.insert(key("missing"), rows_to_yaml(&diff.missing));
+        map.insert(key("extra"), rows_to_yaml(&diff.extra));
+        let changed = diff.changed.iter().map(|row| row.into()).collect();
+        map.insert(key("changed"), Yaml::Array(changed));
+        Yaml::Hash(map)
+    }
+}
+
+impl TryFrom<&Yaml> for DiffReport {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        let Yaml::Integer(test_set_id) = value["test_set"] else {
+            return Err("Failed to parse DiffReport test_set.".into());
+        };
+        let test_case_id = TestCaseId::try_from(&value["test_case"])?;
+        let Yaml::Array(changed) = &value["changed"] else {
+            return Err("Failed to parse DiffReport: changed should be an array.".into());
+        };
+        let diff = RowDiff {
+            missing: parse_rows(&value["missing"], "missing")?,
+            missing_count: parse_count(&value["missing_count"], "missing_count")?,
+            extra: parse_rows(&value["extra"], "extra")?,
+            extra_count: parse_count(&value["extra_count"], "extra_count")?,
+            changed: changed
+                .iter()
+                .map(ChangedRow::try_from)
+                .collect::<Result<_, _>>()?,
+            changed_count: parse_count(&value["changed_count"], "changed_count")?,
+        };
+        Ok(DiffReport {
+            test_set_id: test_set_id as u32,
+            test_case_id,
+            diff,
+        })
+    }
+}
+
+impl Into<Yaml> for &ChangedRow {
+    fn into(self) -> Yaml {
+        let mut map = Hash::new();
+        map.insert(key("origin"), row_to_yaml(&self.origin));
+        map.insert(key("other"), row_to_yaml(&self.other));
+        let columns = self.columns.iter().map(|column| column.into()).collect();
+        map.insert(key("columns"), Yaml::Array(columns));
+        Yaml::Hash(map)
+    }
+}
+
+impl TryFrom<&Yaml> for ChangedRow {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        let Yaml::Array(columns) = &value["columns"] else {
+            return Err("Failed to parse DiffReport: columns should be an array.".into());
+        };
+        Ok(ChangedRow {
+            origin: parse_row(&value["origin"], "origin")?,
+            other: parse_row(&value["other"], "other")?,
+            columns: columns
+                .iter()
+                .map(ColumnDiff::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl Into<Yaml> for &ColumnDiff {
+    fn into(self) -> Yaml {
+        let mut map = Hash::new();
+        map.insert(key("column"), Yaml::String(self.column.clone()));
+        map.insert(key("origin"), Yaml::String(self.origin.clone()));
+        map.insert(key("other"), Yaml::String(self.other.clone()));
+        if let Some(delta) = self.delta {
+            map.insert(key("delta"), Yaml::Real(delta.to_string()));
+        }
+        Yaml::Hash(map)
+    }
+}
+
+impl TryFrom<&Yaml> for ColumnDiff {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        let delta = match &value["delta"] {
+            Yaml::BadValue => None,
+            delta => Some(
+                parse_string(delta, "delta")?
+                    .parse::<f64>()
+                    .map_err(|err| format!("Failed to parse DiffReport delta: {err}"))?,
+            ),
+        };
+        Ok(ColumnDiff {
+            column: parse_string(&value["column"], "column")?,
+            origin: parse_string(&value["origin"], "origin")?,
+            other: parse_string(&value["other"], "other")?,
+            delta,
+        })
+    }
+}
@@ -26,7 +26,7 @@ impl From<csv::Error> for EvalError {
 impl fmt::Display for EvalError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            EvalError::HeaderConflictError(err) => write!(f, "Failed to parse float: {}", err),
+            EvalError::HeaderConflictError(err) => write!(f, "Header conflict: {}", err),
             EvalError::ParseFloatError(err) => write!(f, "Failed to parse float: {}", err),
             EvalError::TypeExtractionError(msg) => write!(f, "Type extraction error: {}", msg),
             EvalError::TypeConversionError(msg) => write!(f, "Type conversion error: {}", msg),
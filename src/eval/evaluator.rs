@@ -1,9 +1,22 @@
-use std::{cmp::Ordering, error::Error, num::ParseFloatError, path::Path};
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, HashMap},
+    error::Error,
+    fmt::Write,
+    fs::File,
+    hash::{Hash, Hasher},
+    num::ParseFloatError,
+    path::Path,
+};
 
 use csv::StringRecord;
 use yaml_rust2::Yaml;
 
-use super::eval_error::EvalError;
+use super::{
+    eval_error::EvalError,
+    external_sort::{sort_records, SORT_RUN_RECORDS},
+    tolerance::{FloatError, FloatWidth, Tolerance},
+};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ResultRelation {
@@ -41,96 +54,494 @@ impl TryFrom<&Yaml> for ResultRelation {
     }
 }
 
-pub fn compare_files(path0: &Path, path1: &Path) -> Result<ResultRelation, EvalError> {
-    if !is_row_count_equal(path0, path1)? {
-        return Ok(ResultRelation::Diff);
+/// Maps the unqualified field names of the other result to the field names of the origin result,
+/// e.g. `ts` to `start` if the other query renamed the start of its windows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnAliases {
+    aliases: HashMap<String, String>,
+}
+
+impl ColumnAliases {
+    pub fn insert(&mut self, alias: impl Into<String>, name: impl Into<String>) {
+        self.aliases.insert(alias.into(), name.into());
     }
-    let res = are_files_equal(path0, path1)?;
-    if res == ResultRelation::Equal {
-        return Ok(res);
+
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map_or(name, String::as_str)
     }
-    return are_files_reordered(path0, path1);
 }
 
-pub fn is_row_count_equal(path0: &Path, path1: &Path) -> Result<bool, EvalError> {
-    let mut rdr0 = csv::Reader::from_path(path0)?;
-    let mut rdr1 = csv::Reader::from_path(path1)?;
-    Ok(rdr0.records().count() == rdr1.records().count())
+impl<A: Into<String>, N: Into<String>> FromIterator<(A, N)> for ColumnAliases {
+    fn from_iter<T: IntoIterator<Item = (A, N)>>(iter: T) -> Self {
+        let mut aliases = ColumnAliases::default();
+        for (alias, name) in iter {
+            aliases.insert(alias, name);
+        }
+        aliases
+    }
 }
 
-pub fn are_files_equal(path0: &Path, path1: &Path) -> Result<ResultRelation, EvalError> {
+/// How results are compared.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompareOptions {
+    pub aliases: ColumnAliases,
+    pub tolerance: Tolerance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub relation: ResultRelation,
+    /// Largest error of the float columns of matching rows. `None` if the results differ or have
+    /// no float columns.
+    pub max_error: Option<FloatError>,
+}
+
+pub fn compare_files(path0: &Path, path1: &Path) -> Result<ResultRelation, EvalError> {
+    compare_files_with(path0, path1, &CompareOptions::default())
+        .map(|comparison| comparison.relation)
+}
+
+/// Compares the result `path0` of the origin with the result `path1` of an other query. Columns
+/// are matched by their field name without the `source$` prefix after resolving the aliases of
+/// the other result, floats are compared with the tolerance. Results with permuted columns are at
+/// most `Reordered`.
+///
+/// Both files are read once in lockstep, which counts the rows, compares them in order and
+/// computes a multiset fingerprint of each result. Results whose columns do not match are only
+/// counted. Only if the fingerprints differ and the results have float columns, which might still
+/// be equal within the tolerance, both results are sorted with an external merge sort and compared
+/// once more.
+pub fn compare_files_with(
+    path0: &Path,
+    path1: &Path,
+    options: &CompareOptions,
+) -> Result<Comparison, EvalError> {
+    let diff = Comparison {
+        relation: ResultRelation::Diff,
+        max_error: None,
+    };
     let mut rdr0 = csv::Reader::from_path(path0)?;
     let mut rdr1 = csv::Reader::from_path(path1)?;
-    let iter = rdr0.records().zip(rdr1.records());
-    for (res0, res1) in iter {
-        let record0 = res0?;
-        let record1 = res1?;
-        log::trace!("{:?} == {:?}", record0, record1);
-        if !are_records_equal_string(&record0, &record1) {
-            return Ok(ResultRelation::Diff);
-        }
-    }
-    Ok(ResultRelation::Equal)
-}
-
-/// compares two String records for ordering
-/// The algorithm compares the first elements of both Records.
-/// If they are unequal we return the result.
-/// If they are equal we look at the second element and compare them.
-/// We repeat this process until one of the records runs out of elements.
-/// If this is the case it is the lesser records
-pub fn comp_records(rec0: &StringRecord, rec1: &StringRecord) -> Ordering {
-    let len0 = rec0.len();
-    let len1 = rec1.len();
-    let min_len = len0.min(len1);
-    for i in 0..min_len {
-        match rec0.get(i).cmp(&rec1.get(i)) {
+    let alignment = align_headers(&mut rdr0, &mut rdr1, &options.aliases);
+    let mut records0 = rdr0.into_records();
+    let mut records1 = rdr1.into_records();
+    let alignment = match alignment {
+        Ok(alignment) => alignment,
+        // results with different row counts differ, whatever their headers are
+        Err(err) => loop {
+            match (records0.next().transpose()?, records1.next().transpose()?) {
+                (Some(_), Some(_)) => continue,
+                (None, None) => return Err(err),
+                _ => return Ok(diff),
+            }
+        },
+    };
+    let types = &alignment.types;
+    let tolerance = &options.tolerance;
+    let has_floats = types.iter().any(EvalType::is_float);
+
+    let mut is_in_order = alignment.is_identity();
+    let mut max_error = FloatError::default();
+    let mut fingerprint0 = Fingerprint::default();
+    let mut fingerprint1 = Fingerprint::default();
+    loop {
+        let (rec0, rec1) = match (records0.next().transpose()?, records1.next().transpose()?) {
+            (Some(rec0), Some(rec1)) => (rec0, alignment.align(&rec1)),
+            (None, None) => break,
+            // different row counts
+            _ => return Ok(diff),
+        };
+        log::trace!("{:?} == {:?}", rec0, rec1);
+        if is_in_order {
+            if are_records_equal(&rec0, &rec1, types, tolerance)? {
+                max_error = max_error.max(record_error(&rec0, &rec1, types)?);
+            } else {
+                is_in_order = false;
+            }
+        }
+        fingerprint0.add(&rec0, types, tolerance);
+        fingerprint1.add(&rec1, types, tolerance);
+    }
+
+    let relation = if is_in_order {
+        ResultRelation::Equal
+    } else if fingerprint0.is_equal(&fingerprint1) {
+        // equal canonical values have no error beyond the precision of the column
+        max_error = FloatError::default();
+        ResultRelation::Reordered
+    } else if !has_floats {
+        return Ok(diff);
+    } else {
+        match max_sorted_error(path0, path1, &alignment, tolerance)? {
+            Some(error) => {
+                max_error = error;
+                ResultRelation::Reordered
+            }
+            None => return Ok(diff),
+        }
+    };
+    Ok(Comparison {
+        relation,
+        max_error: has_floats.then_some(max_error),
+    })
+}
+
+/// Order independent hash of the canonical values of the records of a result.
+#[derive(Debug, Default)]
+struct Fingerprint {
+    sum0: u64,
+    sum1: u64,
+    /// A NaN that is not equal to NaN makes the result unequal to any result.
+    has_unequal_value: bool,
+}
+
+impl Fingerprint {
+    fn add(&mut self, record: &StringRecord, types: &[EvalType], tolerance: &Tolerance) {
+        let mut hasher0 = DefaultHasher::new();
+        // a second independent hash makes collisions negligible
+        let mut hasher1 = DefaultHasher::new();
+        hasher1.write_u8(0xa5);
+        for (field, data_type) in record.iter().zip(types) {
+            let value = canonical_value(field, *data_type, tolerance);
+            if value == CanonicalValue::Unequal {
+                self.has_unequal_value = true;
+            }
+            value.hash(&mut hasher0);
+            value.hash(&mut hasher1);
+        }
+        self.sum0 = self.sum0.wrapping_add(hasher0.finish());
+        self.sum1 = self.sum1.wrapping_add(hasher1.finish());
+    }
+
+    fn is_equal(&self, other: &Fingerprint) -> bool {
+        !self.has_unequal_value
+            && !other.has_unequal_value
+            && (self.sum0, self.sum1) == (other.sum0, other.sum1)
+    }
+}
+
+/// Value of a field such that fields are equal in `are_fields_equal` with a tolerance of zero if
+/// their canonical values are equal.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum CanonicalValue<'a> {
+    Str(&'a str),
+    Bool(bool),
+    Float(u64),
+    Unequal,
+}
+
+fn canonical_value<'a>(
+    field: &'a str,
+    data_type: EvalType,
+    tolerance: &Tolerance,
+) -> CanonicalValue<'a> {
+    let float = |value: f64| {
+        if value.is_nan() {
+            if tolerance.nan_equals_nan {
+                CanonicalValue::Float(f64::NAN.to_bits())
+            } else {
+                CanonicalValue::Unequal
+            }
+        } else if value == 0.0 && tolerance.signed_zeros_equal {
+            CanonicalValue::Float(0)
+        } else {
+            CanonicalValue::Float(value.to_bits())
+        }
+    };
+    match data_type {
+        EvalType::Int => CanonicalValue::Str(field),
+        EvalType::Float32 => match field.parse::<f64>() {
+            // equal f32 values are 0 ulps apart even if their decimal strings differ
+            Ok(value) => float(f64::from(value as f32)),
+            Err(_) => CanonicalValue::Str(field),
+        },
+        EvalType::Float64 => match field.parse::<f64>() {
+            Ok(value) => float(value),
+            Err(_) => CanonicalValue::Str(field),
+        },
+        EvalType::Bool => match parse_bool(field) {
+            Ok(value) => CanonicalValue::Bool(value),
+            Err(_) => CanonicalValue::Str(field),
+        },
+        EvalType::Text => CanonicalValue::Str(field.trim_end_matches('\0')),
+    }
+}
+
+/// Orders records by the values of their fields, so that equal values with different strings,
+/// like `10` and `10.0`, `0` and `-0` or padded and unpadded text, are sorted alike and floats
+/// within the tolerance of each other are sorted next to each other. Unparsable fields are sorted
+/// by their strings after all parsable fields.
+pub(crate) fn comp_typed_records(
+    rec0: &StringRecord,
+    rec1: &StringRecord,
+    types: &[EvalType],
+) -> Ordering {
+    for ((field0, field1), data_type) in rec0.iter().zip(rec1.iter()).zip(types) {
+        match comp_fields(field0, field1, *data_type) {
             Ordering::Equal => continue,
             non_equal => return non_equal,
         }
     }
-    len0.cmp(&len1)
+    rec0.len().cmp(&rec1.len())
 }
 
-pub fn are_files_reordered(path0: &Path, path1: &Path) -> Result<ResultRelation, EvalError> {
-    let mut rdr0 = csv::Reader::from_path(path0)?;
-    let mut rdr1 = csv::Reader::from_path(path1)?;
+fn comp_fields(field0: &str, field1: &str, data_type: EvalType) -> Ordering {
+    match data_type {
+        EvalType::Int => comp_parsed(field0, field1, |field| field.parse::<i128>().ok()),
+        EvalType::Float32 | EvalType::Float64 => comp_parsed(field0, field1, |field| {
+            field
+                .parse::<f64>()
+                .ok()
+                .map(|value| float_key(value, data_type))
+        }),
+        EvalType::Bool => comp_parsed(field0, field1, |field| parse_bool(field).ok()),
+        EvalType::Text => field0
+            .trim_end_matches('\0')
+            .cmp(field1.trim_end_matches('\0')),
+    }
+}
 
-    let header0 = rdr0.headers()?;
-    let header1 = rdr1.headers()?;
-    let type_strings0 = extract_types(&header0)?;
-    let type_strings1 = extract_types(&header1)?;
-    let types0 = convert_types(&type_strings1)?;
-    let types1 = convert_types(&type_strings0)?;
-    // FIXME: This might lead to errors so
-    if types0 != types1 {
-        return Err(EvalError::HeaderConflictError(format!(
-            "Header {:?}, conflicts with header {:?}",
-            header0, header1
-        )));
+/// Compares parsable fields by their values and unparsable fields by their strings.
+fn comp_parsed<T: Ord>(field0: &str, field1: &str, parse: impl Fn(&str) -> Option<T>) -> Ordering {
+    match (parse(field0), parse(field1)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => field0.cmp(field1),
     }
+}
 
-    let mut data0: Vec<StringRecord> = rdr0
-        .records()
-        .collect::<Result<Vec<StringRecord>, csv::Error>>()?;
-    let mut data1: Vec<StringRecord> = rdr1
-        .records()
-        .collect::<Result<Vec<StringRecord>, csv::Error>>()?;
+/// Key of a float in the precision of its column with a single zero and a single NaN, ordered like
+/// `f64::total_cmp`.
+fn float_key(value: f64, data_type: EvalType) -> i64 {
+    let value = match data_type {
+        EvalType::Float32 => f64::from(value as f32),
+        _ => value,
+    };
+    let value = if value == 0.0 {
+        0.0
+    } else if value.is_nan() {
+        f64::NAN
+    } else {
+        value
+    };
+    let bits = value.to_bits() as i64;
+    bits ^ (((bits >> 63) as u64) >> 1) as i64
+}
 
-    data0.sort_by(comp_records);
-    data1.sort_by(comp_records);
+/// Compares the results in the order of `comp_typed_records`. Returns the largest error of the
+/// float columns or `None` if two records are not equal. The results must have the same number of
+/// rows.
+///
+/// Records that are sorted apart although they are equal within the tolerance, e.g. because the
+/// floats of a column are within the tolerance but the next column sorts them the other way, are
+/// matched with up to `MAX_PENDING_RECORDS` unmatched records of the other result.
+fn max_sorted_error(
+    path0: &Path,
+    path1: &Path,
+    alignment: &ColumnAlignment,
+    tolerance: &Tolerance,
+) -> Result<Option<FloatError>, EvalError> {
+    let types = &alignment.types;
+    let records0 = csv::Reader::from_path(path0)?.into_records();
+    let records1 = csv::Reader::from_path(path1)?
+        .into_records()
+        .map(|record| record.map(|record| alignment.align(&record)));
+    let sorted0 = sort_records(records0, types, SORT_RUN_RECORDS)?;
+    let sorted1 = sort_records(records1, types, SORT_RUN_RECORDS)?;
 
-    let iter = data0.iter().zip(data1.iter());
-    for (rec0, rec1) in iter {
+    let mut max_error = FloatError::default();
+    let mut pending0 = Vec::new();
+    let mut pending1 = Vec::new();
+    for (rec0, rec1) in sorted0.zip(sorted1) {
+        let (rec0, rec1) = (rec0?, rec1?);
         log::trace!("{:?} == {:?}", rec0, rec1);
-        if !are_records_equal(&rec0, &rec1, &types0)? {
-            return Ok(ResultRelation::Diff);
+        if are_records_equal(&rec0, &rec1, types, tolerance)? {
+            max_error = max_error.max(record_error(&rec0, &rec1, types)?);
+            continue;
+        }
+        for error in [
+            match_pending(rec0, &mut pending0, &mut pending1, types, tolerance)?,
+            match_pending(rec1, &mut pending1, &mut pending0, types, tolerance)?,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            max_error = max_error.max(error);
+        }
+        if pending0.len().max(pending1.len()) > MAX_PENDING_RECORDS {
+            return Ok(None);
         }
     }
-    Ok(ResultRelation::Reordered)
+    Ok((pending0.is_empty() && pending1.is_empty()).then_some(max_error))
 }
 
-fn extract_types(record: &StringRecord) -> Result<Vec<String>, EvalError> {
+/// Unmatched records per result of `max_sorted_error`.
+const MAX_PENDING_RECORDS: usize = 1_000;
+
+/// Matches `record` with an equal record of `other_pending` and returns their error, or adds it to
+/// `pending`.
+fn match_pending(
+    record: StringRecord,
+    pending: &mut Vec<StringRecord>,
+    other_pending: &mut Vec<StringRecord>,
+    types: &[EvalType],
+    tolerance: &Tolerance,
+) -> Result<Option<FloatError>, EvalError> {
+    let mut matched = None;
+    for (index, other) in other_pending.iter().enumerate() {
+        if are_records_equal(&record, other, types, tolerance)? {
+            matched = Some(index);
+            break;
+        }
+    }
+    match matched {
+        Some(index) => {
+            let other = other_pending.swap_remove(index);
+            Ok(Some(record_error(&record, &other, types)?))
+        }
+        None => {
+            pending.push(record);
+            Ok(None)
+        }
+    }
+}
+
+fn record_error(
+    rec0: &StringRecord,
+    rec1: &StringRecord,
+    types: &[EvalType],
+) -> Result<FloatError, EvalError> {
+    let mut max_error = FloatError::default();
+    for ((field0, field1), data_type) in rec0.iter().zip(rec1.iter()).zip(types) {
+        if let Some(width) = data_type.float_width() {
+            let error = FloatError::between(field0.parse()?, field1.parse()?, width);
+            max_error = max_error.max(error);
+        }
+    }
+    Ok(max_error)
+}
+
+/// Column `i` of the origin result is column `indices[i]` of the other result.
+pub(crate) struct ColumnAlignment {
+    indices: Vec<usize>,
+    /// Types of the columns of the origin result.
+    pub(crate) types: Vec<EvalType>,
+}
+
+impl ColumnAlignment {
+    fn is_identity(&self) -> bool {
+        self.indices
+            .iter()
+            .enumerate()
+            .all(|(i, &index)| i == index)
+    }
+
+    /// Reorders a record of the other result like the columns of the origin result.
+    pub(crate) fn align(&self, record: &StringRecord) -> StringRecord {
+        self.indices
+            .iter()
+            .map(|&index| record.get(index).unwrap_or_default())
+            .collect()
+    }
+}
+
+pub(crate) fn align_files(
+    path0: &Path,
+    path1: &Path,
+    aliases: &ColumnAliases,
+) -> Result<ColumnAlignment, EvalError> {
+    let mut rdr0 = csv::Reader::from_path(path0)?;
+    let mut rdr1 = csv::Reader::from_path(path1)?;
+    align_headers(&mut rdr0, &mut rdr1, aliases)
+}
+
+fn align_headers(
+    rdr0: &mut csv::Reader<File>,
+    rdr1: &mut csv::Reader<File>,
+    aliases: &ColumnAliases,
+) -> Result<ColumnAlignment, EvalError> {
+    let columns0 = parse_columns(rdr0.headers()?, &ColumnAliases::default())?;
+    let columns1 = parse_columns(rdr1.headers()?, aliases)?;
+    align_columns(&columns0, &columns1)
+}
+
+/// Unqualified field name and type of a column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Column {
+    name: String,
+    data_type: EvalType,
+}
+
+fn parse_columns(header: &StringRecord, aliases: &ColumnAliases) -> Result<Vec<Column>, EvalError> {
+    let types = convert_types(&extract_types(header)?)?;
+    let columns = header
+        .iter()
+        .zip(types)
+        .map(|(field, data_type)| {
+            let qualified = field.split(':').next().unwrap_or(field);
+            let name = qualified.rsplit('$').next().unwrap_or(qualified);
+            Column {
+                name: aliases.resolve(name).to_string(),
+                data_type,
+            }
+        })
+        .collect();
+    Ok(columns)
+}
+
+/// Matches the columns of the other result to the columns of the origin result by name. Fails if
+/// a column has no match or the matched columns have different types.
+fn align_columns(columns0: &[Column], columns1: &[Column]) -> Result<ColumnAlignment, EvalError> {
+    let mut indices = Vec::with_capacity(columns0.len());
+    let mut conflicts = String::new();
+    for column in columns0 {
+        // duplicate names are matched in order
+        let index = columns1
+            .iter()
+            .enumerate()
+            .position(|(index, other)| other.name == column.name && !indices.contains(&index));
+        match index {
+            Some(index) if columns1[index].data_type == column.data_type => indices.push(index),
+            Some(index) => {
+                let _ = write!(
+                    conflicts,
+                    " {} is {:?} but {:?} in the other result;",
+                    column.name, column.data_type, columns1[index].data_type
+                );
+                indices.push(index);
+            }
+            None => {
+                let _ = write!(
+                    conflicts,
+                    " {} is missing in the other result;",
+                    column.name
+                );
+            }
+        }
+    }
+    for (index, column) in columns1.iter().enumerate() {
+        if !indices.contains(&index) {
+            let _ = write!(
+                conflicts,
+                " {} is missing in the origin result;",
+                column.name
+            );
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(EvalError::HeaderConflictError(format!(
+            "Columns do not match:{}",
+            conflicts.trim_end_matches(';')
+        )));
+    }
+    Ok(ColumnAlignment {
+        indices,
+        types: columns0.iter().map(|column| column.data_type).collect(),
+    })
+}
+
+pub(crate) fn extract_types(record: &StringRecord) -> Result<Vec<String>, EvalError> {
     let mut types = Vec::new();
 
     for field in record.iter() {
@@ -154,19 +565,44 @@ fn extract_types(record: &StringRecord) -> Result<Vec<String>, EvalError> {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum EvalType {
+pub(crate) enum EvalType {
     Int,
-    Float,
+    Float32,
+    Float64,
+    Bool,
+    Text,
+}
+
+impl EvalType {
+    pub(crate) fn float_width(&self) -> Option<FloatWidth> {
+        match self {
+            EvalType::Float32 => Some(FloatWidth::Bit32),
+            EvalType::Float64 => Some(FloatWidth::Bit64),
+            EvalType::Int | EvalType::Bool | EvalType::Text => None,
+        }
+    }
+
+    fn is_float(&self) -> bool {
+        self.float_width().is_some()
+    }
 }
 
-fn convert_types(string_types: &[String]) -> Result<Vec<EvalType>, EvalError> {
+/// Converts the type strings of a NebulaStream result header. Single chars, fixed-size char arrays
+/// like `Char(8)` or `CHAR[8]` and variable sized `TEXT` are all compared as text.
+pub(crate) fn convert_types(string_types: &[String]) -> Result<Vec<EvalType>, EvalError> {
     string_types
         .iter()
         .map(|str_type| match str_type.to_lowercase().as_str() {
             "integer(64 bits)" | "integer(32 bits)" | "integer(16 bits)" | "integer(8 bits)" => {
                 Ok(EvalType::Int)
             }
-            "float(32 bits)" | "float(64 bits)" => Ok(EvalType::Float),
+            "float(32 bits)" => Ok(EvalType::Float32),
+            "float(64 bits)" => Ok(EvalType::Float64),
+            "boolean" | "bool" => Ok(EvalType::Bool),
+            "char" | "text" => Ok(EvalType::Text),
+            char_array if char_array.starts_with("char(") || char_array.starts_with("char[") => {
+                Ok(EvalType::Text)
+            }
             err => Err(EvalError::TypeConversionError(format!(
                 "Unknown type string {err}"
             ))),
@@ -192,10 +628,11 @@ fn are_records_equal(
     rec0: &StringRecord,
     rec1: &StringRecord,
     types: &[EvalType],
+    tolerance: &Tolerance,
 ) -> Result<bool, EvalError> {
     let rec_iter = rec0.iter().zip(rec1.iter()).zip(types.iter());
     for ((field0, field1), data_type) in rec_iter {
-        let are_files_equal = are_fields_equal(field0, field1, *data_type)?;
+        let are_files_equal = are_fields_equal(field0, field1, *data_type, tolerance)?;
         if !are_files_equal {
             return Ok(false);
         }
@@ -203,11 +640,18 @@ fn are_records_equal(
     Ok(true)
 }
 
-fn are_fields_equal(field0: &str, field1: &str, data_type: EvalType) -> Result<bool, EvalError> {
-    let epsilon = 1e-3;
+pub(crate) fn are_fields_equal(
+    field0: &str,
+    field1: &str,
+    data_type: EvalType,
+    tolerance: &Tolerance,
+) -> Result<bool, EvalError> {
     match data_type {
         EvalType::Int => Ok(int_equal(field0, field1)),
-        EvalType::Float => Ok(float_equal(field0, field1, epsilon)?),
+        EvalType::Float32 => Ok(float_equal(field0, field1, FloatWidth::Bit32, tolerance)?),
+        EvalType::Float64 => Ok(float_equal(field0, field1, FloatWidth::Bit64, tolerance)?),
+        EvalType::Bool => Ok(bool_equal(field0, field1)?),
+        EvalType::Text => Ok(text_equal(field0, field1)),
     }
 }
 
@@ -215,18 +659,63 @@ fn int_equal(field0: &str, field1: &str) -> bool {
     field1 == field0
 }
 
-fn float_equal(field0: &str, field1: &str, epsilon: f64) -> Result<bool, EvalError> {
+fn float_equal(
+    field0: &str,
+    field1: &str,
+    width: FloatWidth,
+    tolerance: &Tolerance,
+) -> Result<bool, EvalError> {
     let a = field0.parse::<f64>()?;
     let b = field1.parse::<f64>()?;
-    Ok((a - b).abs() < epsilon)
+    Ok(tolerance.accepts(a, b, width))
 }
 
-fn are_records_equal_string(rec0: &StringRecord, rec1: &StringRecord) -> bool {
-    let rec_iter = rec0.iter().zip(rec1.iter());
-    for (field0, field1) in rec_iter {
-        if field0 != field1 {
-            return false;
-        }
+/// NebulaStream writes bools as `1` and `0`, the reference interpreter as well, but other engines
+/// may write `true` and `false`.
+fn bool_equal(field0: &str, field1: &str) -> Result<bool, EvalError> {
+    Ok(parse_bool(field0)? == parse_bool(field1)?)
+}
+
+fn parse_bool(field: &str) -> Result<bool, EvalError> {
+    match field.trim().to_lowercase().as_str() {
+        "1" | "true" => Ok(true),
+        "0" | "false" => Ok(false),
+        err => Err(EvalError::TypeConversionError(format!(
+            "Unable to parse {err:?} as bool"
+        ))),
+    }
+}
+
+/// Fixed-size char arrays are padded with NUL characters.
+fn text_equal(field0: &str, field1: &str) -> bool {
+    field0.trim_end_matches('\0') == field1.trim_end_matches('\0')
+}
+
+#[cfg(test)]
+mod evaluator_tests {
+    use crate::eval::tolerance::Tolerance;
+
+    use super::{are_fields_equal, convert_types, EvalType};
+
+    #[test]
+    fn bool_and_text_fields() {
+        let types = ["BOOLEAN", "Char", "TEXT", "Char(8)", "INTEGER(8 bits)"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(
+            convert_types(&types).unwrap(),
+            vec![
+                EvalType::Bool,
+                EvalType::Text,
+                EvalType::Text,
+                EvalType::Text,
+                EvalType::Int
+            ]
+        );
+        let tolerance = Tolerance::default();
+        assert!(are_fields_equal("1", "true", EvalType::Bool, &tolerance).unwrap());
+        assert!(!are_fields_equal("0", "true", EvalType::Bool, &tolerance).unwrap());
+        assert!(are_fields_equal("ab\0\0", "ab", EvalType::Text, &tolerance).unwrap());
+        assert!(!are_fields_equal("ab", "Ab", EvalType::Text, &tolerance).unwrap());
     }
-    true
 }
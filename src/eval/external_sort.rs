@@ -0,0 +1,207 @@
+//! External merge sort of result records. Results with more records than fit into one run are
+//! sorted in runs that are spilled to temporary files and merged, so that sorting multi-GB results
+//! only keeps one run and one record per run in memory.
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    fs::{self, File},
+    path::PathBuf,
+    process,
+    rc::Rc,
+    sync::atomic::{self, AtomicUsize},
+    vec,
+};
+
+use csv::{ReaderBuilder, StringRecord, StringRecordsIntoIter, WriterBuilder};
+
+use super::{
+    eval_error::EvalError,
+    evaluator::{comp_typed_records, EvalType},
+};
+
+/// Records per sorted run.
+pub(crate) const SORT_RUN_RECORDS: usize = 100_000;
+
+/// Records in the order of `comp_typed_records`.
+pub(crate) enum SortedRecords {
+    InMemory(vec::IntoIter<StringRecord>),
+    Merge(Merge),
+}
+
+impl Iterator for SortedRecords {
+    type Item = Result<StringRecord, EvalError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            SortedRecords::InMemory(records) => records.next().map(Ok),
+            SortedRecords::Merge(merge) => merge.next(),
+        }
+    }
+}
+
+/// Sorts the `records` with the column `types` in runs of `run_size` records. Only results with
+/// more than one run are written to disk.
+pub(crate) fn sort_records(
+    records: impl Iterator<Item = Result<StringRecord, csv::Error>>,
+    types: &[EvalType],
+    run_size: usize,
+) -> Result<SortedRecords, EvalError> {
+    let mut records = records.peekable();
+    let mut run = next_run(&mut records, types, run_size)?;
+    if records.peek().is_none() {
+        return Ok(SortedRecords::InMemory(run.into_iter()));
+    }
+
+    let dir = SpillDir::create()?;
+    let mut run_paths = Vec::new();
+    while !run.is_empty() {
+        let path = dir.0.join(format!("run-{}.csv", run_paths.len()));
+        let mut writer = WriterBuilder::new().has_headers(false).from_path(&path)?;
+        for record in &run {
+            writer.write_record(record)?;
+        }
+        writer.flush().map_err(csv::Error::from)?;
+        run_paths.push(path);
+        run = next_run(&mut records, types, run_size)?;
+    }
+
+    let mut runs = run_paths
+        .iter()
+        .map(|path| {
+            let reader = ReaderBuilder::new().has_headers(false).from_path(path)?;
+            Ok(reader.into_records())
+        })
+        .collect::<Result<Vec<_>, csv::Error>>()?;
+    let types = Rc::<[EvalType]>::from(types);
+    let mut heap = BinaryHeap::new();
+    for (index, run) in runs.iter_mut().enumerate() {
+        if let Some(record) = run.next() {
+            heap.push(HeapEntry {
+                record: record?,
+                run: index,
+                types: types.clone(),
+            });
+        }
+    }
+    Ok(SortedRecords::Merge(Merge {
+        runs,
+        heap,
+        types,
+        _dir: dir,
+    }))
+}
+
+fn next_run(
+    records: &mut impl Iterator<Item = Result<StringRecord, csv::Error>>,
+    types: &[EvalType],
+    run_size: usize,
+) -> Result<Vec<StringRecord>, EvalError> {
+    let mut run = records
+        .take(run_size.max(1))
+        .collect::<Result<Vec<_>, csv::Error>>()?;
+    run.sort_by(|rec0, rec1| comp_typed_records(rec0, rec1, types));
+    Ok(run)
+}
+
+/// K-way merge of the sorted runs.
+pub(crate) struct Merge {
+    runs: Vec<StringRecordsIntoIter<File>>,
+    heap: BinaryHeap<HeapEntry>,
+    types: Rc<[EvalType]>,
+    // removes the runs after the merge
+    _dir: SpillDir,
+}
+
+impl Iterator for Merge {
+    type Item = Result<StringRecord, EvalError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let HeapEntry { record, run, .. } = self.heap.pop()?;
+        match self.runs[run].next() {
+            Some(Ok(next)) => self.heap.push(HeapEntry {
+                record: next,
+                run,
+                types: self.types.clone(),
+            }),
+            Some(Err(err)) => return Some(Err(err.into())),
+            None => (),
+        }
+        Some(Ok(record))
+    }
+}
+
+/// Orders the smallest record first in the max-heap.
+struct HeapEntry {
+    record: StringRecord,
+    run: usize,
+    types: Rc<[EvalType]>,
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        comp_typed_records(&other.record, &self.record, &self.types).then(other.run.cmp(&self.run))
+    }
+}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+/// Temporary directory of the runs of one sort, removed on drop.
+struct SpillDir(PathBuf);
+
+impl SpillDir {
+    fn create() -> Result<Self, EvalError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let id = COUNTER.fetch_add(1, atomic::Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("achilles-sort-{}-{id}", process::id()));
+        fs::create_dir_all(&path).map_err(csv::Error::from)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for SpillDir {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.0) {
+            log::warn!("Unable to remove {:?}: {err}", self.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod external_sort_tests {
+    use csv::StringRecord;
+    use rand::seq::SliceRandom;
+
+    use crate::{
+        eval::evaluator::{comp_typed_records, EvalType},
+        seed::seeded_rng,
+    };
+
+    use super::sort_records;
+
+    #[test]
+    fn spilled_runs_are_merged() {
+        let mut records = (0..50)
+            .map(|i| StringRecord::from(vec![(i % 7).to_string(), i.to_string(), String::new()]))
+            .collect::<Vec<_>>();
+        records.shuffle(&mut seeded_rng(7));
+        let types = [EvalType::Int, EvalType::Int, EvalType::Text];
+        let sorted = sort_records(records.clone().into_iter().map(Ok), &types, 6)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        records.sort_by(|rec0, rec1| comp_typed_records(rec0, rec1, &types));
+        assert_eq!(sorted, records);
+    }
+}
@@ -1,3 +1,7 @@
 pub mod evaluator;
 pub mod check_results;
+pub mod check_reference;
+pub mod diff_report;
 pub mod eval_error;
+pub mod external_sort;
+pub mod tolerance;
@@ -0,0 +1,246 @@
+//! Tolerance for float columns of results. Aggregates of floats depend on the order in which the
+//! records are summed up, so the origin and the other results of an oracle are rarely bit-equal.
+use yaml_rust2::{yaml::Hash, Yaml};
+
+/// Bounds on the difference of two floats that are considered equal. Two finite values are equal
+/// if their difference is within any of the bounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Largest absolute difference.
+    pub absolute: f64,
+    /// Largest difference relative to the larger magnitude of both values.
+    pub relative: f64,
+    /// Largest distance in units in the last place of the type of the column.
+    pub ulps: u64,
+    pub nan_equals_nan: bool,
+    /// Treat `0` and `-0` as equal.
+    pub signed_zeros_equal: bool,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            absolute: 1e-9,
+            relative: 1e-6,
+            ulps: 4,
+            nan_equals_nan: true,
+            signed_zeros_equal: true,
+        }
+    }
+}
+
+impl Tolerance {
+    /// Tolerance of oracles that compute an average as `sum / count` after a re-aggregation,
+    /// which accumulates more rounding error than a single aggregation. Its relative bound is at
+    /// least 1e-4, the other bounds are the ones of `self`.
+    pub fn average(&self) -> Self {
+        Self {
+            relative: self.relative.max(1e-4),
+            ..*self
+        }
+    }
+
+    pub fn accepts(&self, a: f64, b: f64, width: FloatWidth) -> bool {
+        if a.is_nan() || b.is_nan() {
+            return self.nan_equals_nan && a.is_nan() && b.is_nan();
+        }
+        if a == b {
+            return a != 0.0
+                || self.signed_zeros_equal
+                || a.is_sign_negative() == b.is_sign_negative();
+        }
+        if a.is_infinite() || b.is_infinite() {
+            return false;
+        }
+        let error = FloatError::between(a, b, width);
+        error.absolute <= self.absolute
+            || error.relative <= self.relative
+            || error.ulps <= self.ulps
+    }
+}
+
+/// Width of a float column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatWidth {
+    Bit32,
+    Bit64,
+}
+
+/// Difference between two floats. Equal values, including NaNs and equal infinities, have no
+/// error.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FloatError {
+    pub absolute: f64,
+    pub relative: f64,
+    pub ulps: u64,
+}
+
+impl FloatError {
+    pub fn between(a: f64, b: f64, width: FloatWidth) -> Self {
+        if a == b || (a.is_nan() && b.is_nan()) {
+            return Self::default();
+        }
+        let absolute = (a - b).abs();
+        let magnitude = a.abs().max(b.abs());
+        let relative = if magnitude > 0.0 {
+            absolute / magnitude
+        } else {
+            0.0
+        };
+        Self {
+            absolute,
+            relative,
+            ulps: ulp_distance(a, b, width),
+        }
+    }
+
+    /// Component-wise maximum.
+    pub fn max(self, other: Self) -> Self {
+        Self {
+            absolute: self.absolute.max(other.absolute),
+            relative: self.relative.max(other.relative),
+            ulps: self.ulps.max(other.ulps),
+        }
+    }
+}
+
+/// Number of representable values between `a` and `b` in the type of the column. NaN is as far
+/// away as possible.
+fn ulp_distance(a: f64, b: f64, width: FloatWidth) -> u64 {
+    if a.is_nan() || b.is_nan() {
+        return u64::MAX;
+    }
+    // maps the floats to integers with the same order, -0 and 0 to the same integer
+    let (a, b) = match width {
+        FloatWidth::Bit32 => {
+            let ordered = |value: f32| {
+                let bits = i64::from(value.to_bits());
+                if bits & 0x8000_0000 == 0 {
+                    bits
+                } else {
+                    0x8000_0000 - bits
+                }
+            };
+            (i128::from(ordered(a as f32)), i128::from(ordered(b as f32)))
+        }
+        FloatWidth::Bit64 => {
+            let ordered = |value: f64| {
+                let bits = i128::from(value.to_bits());
+                if bits & (1 << 63) == 0 {
+                    bits
+                } else {
+                    (1 << 63) - bits
+                }
+            };
+            (ordered(a), ordered(b))
+        }
+    };
+    u64::try_from((a - b).unsigned_abs()).unwrap_or(u64::MAX)
+}
+
+// yaml
+
+impl Into<Yaml> for &FloatError {
+    fn into(self) -> Yaml {
+        let mut map = Hash::new();
+        map.insert(Yaml::String("absolute".into()), real(self.absolute));
+        map.insert(Yaml::String("relative".into()), real(self.relative));
+        map.insert(
+            Yaml::String("ulps".into()),
+            Yaml::Integer(i64::try_from(self.ulps).unwrap_or(i64::MAX)),
+        );
+        Yaml::Hash(map)
+    }
+}
+
+impl TryFrom<&Yaml> for FloatError {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        let parse_f64 = |name: &str| match &value[name] {
+            Yaml::Integer(int) => Ok(*int as f64),
+            real @ Yaml::Real(_) => real
+                .as_f64()
+                .ok_or_else(|| format!("Unable to parse {name} of float error.")),
+            _ => Err(format!("Unable to parse {name} of float error.")),
+        };
+        let Some(ulps) = value["ulps"].as_i64() else {
+            return Err("Unable to parse ulps of float error.".into());
+        };
+        Ok(Self {
+            absolute: parse_f64("absolute")?,
+            relative: parse_f64("relative")?,
+            ulps: ulps as u64,
+        })
+    }
+}
+
+/// Yaml reals must not be written as `inf` or `NaN`, which yaml parses as strings.
+fn real(value: f64) -> Yaml {
+    if value.is_nan() {
+        Yaml::Real(".nan".into())
+    } else if value.is_infinite() {
+        let sign = if value < 0.0 { "-" } else { "" };
+        Yaml::Real(format!("{sign}.inf"))
+    } else {
+        Yaml::Real(format!("{value:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tolerance_tests {
+    use super::{FloatError, FloatWidth, Tolerance};
+
+    #[test]
+    fn bounds() {
+        let tolerance = Tolerance {
+            absolute: 0.0,
+            relative: 0.0,
+            ulps: 0,
+            ..Tolerance::default()
+        };
+        assert!(tolerance.accepts(1.5, 1.5, FloatWidth::Bit64));
+        assert!(!tolerance.accepts(1.0, 1.0 + f64::EPSILON, FloatWidth::Bit64));
+        assert!(!Tolerance {
+            ulps: 1,
+            ..tolerance
+        }
+        .accepts(1.0, 1.0 + 2.0 * f64::EPSILON, FloatWidth::Bit64));
+        // the difference vanishes in f32
+        assert!(Tolerance {
+            ulps: 1,
+            ..tolerance
+        }
+        .accepts(1.0, 1.0 + 2.0 * f64::EPSILON, FloatWidth::Bit32));
+        assert!(Tolerance {
+            relative: 1e-3,
+            ..tolerance
+        }
+        .accepts(1e12, 1e12 + 1e8, FloatWidth::Bit64));
+        assert!(!Tolerance {
+            absolute: 1e-3,
+            ..tolerance
+        }
+        .accepts(1e12, 1e12 + 1e8, FloatWidth::Bit64));
+    }
+
+    #[test]
+    fn special_values() {
+        let tolerance = Tolerance::default();
+        assert!(tolerance.accepts(f64::NAN, f64::NAN, FloatWidth::Bit64));
+        assert!(!tolerance.accepts(f64::NAN, 0.0, FloatWidth::Bit64));
+        assert!(tolerance.accepts(0.0, -0.0, FloatWidth::Bit64));
+        assert!(tolerance.accepts(f64::INFINITY, f64::INFINITY, FloatWidth::Bit64));
+        assert!(!tolerance.accepts(f64::INFINITY, f64::MAX, FloatWidth::Bit64));
+        let strict = Tolerance {
+            nan_equals_nan: false,
+            signed_zeros_equal: false,
+            ..tolerance
+        };
+        assert!(!strict.accepts(f64::NAN, f64::NAN, FloatWidth::Bit64));
+        assert!(!strict.accepts(0.0, -0.0, FloatWidth::Bit64));
+        // neighbours across 0 are 2 ulps apart
+        let min = f64::from_bits(1);
+        assert_eq!(FloatError::between(min, -min, FloatWidth::Bit64).ulps, 2);
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
 use nes_types::{FloatType, IntType, NesType};
 use rand::prelude::*;
@@ -14,6 +14,10 @@ use nes_rust_client::{
     prelude::{UnaryExpr, UnaryOp},
 };
 
+use crate::{
+    edge_values::edge_values, reference::value::int_bits, stream_gen::data_generator::generate_char,
+};
+
 const IS_EARLY_STOP: f64 = 0.25;
 const IS_FIELD: f64 = 0.50;
 
@@ -25,69 +29,271 @@ impl Display for GenerationError {
     }
 }
 
+/// Relative weights of the binary and unary operators of inner expression nodes by the name of
+/// their variant, e.g. `Remainder` or `Absolute`. Operators without an explicit weight have the
+/// weight 1 and a weight of 0 disables an operator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperatorWeights {
+    weights: HashMap<&'static str, u32>,
+}
+
+impl OperatorWeights {
+    pub fn weight(&self, operator: &str) -> u32 {
+        self.weights.get(operator).copied().unwrap_or(1)
+    }
+
+    /// Sets the weight of `operator`. Fails if no binary or unary operator has this name.
+    pub fn set(&mut self, operator: &str, weight: u32) -> Result<(), String> {
+        let Some(name) = operator_names().find(|&name| name == operator) else {
+            return Err(format!("Unknown operator {operator}."));
+        };
+        self.weights.insert(name, weight);
+        Ok(())
+    }
+}
+
+/// Settings of the expression generator.
+#[derive(Debug, Clone)]
+pub struct ExprGenConfig {
+    pub predicate_depth: u32,
+    pub operator_weights: OperatorWeights,
+    /// Avoid operations whose result NebulaStream does not define: integer division and remainder
+    /// by zero and integer `Power`, which overflows almost always. Without guards the reference
+    /// check skips test cases that divide by zero.
+    pub guard_undefined: bool,
+    /// Probability that the operands of a binary operation have different types.
+    pub mixed_type_ratio: f64,
+    /// Probability that a literal is one of the edge values of its type, see `edge_values`.
+    pub edge_value_ratio: f64,
+    /// Probability that a literal is a value of a field in the generated data, which makes
+    /// predicates on the field selective.
+    pub data_literal_ratio: f64,
+    pub data_values: DataValues,
+}
+
+impl Default for ExprGenConfig {
+    fn default() -> Self {
+        Self {
+            predicate_depth: 3,
+            operator_weights: OperatorWeights::default(),
+            guard_undefined: true,
+            mixed_type_ratio: 0.5,
+            edge_value_ratio: 0.1,
+            data_literal_ratio: 0.5,
+            data_values: DataValues::default(),
+        }
+    }
+}
+
+/// Sample of the values of every field in the generated data by field name and type.
+#[derive(Debug, Clone, Default)]
+pub struct DataValues {
+    fields: Vec<(String, NesType, Vec<String>)>,
+}
+
+impl DataValues {
+    pub fn add(&mut self, field_name: impl Into<String>, data_type: NesType, values: Vec<String>) {
+        self.fields.push((field_name.into(), data_type, values));
+    }
+
+    /// Sampled values of all fields named `field_name` with type `data_type`.
+    pub fn values<'a>(
+        &'a self,
+        field_name: &'a str,
+        data_type: NesType,
+    ) -> impl Iterator<Item = &'a String> + 'a {
+        self.fields
+            .iter()
+            .filter(move |(name, field_type, _)| name == field_name && *field_type == data_type)
+            .flat_map(|(_, _, values)| values)
+    }
+}
+
+/// Name of the variant of a binary operator, e.g. `Remainder`.
+pub fn binary_op_name(operator: BinaryOp) -> &'static str {
+    match operator {
+        BinaryOp::And => "And",
+        BinaryOp::Or => "Or",
+        BinaryOp::Equals => "Equals",
+        BinaryOp::NotEquals => "NotEquals",
+        BinaryOp::Greater => "Greater",
+        BinaryOp::GreaterEquals => "GreaterEquals",
+        BinaryOp::Less => "Less",
+        BinaryOp::LessEquals => "LessEquals",
+        BinaryOp::Add => "Add",
+        BinaryOp::Subtract => "Subtract",
+        BinaryOp::Multiply => "Multiply",
+        BinaryOp::Divide => "Divide",
+        BinaryOp::Remainder => "Remainder",
+        BinaryOp::Power => "Power",
+    }
+}
+
+pub fn unary_op_name(operator: UnaryOp) -> &'static str {
+    match operator {
+        UnaryOp::Negate => "Negate",
+        UnaryOp::Absolute => "Absolute",
+    }
+}
+
+/// Names of all binary and unary operators.
+pub fn operator_names() -> impl Iterator<Item = &'static str> {
+    BinaryOp::iter()
+        .map(binary_op_name)
+        .chain(UnaryOp::iter().map(unary_op_name))
+}
+
+/// Operator of an inner expression node.
+#[derive(Clone, Copy)]
+enum Operator {
+    Binary(BinaryOp),
+    Unary(UnaryOp),
+}
+
 /// Generates a random logical expression. Each branch has the specified `depth`. Fields are selected from the List
 /// of `fields`. See `generate_raw_expr` for more details.
-pub fn generate_logical_expr(depth: u32, fields: &[Field]) -> Result<LogicalExpr, GenerationError> {
+pub fn generate_logical_expr(
+    rng: &mut impl Rng,
+    depth: u32,
+    fields: &[Field],
+    config: &ExprGenConfig,
+) -> Result<LogicalExpr, GenerationError> {
     Ok(LogicalExpr(generate_raw_expr(
+        rng,
         depth,
         fields,
         NesType::Bool,
+        config,
     )?))
 }
 
 /// Generates a random expression. Leaf nodes are `Fields` or `Literal`s and non leaf nodes are
 /// `BinaryExpr` or `UnaryExpr`. Each branch has the specified depth. Fields are selected from the List
 /// of `fields`. The `output_type` specifies the return type of the expression. Undefined results in a
-/// random return type.
+/// random return type. The operators of non leaf nodes are chosen by the `operator_weights` of
+/// `config`. All random decisions are drawn from `rng`.
 pub fn generate_raw_expr(
+    rng: &mut impl Rng,
     depth: u32,
     fields: &[Field],
     output_type: NesType,
+    config: &ExprGenConfig,
 ) -> Result<RawExpr, GenerationError> {
-    let mut rng = rand::thread_rng();
-    if depth == 0 || rng.gen_bool(IS_EARLY_STOP) {
+    let operator = if depth == 0 || rng.gen_bool(IS_EARLY_STOP) {
+        None
+    } else {
+        choose_operator(rng, output_type, config)
+    };
+    let Some(operator) = operator else {
         let is_field = rng.gen_bool(IS_FIELD);
         if is_field {
-            let Some(field) = generate_field(fields, output_type) else {
-                let literal = generate_literal(output_type)?;
+            let Some(field) = generate_field(rng, fields, output_type) else {
+                let literal = generate_literal(rng, output_type, fields, config)?;
                 return Ok(RawExpr::Literal(literal));
             };
             return Ok(RawExpr::Field(field));
         }
-        let literal = generate_literal(output_type)?;
+        let literal = generate_literal(rng, output_type, fields, config)?;
         return Ok(RawExpr::Literal(literal));
+    };
+    match operator {
+        Operator::Binary(operator) => {
+            // should only select types with existing fields
+            let (lhs_type, rhs_type) =
+                binary_op_input_types(rng, operator, fields, output_type, config)?;
+            let lhs = generate_raw_expr(rng, depth - 1, fields, lhs_type, config)?;
+            let rhs = if config.guard_undefined && is_integer_division(operator, output_type) {
+                RawExpr::Literal(generate_non_zero_literal(rng, rhs_type)?)
+            } else {
+                generate_raw_expr(rng, depth - 1, fields, rhs_type, config)?
+            };
+            // `output_type` is the type NebulaStream infers for the operands, see `promote`
+            let binary = BinaryExpr {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                data_type: output_type,
+                operator,
+            };
+            Ok(RawExpr::Binary(binary))
+        }
+        Operator::Unary(operator) => {
+            // `Absolute` of the smallest signed integer overflows and wraps around to itself, the
+            // reference interpreter expects the same
+            let input_type = output_type;
+            let unary = UnaryExpr {
+                expr: Box::new(generate_raw_expr(
+                    rng,
+                    depth - 1,
+                    fields,
+                    input_type,
+                    config,
+                )?),
+                data_type: output_type,
+                operator,
+            };
+            Ok(RawExpr::Unary(unary))
+        }
     }
-    // binary operator
-    if rng.gen_bool(1.0) {
-        let operator = BinaryOp::iter()
-            .filter(|&operator| operator != BinaryOp::Remainder && operator != BinaryOp::Power)
-            .filter(|&operator| binary_op_can_return(operator, output_type))
-            .choose(&mut rng)
-            .expect("Failed to find binary operator");
-        // should only select types with existing fields
-        let input_type = binary_op_input_type(operator, fields, output_type)?;
-        let binary = BinaryExpr {
-            lhs: Box::new(generate_raw_expr(depth - 1, fields, input_type)?),
-            rhs: Box::new(generate_raw_expr(depth - 1, fields, input_type)?),
-            data_type: output_type,
-            operator,
+}
+
+/// Chooses the operator of a non leaf node that returns `output_type` by the `operator_weights`.
+/// Returns `None` if no operator with a positive weight can return `output_type`.
+fn choose_operator(
+    rng: &mut impl Rng,
+    output_type: NesType,
+    config: &ExprGenConfig,
+) -> Option<Operator> {
+    let binary = BinaryOp::iter()
+        .filter(|&operator| binary_op_can_return(operator, output_type))
+        .filter(|&operator| !(config.guard_undefined && is_integer_power(operator, output_type)))
+        .map(|operator| {
+            let weight = config.operator_weights.weight(binary_op_name(operator));
+            (Operator::Binary(operator), weight)
+        });
+    let unary = UnaryOp::iter()
+        .filter(|&operator| unary_op_can_return(operator, output_type))
+        .map(|operator| {
+            let weight = config.operator_weights.weight(unary_op_name(operator));
+            (Operator::Unary(operator), weight)
+        });
+    let candidates = binary
+        .chain(unary)
+        .filter(|(_, weight)| *weight > 0)
+        .collect::<Vec<_>>();
+    candidates
+        .choose_weighted(rng, |(_, weight)| *weight)
+        .ok()
+        .map(|(operator, _)| *operator)
+}
+
+fn is_integer_division(operator: BinaryOp, output_type: NesType) -> bool {
+    matches!(output_type, NesType::Int(_))
+        && matches!(operator, BinaryOp::Divide | BinaryOp::Remainder)
+}
+
+fn is_equality(operator: BinaryOp) -> bool {
+    matches!(operator, BinaryOp::Equals | BinaryOp::NotEquals)
+}
+
+fn is_integer_power(operator: BinaryOp, output_type: NesType) -> bool {
+    operator == BinaryOp::Power && !matches!(output_type, NesType::Float(_))
+}
+
+/// Divisor of a guarded integer division. Besides 0 it also avoids -1, because `MIN / -1`
+/// overflows, which is undefined in C++ as well.
+fn generate_non_zero_literal(
+    rng: &mut impl Rng,
+    data_type: NesType,
+) -> Result<Literal, GenerationError> {
+    loop {
+        let NesType::Int(t) = data_type else {
+            return generate_uniform_literal(rng, data_type);
         };
-        let expr = RawExpr::Binary(binary);
-        return Ok(expr);
+        let value = generate_int(rng, t);
+        if value != "0" && value != "-1" {
+            return Ok(Literal::typed(value, data_type));
+        }
     }
-    // unary operator
-    let operator = UnaryOp::iter()
-        .filter(|&operator| unary_op_can_return(operator, output_type))
-        .choose(&mut rng)
-        .expect("Failed to find unary operator");
-    let input_type = output_type;
-    let unary = UnaryExpr {
-        expr: Box::new(generate_raw_expr(depth - 1, fields, input_type)?),
-        data_type: output_type,
-        operator,
-    };
-    let expr = RawExpr::Unary(unary);
-    Ok(expr)
 }
 
 fn unary_op_can_return(operator: UnaryOp, output_type: NesType) -> bool {
@@ -96,9 +302,9 @@ fn unary_op_can_return(operator: UnaryOp, output_type: NesType) -> bool {
 }
 
 fn unary_op_accepted_output_types(operator: UnaryOp) -> Vec<NesType> {
-    match operator{
+    match operator {
         UnaryOp::Negate => logical_types(),
-        UnaryOp::Absolute => arithmetic_types()
+        UnaryOp::Absolute => arithmetic_types(),
     }
 }
 
@@ -107,20 +313,48 @@ fn binary_op_can_return(operator: BinaryOp, output_type: NesType) -> bool {
         || binary_op_accepted_output_types(operator).contains(&output_type)
 }
 
-fn generate_field(fields: &[Field], data_type: NesType) -> Option<Field> {
-    let mut rng = rand::thread_rng();
+fn generate_field(rng: &mut impl Rng, fields: &[Field], data_type: NesType) -> Option<Field> {
     if data_type == NesType::Undefined {
-        return fields.choose(&mut rng).cloned();
+        return fields.choose(rng).cloned();
     }
     fields
         .iter()
         .filter(|field| field.data_type() == data_type)
-        .choose(&mut rng)
+        .choose(rng)
         .cloned()
 }
 
-fn generate_literal(data_type: NesType) -> Result<Literal, GenerationError> {
-    let mut rng = rand::thread_rng();
+/// Generates a literal of `data_type`. With the probabilities of `config` the literal is a value
+/// of one of the `fields` in the generated data or an edge value of the type. Otherwise it is drawn
+/// uniformly from the whole range of the type.
+fn generate_literal(
+    rng: &mut impl Rng,
+    data_type: NesType,
+    fields: &[Field],
+    config: &ExprGenConfig,
+) -> Result<Literal, GenerationError> {
+    if rng.gen_bool(config.data_literal_ratio) {
+        let values = fields
+            .iter()
+            .filter(|field| field.data_type() == data_type)
+            .flat_map(|field| config.data_values.values(field.name(), data_type))
+            .collect::<Vec<_>>();
+        if let Some(&value) = values.choose(rng) {
+            return Ok(Literal::typed(value.clone(), data_type));
+        }
+    }
+    if rng.gen_bool(config.edge_value_ratio) {
+        if let Some(value) = edge_values(data_type).choose(rng) {
+            return Ok(Literal::typed(value.clone(), data_type));
+        }
+    }
+    generate_uniform_literal(rng, data_type)
+}
+
+fn generate_uniform_literal(
+    rng: &mut impl Rng,
+    data_type: NesType,
+) -> Result<Literal, GenerationError> {
     match data_type {
         NesType::Undefined => Err(GenerationError(
             "Cannot generate literal of type undefined.".into(),
@@ -129,18 +363,13 @@ fn generate_literal(data_type: NesType) -> Result<Literal, GenerationError> {
             rng.gen::<bool>().to_string().to_string(),
             NesType::Bool,
         )),
-        NesType::Char => Err(GenerationError(
-            "Cannot generate literal of type char.".into(),
-        )),
-        NesType::Int(t) => Ok(Literal::typed(generate_int(&mut rng, t), NesType::Int(t))),
-        NesType::Float(t) => Ok(Literal::typed(
-            generate_float(&mut rng, t),
-            NesType::Float(t),
-        )),
+        NesType::Char => Ok(Literal::typed(generate_char(rng), NesType::Char)),
+        NesType::Int(t) => Ok(Literal::typed(generate_int(rng, t), NesType::Int(t))),
+        NesType::Float(t) => Ok(Literal::typed(generate_float(rng, t), NesType::Float(t))),
     }
 }
 
-fn generate_int(rng: &mut ThreadRng, data_type: IntType) -> String {
+fn generate_int(rng: &mut impl Rng, data_type: IntType) -> String {
     match data_type {
         IntType::Signed8 => rng.gen::<i8>().to_string(),
         IntType::Unsigned8 => rng.gen::<u8>().to_string(),
@@ -153,16 +382,22 @@ fn generate_int(rng: &mut ThreadRng, data_type: IntType) -> String {
     }
 }
 
-fn generate_float(rng: &mut ThreadRng, data_type: FloatType) -> String {
+fn generate_float(rng: &mut impl Rng, data_type: FloatType) -> String {
     match data_type {
         FloatType::Bit32 => rng.gen::<f32>().to_string(),
         FloatType::Bit64 => rng.gen::<f64>().to_string(),
     }
 }
 
+/// Bool and char operands can only be compared for equality.
 fn binary_op_accepted_input_types(operator: BinaryOp) -> Vec<NesType> {
     match operator.get_op_type() {
         BinaryOpType::Logical => logical_types(),
+        BinaryOpType::Relational if is_equality(operator) => {
+            let mut types = arithmetic_types();
+            types.extend([NesType::Bool, NesType::Char]);
+            types
+        }
         BinaryOpType::Relational | BinaryOpType::Arithmetic => arithmetic_types(),
     }
 }
@@ -174,29 +409,126 @@ fn binary_op_accepted_output_types(operator: BinaryOp) -> Vec<NesType> {
     }
 }
 
-fn binary_op_input_type(
+/// Returns the types of the operands of `operator`. With probability `mixed_type_ratio` the
+/// operands have different types: comparisons compare two field types and arithmetic operations
+/// use a pair of types that `promote` to `output_type`.
+fn binary_op_input_types(
+    rng: &mut impl Rng,
     operator: BinaryOp,
     fields: &[Field],
     output_type: NesType,
-) -> Result<NesType, GenerationError> {
-    let mut rng = rand::thread_rng();
+    config: &ExprGenConfig,
+) -> Result<(NesType, NesType), GenerationError> {
+    let is_mixed = rng.gen_bool(config.mixed_type_ratio);
     match operator.get_op_type() {
-        BinaryOpType::Logical => Ok(NesType::Bool),
-        BinaryOpType::Relational => match binary_op_accepted_input_types(operator)
-            .iter()
-            .filter(|&input_type| {
-                fields
-                    .iter()
-                    .map(|field| field.data_type())
-                    .find(|t| t == input_type)
-                    .is_some()
-            })
-            .choose(&mut rng)
-        {
-            Some(t) => Ok(*t),
-            None => Err(GenerationError("Unable to find input type.".to_string())),
+        BinaryOpType::Logical => Ok((NesType::Bool, NesType::Bool)),
+        BinaryOpType::Relational => {
+            let field_types = binary_op_accepted_input_types(operator)
+                .into_iter()
+                .filter(|input_type| fields.iter().any(|field| field.data_type() == *input_type))
+                .collect::<Vec<_>>();
+            let Some(&lhs) = field_types.choose(rng) else {
+                return Err(GenerationError("Unable to find input type.".to_string()));
+            };
+            let rhs = field_types
+                .iter()
+                .filter(|&&rhs| is_mixed && promote(lhs, rhs).is_some())
+                .choose(rng)
+                .copied()
+                .unwrap_or(lhs);
+            Ok((lhs, rhs))
+        }
+        BinaryOpType::Arithmetic if is_mixed => {
+            let pairs = arithmetic_types()
+                .into_iter()
+                .flat_map(|lhs| arithmetic_types().into_iter().map(move |rhs| (lhs, rhs)))
+                .filter(|&(lhs, rhs)| lhs != rhs && promote(lhs, rhs) == Some(output_type))
+                .collect::<Vec<_>>();
+            Ok(pairs
+                .choose(rng)
+                .copied()
+                .unwrap_or((output_type, output_type)))
+        }
+        BinaryOpType::Arithmetic => Ok((output_type, output_type)),
+    }
+}
+
+/// Type promotion of NebulaStream for binary arithmetic operations and comparisons. Returns the
+/// type both operands are converted to, which is the result type of an arithmetic operation, or
+/// `None` if the types cannot be combined.
+///
+/// | lhs \ rhs    | float `b`         | signed `b`           | unsigned `b`           |
+/// |--------------|-------------------|----------------------|------------------------|
+/// | float `a`    | float `max(a, b)` | float `max(a, b)`    | float `max(a, b)`      |
+/// | signed `a`   | float `max(a, b)` | signed `max(a, b)`   | signed `max(a, b)`     |
+/// | unsigned `a` | float `max(a, b)` | signed `max(a, b)`   | unsigned `max(a, b)`   |
+///
+/// This follows `Integer::join` and `Float::join` of NebulaStream's data types: the joined stamp
+/// has the larger width and the union of both value ranges, and `DefaultPhysicalTypeFactory`
+/// lowers an integer stamp with a negative lower bound to the signed type of its width. So
+/// `i16 + u32` is an `i32`, `i32 + u8` an `i32` and `i64 + u64` an `i64`, which wraps values of
+/// the unsigned operand that the signed type cannot hold.
+pub fn promote(lhs: NesType, rhs: NesType) -> Option<NesType> {
+    let promoted = match (lhs, rhs) {
+        (NesType::Float(lhs), NesType::Float(rhs)) => {
+            NesType::Float(float_type(float_bits(lhs).max(float_bits(rhs))))
+        }
+        (NesType::Float(float), NesType::Int(int)) | (NesType::Int(int), NesType::Float(float)) => {
+            NesType::Float(float_type(float_bits(float).max(int_bits(int).0)))
+        }
+        (NesType::Int(lhs), NesType::Int(rhs)) => {
+            let (lhs_bits, lhs_signed) = int_bits(lhs);
+            let (rhs_bits, rhs_signed) = int_bits(rhs);
+            NesType::Int(int_type(lhs_bits.max(rhs_bits), lhs_signed || rhs_signed))
+        }
+        _ => return None,
+    };
+    Some(promoted)
+}
+
+/// Result type of `expr` as NebulaStream infers it from the types of its fields and literals, see
+/// `promote`. `Undefined` if it depends on untyped fields.
+pub fn infer_type(expr: &RawExpr) -> NesType {
+    match expr {
+        RawExpr::Field(field) => field.data_type(),
+        RawExpr::Literal(literal) => literal.data_type(),
+        RawExpr::Binary(binary) => match binary.operator.get_op_type() {
+            BinaryOpType::Logical | BinaryOpType::Relational => NesType::Bool,
+            BinaryOpType::Arithmetic => promote(infer_type(&binary.lhs), infer_type(&binary.rhs))
+                .unwrap_or(NesType::Undefined),
         },
-        BinaryOpType::Arithmetic => Ok(output_type),
+        RawExpr::Unary(unary) => match unary.operator {
+            UnaryOp::Negate => NesType::Bool,
+            UnaryOp::Absolute => infer_type(&unary.expr),
+        },
+    }
+}
+
+fn float_bits(float_type: FloatType) -> u32 {
+    match float_type {
+        FloatType::Bit32 => 32,
+        FloatType::Bit64 => 64,
+    }
+}
+
+fn float_type(bits: u32) -> FloatType {
+    if bits <= 32 {
+        FloatType::Bit32
+    } else {
+        FloatType::Bit64
+    }
+}
+
+fn int_type(bits: u32, signed: bool) -> IntType {
+    match (bits, signed) {
+        (8, true) => IntType::Signed8,
+        (8, false) => IntType::Unsigned8,
+        (16, true) => IntType::Signed16,
+        (16, false) => IntType::Unsigned16,
+        (32, true) => IntType::Signed32,
+        (32, false) => IntType::Unsigned32,
+        (_, true) => IntType::Signed64,
+        (_, false) => IntType::Unsigned64,
     }
 }
 
@@ -218,3 +550,76 @@ fn arithmetic_types() -> Vec<NesType> {
 fn logical_types() -> Vec<NesType> {
     vec![NesType::Bool]
 }
+
+#[cfg(test)]
+mod expr_gen_tests {
+    use std::collections::HashSet;
+
+    use nes_rust_client::expression::binary_expression::BinaryOp;
+    use nes_types::{FloatType, IntType, NesType};
+
+    use super::{is_equality, is_integer_division, operator_names, promote, OperatorWeights};
+
+    #[test]
+    fn operator_weights() {
+        let names = operator_names().collect::<Vec<_>>();
+        assert_eq!(names.len(), names.iter().collect::<HashSet<_>>().len());
+        let mut weights = OperatorWeights::default();
+        weights.set("Divide", 0).unwrap();
+        assert_eq!(weights.weight("Divide"), 0);
+        assert_eq!(weights.weight("Multiply"), 1);
+        assert!(weights.set("divide", 1).is_err());
+    }
+
+    #[test]
+    fn integer_division() {
+        let int = NesType::Int(IntType::Signed32);
+        assert!(is_integer_division(BinaryOp::Divide, int));
+        assert!(is_integer_division(BinaryOp::Remainder, int));
+        assert!(!is_integer_division(BinaryOp::Divide, NesType::f64()));
+        assert!(!is_integer_division(BinaryOp::Multiply, int));
+    }
+
+    #[test]
+    fn equality_operators() {
+        assert!(is_equality(BinaryOp::Equals));
+        assert!(is_equality(BinaryOp::NotEquals));
+        assert!(!is_equality(BinaryOp::Less));
+        assert!(!is_equality(BinaryOp::And));
+    }
+
+    #[test]
+    fn promotion_table() {
+        let int = NesType::Int;
+        let float = NesType::Float;
+        assert_eq!(
+            promote(int(IntType::Signed16), int(IntType::Unsigned32)),
+            Some(int(IntType::Signed32))
+        );
+        assert_eq!(
+            promote(int(IntType::Signed32), int(IntType::Unsigned8)),
+            Some(int(IntType::Signed32))
+        );
+        assert_eq!(
+            promote(int(IntType::Unsigned8), int(IntType::Unsigned16)),
+            Some(int(IntType::Unsigned16))
+        );
+        assert_eq!(
+            promote(int(IntType::Unsigned64), int(IntType::Signed64)),
+            Some(int(IntType::Signed64))
+        );
+        assert_eq!(
+            promote(int(IntType::Unsigned16), int(IntType::Signed8)),
+            Some(int(IntType::Signed16))
+        );
+        assert_eq!(
+            promote(int(IntType::Unsigned8), float(FloatType::Bit64)),
+            Some(float(FloatType::Bit64))
+        );
+        assert_eq!(
+            promote(float(FloatType::Bit32), int(IntType::Signed64)),
+            Some(float(FloatType::Bit64))
+        );
+        assert_eq!(promote(NesType::Bool, int(IntType::Signed8)), None);
+    }
+}
@@ -0,0 +1,371 @@
+use nes_rust_client::{
+    expression::{
+        binary_expression::{BinaryExpr, BinaryOp},
+        expression::RawExpr,
+        literal::Literal,
+        LogicalExpr,
+    },
+    prelude::{UnaryExpr, UnaryOp},
+};
+use nes_types::NesType;
+use rand::prelude::*;
+
+/// An equivalence preserving rewrite of a single expression node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rewrite {
+    /// `p` ⇔ `!!p`
+    DoubleNegation,
+    /// `a && b` ⇔ `!(!a || !b)` and `a || b` ⇔ `!(!a && !b)`
+    DeMorgan,
+    /// `a op b` ⇔ `b op a` for commutative operators
+    Commute,
+    /// `a < b` ⇔ `b > a`, `a <= b` ⇔ `b >= a` and vice versa
+    FlipComparison,
+    /// `p` ⇔ `p && true`
+    AndTrue,
+    /// `p` ⇔ `p || false`
+    OrFalse,
+}
+
+pub const REWRITES: [Rewrite; 6] = [
+    Rewrite::DoubleNegation,
+    Rewrite::DeMorgan,
+    Rewrite::Commute,
+    Rewrite::FlipComparison,
+    Rewrite::AndTrue,
+    Rewrite::OrFalse,
+];
+
+/// Returns a predicate that is semantically equivalent to `predicate`. Each of the `count` steps
+/// applies a random `Rewrite` to a random node of the expression that it is applicable to. All
+/// random decisions are drawn from `rng`.
+///
+/// Rewrites that only hold in two-valued logic, like `!(a < b)` ⇔ `a >= b`, are not used, because
+/// they do not hold for NaN. The rewritten predicate may contain bool literals, which
+/// `generate_predicate` never generates.
+pub fn rewrite_logical_expr(
+    rng: &mut impl Rng,
+    predicate: &LogicalExpr,
+    count: u32,
+) -> LogicalExpr {
+    let mut expr = predicate.0.clone();
+    for _ in 0..count {
+        let candidates = node_paths(&expr)
+            .into_iter()
+            .flat_map(|path| {
+                let node = node_at(&expr, &path);
+                REWRITES
+                    .into_iter()
+                    .filter(|&rewrite| apply_rewrite(rewrite, node).is_some())
+                    .map(move |rewrite| (path.clone(), rewrite))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let Some((path, rewrite)) = candidates.choose(rng) else {
+            break;
+        };
+        let node = node_at_mut(&mut expr, path);
+        if let Some(rewritten) = apply_rewrite(*rewrite, node) {
+            *node = rewritten;
+        }
+    }
+    LogicalExpr(expr)
+}
+
+/// Applies `rewrite` to the root of `expr`. Returns `None` if the rewrite is not applicable.
+pub fn apply_rewrite(rewrite: Rewrite, expr: &RawExpr) -> Option<RawExpr> {
+    match rewrite {
+        Rewrite::DoubleNegation if is_bool(expr) => Some(not(not(expr.clone()))),
+        Rewrite::DeMorgan => {
+            let RawExpr::Binary(binary) = expr else {
+                return None;
+            };
+            let dual = match binary.operator {
+                BinaryOp::And => BinaryOp::Or,
+                BinaryOp::Or => BinaryOp::And,
+                _ => return None,
+            };
+            Some(not(binary_expr(
+                not((*binary.lhs).clone()),
+                dual,
+                not((*binary.rhs).clone()),
+                NesType::Bool,
+            )))
+        }
+        Rewrite::Commute => {
+            let RawExpr::Binary(binary) = expr else {
+                return None;
+            };
+            let is_commutative = match binary.operator {
+                BinaryOp::And
+                | BinaryOp::Or
+                | BinaryOp::Equals
+                | BinaryOp::NotEquals
+                | BinaryOp::Add
+                | BinaryOp::Multiply => true,
+                BinaryOp::Greater
+                | BinaryOp::GreaterEquals
+                | BinaryOp::Less
+                | BinaryOp::LessEquals
+                | BinaryOp::Subtract
+                | BinaryOp::Divide
+                | BinaryOp::Remainder
+                | BinaryOp::Power => false,
+            };
+            if !is_commutative {
+                return None;
+            }
+            Some(binary_expr(
+                (*binary.rhs).clone(),
+                binary.operator,
+                (*binary.lhs).clone(),
+                binary.data_type,
+            ))
+        }
+        Rewrite::FlipComparison => {
+            let RawExpr::Binary(binary) = expr else {
+                return None;
+            };
+            let flipped = match binary.operator {
+                BinaryOp::Less => BinaryOp::Greater,
+                BinaryOp::LessEquals => BinaryOp::GreaterEquals,
+                BinaryOp::Greater => BinaryOp::Less,
+                BinaryOp::GreaterEquals => BinaryOp::LessEquals,
+                _ => return None,
+            };
+            Some(binary_expr(
+                (*binary.rhs).clone(),
+                flipped,
+                (*binary.lhs).clone(),
+                binary.data_type,
+            ))
+        }
+        Rewrite::AndTrue if is_bool(expr) => Some(binary_expr(
+            expr.clone(),
+            BinaryOp::And,
+            bool_literal(true),
+            NesType::Bool,
+        )),
+        Rewrite::OrFalse if is_bool(expr) => Some(binary_expr(
+            expr.clone(),
+            BinaryOp::Or,
+            bool_literal(false),
+            NesType::Bool,
+        )),
+        _ => None,
+    }
+}
+
+fn is_bool(expr: &RawExpr) -> bool {
+    let data_type = match expr {
+        RawExpr::Field(field) => field.data_type(),
+        RawExpr::Literal(literal) => literal.data_type(),
+        RawExpr::Binary(binary) => binary.data_type,
+        RawExpr::Unary(unary) => unary.data_type,
+    };
+    data_type == NesType::Bool
+}
+
+fn not(expr: RawExpr) -> RawExpr {
+    RawExpr::Unary(UnaryExpr {
+        expr: Box::new(expr),
+        data_type: NesType::Bool,
+        operator: UnaryOp::Negate,
+    })
+}
+
+fn binary_expr(lhs: RawExpr, operator: BinaryOp, rhs: RawExpr, data_type: NesType) -> RawExpr {
+    RawExpr::Binary(BinaryExpr {
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+        data_type,
+        operator,
+    })
+}
+
+fn bool_literal(value: bool) -> RawExpr {
+    RawExpr::Literal(Literal::typed(value.to_string(), NesType::Bool))
+}
+
+/// Paths of all nodes in pre-order. A path lists the child indices from the root to the node.
+fn node_paths(expr: &RawExpr) -> Vec<Vec<usize>> {
+    let mut paths = vec![Vec::new()];
+    for (index, child) in children(expr).into_iter().enumerate() {
+        for mut path in node_paths(child) {
+            path.insert(0, index);
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+fn children(expr: &RawExpr) -> Vec<&RawExpr> {
+    match expr {
+        RawExpr::Binary(binary) => vec![&*binary.lhs, &*binary.rhs],
+        RawExpr::Unary(unary) => vec![&*unary.expr],
+        RawExpr::Field(_) | RawExpr::Literal(_) => Vec::new(),
+    }
+}
+
+fn node_at<'a>(expr: &'a RawExpr, path: &[usize]) -> &'a RawExpr {
+    path.iter().fold(expr, |node, &index| children(node)[index])
+}
+
+fn node_at_mut<'a>(expr: &'a mut RawExpr, path: &[usize]) -> &'a mut RawExpr {
+    let Some((&index, rest)) = path.split_first() else {
+        return expr;
+    };
+    let child = match (expr, index) {
+        (RawExpr::Binary(binary), 0) => &mut binary.lhs,
+        (RawExpr::Binary(binary), _) => &mut binary.rhs,
+        (RawExpr::Unary(unary), _) => &mut unary.expr,
+        (expr, _) => return expr,
+    };
+    node_at_mut(child, rest)
+}
+
+#[cfg(test)]
+mod expr_rewrite_tests {
+    use std::collections::HashMap;
+
+    use nes_rust_client::{
+        expression::{
+            binary_expression::{BinaryExpr, BinaryOp},
+            expression::RawExpr,
+            Field,
+        },
+        query::stringify::stringify_expr,
+    };
+    use nes_types::{IntType, NesType};
+    use rand::Rng;
+
+    use crate::{
+        expr_gen::expr_gen::ExprGenConfig,
+        reference::{
+            expr_eval::{eval_expr, FieldLookup},
+            value::Value,
+        },
+        seed::seeded_rng,
+        test_case_gen::util::generate_predicate,
+    };
+
+    use super::{apply_rewrite, bool_literal, children, not, rewrite_logical_expr, Rewrite};
+
+    struct Record(HashMap<String, Value>);
+
+    impl FieldLookup for Record {
+        fn lookup(&self, name: &str) -> Option<Value> {
+            self.0.get(name).copied()
+        }
+    }
+
+    #[test]
+    fn rewrites_are_equivalent() {
+        let int_type = NesType::Int(IntType::Signed32);
+        let fields = [
+            Field::typed("a".to_string(), int_type),
+            Field::typed("b".to_string(), int_type),
+            Field::typed("c".to_string(), NesType::f64()),
+        ];
+        let config = ExprGenConfig::default();
+        let mut rng = seeded_rng(42);
+        for _ in 0..50 {
+            let predicate = generate_predicate(&mut rng, &config, &fields);
+            let rewritten = rewrite_logical_expr(&mut rng, &predicate, 3);
+            for _ in 0..20 {
+                let record = Record(HashMap::from([
+                    ("a".to_string(), Value::Int(rng.gen_range(-5..5))),
+                    ("b".to_string(), Value::Int(rng.gen_range(-5..5))),
+                    ("c".to_string(), Value::Float(rng.gen_range(-5.0..5.0))),
+                ]));
+                let expected = eval_expr(&predicate.0, &record);
+                let actual = eval_expr(&rewritten.0, &record);
+                if let Ok(expected) = expected {
+                    assert_eq!(Ok(expected), actual);
+                }
+            }
+        }
+    }
+
+    fn field(name: &str, data_type: NesType) -> RawExpr {
+        RawExpr::Field(Field::typed(name.to_string(), data_type))
+    }
+
+    fn binary(lhs: RawExpr, operator: BinaryOp, rhs: RawExpr) -> RawExpr {
+        RawExpr::Binary(BinaryExpr {
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            data_type: NesType::Bool,
+            operator,
+        })
+    }
+
+    fn count_non_bool_literals(expr: &RawExpr) -> usize {
+        let own = matches!(expr, RawExpr::Literal(literal) if literal.data_type() != NesType::Bool);
+        usize::from(own)
+            + children(expr)
+                .into_iter()
+                .map(count_non_bool_literals)
+                .sum::<usize>()
+    }
+
+    #[test]
+    fn rewrites_have_the_expected_shape() {
+        let int_type = NesType::Int(IntType::Signed32);
+        let a = || field("a", int_type);
+        let b = || field("b", int_type);
+        let p = || field("p", NesType::Bool);
+        let rewrite = |rewrite, expr: RawExpr| {
+            apply_rewrite(rewrite, &expr).map(|expr| stringify_expr(&expr))
+        };
+        let expected = |expr: RawExpr| Some(stringify_expr(&expr));
+
+        let less = binary(a(), BinaryOp::LessEquals, b());
+        assert_eq!(
+            rewrite(Rewrite::FlipComparison, less.clone()),
+            expected(binary(b(), BinaryOp::GreaterEquals, a()))
+        );
+        assert_eq!(
+            rewrite(Rewrite::Commute, binary(a(), BinaryOp::Equals, b())),
+            expected(binary(b(), BinaryOp::Equals, a()))
+        );
+        assert_eq!(
+            rewrite(Rewrite::DeMorgan, binary(p(), BinaryOp::And, less.clone())),
+            expected(not(binary(not(p()), BinaryOp::Or, not(less.clone()))))
+        );
+        assert_eq!(
+            rewrite(Rewrite::DoubleNegation, p()),
+            expected(not(not(p())))
+        );
+        assert_eq!(
+            rewrite(Rewrite::AndTrue, less.clone()),
+            expected(binary(less.clone(), BinaryOp::And, bool_literal(true)))
+        );
+        assert_eq!(
+            rewrite(Rewrite::OrFalse, p()),
+            expected(binary(p(), BinaryOp::Or, bool_literal(false)))
+        );
+        assert_eq!(rewrite(Rewrite::Commute, less), None);
+        assert_eq!(rewrite(Rewrite::DoubleNegation, a()), None);
+        assert_eq!(rewrite(Rewrite::AndTrue, a()), None);
+    }
+
+    #[test]
+    fn rewrites_only_insert_bool_literals() {
+        let int_type = NesType::Int(IntType::Signed32);
+        let fields = [
+            Field::typed("a".to_string(), int_type),
+            Field::typed("p".to_string(), NesType::Bool),
+        ];
+        let config = ExprGenConfig::default();
+        let mut rng = seeded_rng(7);
+        for _ in 0..50 {
+            let predicate = generate_predicate(&mut rng, &config, &fields);
+            let rewritten = rewrite_logical_expr(&mut rng, &predicate, 5);
+            assert_eq!(
+                count_non_bool_literals(&predicate.0),
+                count_non_bool_literals(&rewritten.0)
+            );
+        }
+    }
+}
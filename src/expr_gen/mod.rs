@@ -1 +1,2 @@
 pub mod expr_gen;
+pub mod expr_rewrite;
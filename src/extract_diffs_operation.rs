@@ -6,18 +6,19 @@ use std::{
 use crate::{
     eval::{
         check_results::{read_test_set_results_from_file, TestSetResult},
+        diff_report::read_diff_reports_from_file,
         evaluator::ResultRelation,
     },
     test_case_gen::query_id::TestCaseId,
     yaml_util::store_yaml_array,
-    LancerConfig,
+    AchillesError, LancerConfig,
 };
 
-pub fn extract_diffs_operatoion(config: &LancerConfig) {
+pub fn extract_diffs_operatoion(config: &LancerConfig) -> Result<(), AchillesError> {
     for run_id in 0..config.test_config.test_run_count {
-        reset_extract_dir();
-        create_extract_dir(run_id);
-        let test_set_results = read_test_set_results_from_file(run_id, &config);
+        reset_extract_dir()?;
+        create_extract_dir(run_id)?;
+        let test_set_results = read_test_set_results_from_file(run_id, config)?;
 
         //extract diffs
         let extracted_test_set_results = test_set_results
@@ -27,7 +28,18 @@ pub fn extract_diffs_operatoion(config: &LancerConfig) {
         let path = PathBuf::from(format!(
             "./extract-diffs/test-run-{run_id}/test-case-results.yml"
         ));
-        store_yaml_array(&path, &extracted_test_set_results);
+        store_yaml_array(&path, &extracted_test_set_results)?;
+
+        // test runs evaluated before diff reports existed have none
+        match read_diff_reports_from_file(run_id, config) {
+            Ok(diff_reports) => {
+                let path = PathBuf::from(format!(
+                    "./extract-diffs/test-run-{run_id}/diff-reports.yml"
+                ));
+                store_yaml_array(&path, &diff_reports)?;
+            }
+            Err(err) => log::warn!("Unable to read diff reports: {err}"),
+        }
 
         // copy diff result files
         for test_set in extracted_test_set_results {
@@ -53,6 +65,7 @@ pub fn extract_diffs_operatoion(config: &LancerConfig) {
             }
         }
     }
+    Ok(())
 }
 
 fn generate_paths(run_id: u32, file_name: &str, config: &LancerConfig) -> (PathBuf, PathBuf) {
@@ -82,35 +95,30 @@ fn extract_diffs(set: TestSetResult) -> TestSetResult {
     TestSetResult {
         id: set.id,
         strategy: set.strategy,
+        origin_reference: set.origin_reference,
         test_cases,
     }
 }
 
-fn reset_extract_dir() {
+fn reset_extract_dir() -> Result<(), AchillesError> {
     let override_files = true;
     let path = PathBuf::from("./extract-diffs");
     if override_files && path.exists() {
         log::info!("Deleting existing files in path: {:?}", &path);
-        fs::remove_dir_all(&path).unwrap();
+        fs::remove_dir_all(&path).map_err(|err| AchillesError::io(&path, err))?;
     }
-    fs::create_dir(&path).unwrap();
+    fs::create_dir(&path).map_err(|err| AchillesError::io(&path, err))
 }
 
-fn create_extract_dir(test_run_id: u32) {
+fn create_extract_dir(test_run_id: u32) -> Result<(), AchillesError> {
     let test_run_path = PathBuf::from(format!("./extract-diffs/test-run-{test_run_id}"));
     log::info!(
         "Creating test-run-{test_run_id} directory in {:?}",
         test_run_path
     );
-    if let Err(err) = fs::create_dir(test_run_path) {
-        log::error!("{err}");
-        return;
-    }
+    fs::create_dir(&test_run_path).map_err(|err| AchillesError::io(&test_run_path, err))?;
 
     let result_path = PathBuf::from(format!("./extract-diffs/test-run-{test_run_id}/results"));
     log::info!("Creating result directory in {:?}", result_path);
-    if let Err(err) = fs::create_dir(result_path) {
-        log::error!("{err}");
-        return;
-    }
+    fs::create_dir(&result_path).map_err(|err| AchillesError::io(&result_path, err))
 }
@@ -1,117 +1,115 @@
 use crate::config::LancerConfig;
-use crate::stream_gen::data_generator::{KeyStrategy, TimeStampStrategy};
-use crate::stream_gen::SourceBundle;
-use crate::stream_gen::{
-    data_generator::{FieldGenerator, RandomStrategy, RecordGenerator},
-    physical_source::PhysicalSource,
-    stream_gen::{NesLogLevel, StreamGen},
-    LogicalSource,
-};
-use nes_rust_client::expression::Field;
+use crate::error::AchillesError;
+use crate::stream_gen::source_schema::{FieldSchema, FieldStrategySpec, SourceSchema};
+use crate::stream_gen::stream_gen::{NesLogLevel, StreamGen};
+use crate::test_case_gen::oracle::QueryGenStrategy;
+use crate::yaml_util::load_yaml_array;
 use nes_types::NesType;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
 
-pub fn generate_files(test_run_id: u32, config: &LancerConfig) {
+use crate::seed::{seeded_rng, stream_gen_seed, test_run_seed};
+
+pub fn generate_files(test_run_id: u32, config: &LancerConfig) -> Result<(), AchillesError> {
     log::info!("Start generating files.");
-    let source_bundles = get_n_random_source_bundles(5, config);
+    let seed = stream_gen_seed(test_run_seed(config.test_config.seed, test_run_id));
+    let schemas = match &config.test_config.schema_file {
+        Some(path) => {
+            let schemas = load_yaml_array::<SourceSchema>(path)?;
+            check_required_fields(&schemas, &config.test_config.oracles)
+                .map_err(AchillesError::Config)?;
+            schemas
+        }
+        None => get_n_random_source_schemas(config.test_config.source_count, seed, config),
+    };
 
+    let cluster_paths = (0..config.net_config.cluster_count.max(1))
+        .map(|cluster_id| config.path_config.cluster(test_run_id, cluster_id));
     let builder = StreamGen::builder()
         .in_path(&config.path_config.test_run(test_run_id))
+        .cluster_paths(cluster_paths)
         .network_config(config.net_config.clone())
         .coordinator_log_level(NesLogLevel::Debug)
         .worker_log_level(NesLogLevel::Debug)
-        .add_source_bundles(source_bundles)
+        .add_source_schemas(&schemas, seed, &config.test_config)
+        .map_err(AchillesError::Config)?
         .query_comp_config(config.query_comp_config.clone())
         .opt_config(config.opt_config.clone());
-    let result = builder.build().generate();
-
-    if let Err(err) = result {
-        log::error!("Error generating files: {}", err);
-        return;
-    }
+    builder.build().generate()?;
     log::info!("Generating files done.");
+    Ok(())
 }
 
-fn get_n_random_source_bundles(source_count: u32, config: &LancerConfig) -> Vec<SourceBundle> {
-    let mut source_bundles = Vec::new();
-    for id in 0..source_count {
-        let source_name = format!("source-{id}");
-        let field_count = config.test_config.field_count;
-        let physical_source_count = config.test_config.physical_source_count;
-        let record_count = config.test_config.record_count;
-        let source_bundle = get_random_source_bundle(
-            source_name,
-            field_count,
-            physical_source_count,
-            record_count,
-        );
-        source_bundles.push(source_bundle);
+/// Checks that every declared logical source has the fields the oracles query, like `ts` for
+/// windows and `key` for keyed windows.
+fn check_required_fields(
+    schemas: &[SourceSchema],
+    oracles: &[QueryGenStrategy],
+) -> Result<(), String> {
+    for oracle in oracles {
+        for required in oracle.required_fields() {
+            let missing = schemas
+                .iter()
+                .find(|schema| schema.fields.iter().all(|field| field.name != *required));
+            if let Some(schema) = missing {
+                return Err(format!(
+                    "Source {} has no field {required}, which the oracle {oracle:?} requires.",
+                    schema.name
+                ));
+            }
+        }
     }
-    source_bundles
+    Ok(())
 }
 
-fn get_random_source_bundle(
-    source_name: impl Into<String>,
-    field_count: u32,
-    physical_source_count: u32,
-    record_count: u32,
-) -> SourceBundle {
-    let mut fields = Vec::new();
-    //list of list of field generators (lolofigen)
-    let mut lolofigen: Vec<Vec<FieldGenerator>> = Vec::new();
-
-    fields.push(Field::typed("ts", NesType::i64()));
-    fields.push(Field::typed("key", NesType::i64()));
-    for _ in 0..physical_source_count {
-        let field_gens = vec![
-            FieldGenerator::new("ts", NesType::i64(), TimeStampStrategy::new(100)),
-            FieldGenerator::new("key", NesType::u64(), KeyStrategy::new(0..21)),
-        ];
-        lolofigen.push(field_gens);
-    }
+fn get_n_random_source_schemas(
+    source_count: u32,
+    seed: u64,
+    config: &LancerConfig,
+) -> Vec<SourceSchema> {
+    let mut rng = seeded_rng(seed);
+    (0..source_count)
+        .map(|id| SourceSchema {
+            name: format!("source-{id}"),
+            physical_source_count: None,
+            record_count: None,
+            fields: get_random_fields(&mut rng, config.test_config.field_count),
+        })
+        .collect()
+}
 
+/// Fields of a random logical source: bursty time stamps `ts`, uniform keys `key` and
+/// `field_count` fields `f{id}` of random types.
+fn get_random_fields(rng: &mut impl Rng, field_count: u32) -> Vec<FieldSchema> {
+    let mut fields = vec![
+        FieldSchema::new(
+            "ts",
+            NesType::i64(),
+            FieldStrategySpec::TimeStamp { start: 100 },
+        ),
+        FieldSchema::new(
+            "key",
+            NesType::i64(),
+            FieldStrategySpec::Key { min: 0, max: 21 },
+        ),
+    ];
     for id in 0..field_count {
-        let name = format!("f{id}");
-        let data_type = get_random_type();
-        let field = Field::typed(&name, data_type);
-        fields.push(field);
-        for field_gens in &mut lolofigen {
-            field_gens.push(FieldGenerator::new(
-                &name,
-                data_type,
-                RandomStrategy::new(data_type),
-            ));
-        }
-    }
-
-    let source_name = source_name.into();
-    let logical_source = LogicalSource {
-        source_name: source_name.clone(),
-        fields,
-    };
-
-    let mut physical_sources = vec![];
-    for (id, field_gens) in lolofigen.into_iter().enumerate() {
-        let physical_source = PhysicalSource {
-            physical_source_name: format!("{source_name}-{id}"),
-            generator: RecordGenerator {
-                field_generators: field_gens,
-                record_count: record_count.into(),
-            },
-        };
-        physical_sources.push(physical_source);
-    }
-
-    SourceBundle {
-        logical_source,
-        physical_sources,
+        let data_type = get_random_type(rng);
+        fields.push(FieldSchema::new(
+            format!("f{id}"),
+            data_type,
+            FieldStrategySpec::default(),
+        ));
     }
+    fields
 }
 
-fn get_random_type() -> NesType {
+fn get_random_type(rng: &mut impl Rng) -> NesType {
     let types = [
-        // NesType::bool(),
+        NesType::bool(),
+        // TODO: Add fixed-size text fields once nes-rust-client has a text type; the evaluator
+        // already compares NUL padded `Char(N)` and `TEXT` columns.
+        NesType::Char,
         NesType::u8(),
         NesType::i8(),
         NesType::u16(),
@@ -123,8 +121,30 @@ fn get_random_type() -> NesType {
         NesType::f32(),
         NesType::f64(),
     ];
-    let mut rng = thread_rng();
-    *types
-        .choose(&mut rng)
-        .expect("Should be able to choose type")
+    *types.choose(rng).expect("Should be able to choose type")
+}
+
+#[cfg(test)]
+mod generate_files_tests {
+    use super::*;
+
+    #[test]
+    fn oracles_require_their_fields() {
+        let mut rng = seeded_rng(1);
+        let random = SourceSchema {
+            name: "random".into(),
+            physical_source_count: None,
+            record_count: None,
+            fields: get_random_fields(&mut rng, 2),
+        };
+        let mut unkeyed = random.clone();
+        unkeyed.name = "unkeyed".into();
+        unkeyed.fields.retain(|field| field.name != "key");
+        let schemas = [random, unkeyed];
+
+        assert!(check_required_fields(&schemas, &[QueryGenStrategy::Filter]).is_ok());
+        assert!(check_required_fields(&schemas, &[QueryGenStrategy::WinPartSum]).is_ok());
+        let err = check_required_fields(&schemas, &[QueryGenStrategy::KeyAggSum]).unwrap_err();
+        assert!(err.contains("unkeyed") && err.contains("key"));
+    }
 }
@@ -1,4 +1,7 @@
 pub mod generate_files;
+pub mod checkpoint;
+pub mod cluster;
+pub mod cli;
 pub mod config;
 pub mod load_config;
 pub mod nes_query_comp_config;
@@ -9,16 +12,24 @@ pub mod test_case_gen;
 pub mod runner;
 pub mod stream_gen;
 pub mod stages;
+pub mod error;
 pub mod eval;
 pub mod stream_schema;
 pub mod expr_gen;
 pub mod process_test_case;
 pub mod yaml_util;
 pub mod replay_exec;
+pub mod ron_tree;
+pub mod seed;
+pub mod edge_values;
 pub mod summery;
+pub mod summary_export;
 pub mod extract_diffs_operation;
+pub mod reducer;
+pub mod reference;
 
 pub use config::LancerConfig;
+pub use error::AchillesError;
 pub use generate_files::generate_files;
 pub use eval::check_results::check_test_sets;
 pub use test_case_gen::generate_test_sets::generate_test_sets;
@@ -1,255 +1,674 @@
 use std::{
-    fs,
+    collections::HashMap,
+    net::Ipv4Addr,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
-use yaml_rust2::{Yaml, YamlLoader};
+use yaml_rust2::{yaml::Hash, Yaml};
 
 use crate::{
-    config::{FilePathConfig, TestConfig},
+    config::{FilePathConfig, NetworkConfig, TestConfig, AVERAGE_ORACLES},
+    eval::{evaluator::ColumnAliases, tolerance::Tolerance},
+    nes_opt_config::NesOptConfig,
     nes_query_comp_config::NesQueryCompilerConfig,
-    LancerConfig,
+    runner::runner_config::RunnerConfig,
+    stream_gen::data_generator::{TimeStampDisorder, MAX_TIME_STAMP_GAP},
+    test_case_gen::oracle::QueryGenStrategy,
+    yaml_util::load_yaml_doc,
+    AchillesError, LancerConfig,
 };
 
-// FIXME: fully implement this function
-pub fn load_config(path: &Path) -> LancerConfig {
-    let Ok(content) = fs::read_to_string(path) else {
-        return LancerConfig::default();
-    };
-    let Ok(docs) = YamlLoader::load_from_str(&content) else {
-        return LancerConfig::default();
-    };
-    let Some(doc) = &docs.first() else {
-        return LancerConfig::default();
-    };
+/// Loads the `LancerConfig` from the yaml file in `path`. Every key that is not present in the
+/// file keeps its default value. Unknown keys and values of the wrong type are rejected.
+pub fn load_config(path: &Path) -> Result<LancerConfig, AchillesError> {
+    let doc = load_yaml_doc(path)?;
+    parse_config(&doc)
+        .map_err(|err| AchillesError::Config(format!("Invalid config file {:?}: {err}", path)))
+}
 
-    // Create a default LancerConfig
+pub fn parse_config(doc: &Yaml) -> Result<LancerConfig, String> {
     let mut config = LancerConfig::default();
+    for (key, value) in as_hash("config", doc)? {
+        match as_key(key)? {
+            "test_config" => config.test_config = parse_test_config(value)?,
+            "path_config" => config.path_config = parse_path_config(value)?,
+            "query_comp_config" => config.query_comp_config = parse_query_comp_config(value)?,
+            "opt_config" => config.opt_config = parse_opt_config(value)?,
+            "runner_config" => config.runner_config = parse_runner_config(value)?,
+            "net_config" => config.net_config = parse_net_config(value)?,
+            "test_case_timeout" => {
+                config.test_case_timeout = parse_duration("test_case_timeout", value)?
+            }
+            "skip_to_stage" => config.skip_to_stage = parse_from_str("skip_to_stage", value)?,
+            "stop_after_stage" => {
+                config.stop_after_stage = parse_from_str("stop_after_stage", value)?
+            }
+            "resume" => config.resume = parse_bool("resume", value)?,
+            err => return Err(format!("Unknown key {err}.")),
+        }
+    }
+    Ok(config)
+}
 
-    // Update config with values from YAML
-    if let Yaml::Hash(ref hash) = doc {
-        for (key, value) in hash {
-            if let Yaml::String(ref key_str) = key {
-                match key_str.as_str() {
-                    "test_config" => config.test_config = parse_test_config(value),
-                    "path_config" => config.path_config = parse_path_config(value),
-                    "query_comp_config" => config.query_comp_config = parse_query_comp_config(value),
-                    _ => {}
+fn parse_test_config(yaml: &Yaml) -> Result<TestConfig, String> {
+    let mut config = TestConfig::default();
+    let mut tolerance_overrides = None;
+    for (key, value) in as_hash("test_config", yaml)? {
+        match as_key(key)? {
+            "oracles" => {
+                let Yaml::Array(arr) = value else {
+                    return Err("Unable to parse test_config.oracles: Expected array.".into());
+                };
+                config.oracles = arr
+                    .iter()
+                    .map(|oracle| oracle.try_into())
+                    .collect::<Result<Vec<_>, String>>()
+                    .map_err(|err| format!("Unable to parse test_config.oracles: {err}"))?;
+            }
+            "seed" => config.seed = parse_seed(value)?,
+            "test_run_count" => config.test_run_count = parse_u32("test_run_count", value)?,
+            "oracle_reps" => config.oracle_reps = parse_u32("oracle_reps", value)?,
+            "test_case_count" => config.test_case_count = parse_u32("test_case_count", value)?,
+            "field_count" => config.field_count = parse_u32("field_count", value)?,
+            "record_count" => config.record_count = parse_u32("record_count", value)?,
+            "physical_source_count" => {
+                config.physical_source_count = parse_u32("physical_source_count", value)?
+            }
+            "source_count" => config.source_count = parse_u32("source_count", value)?,
+            "schema_file" => config.schema_file = Some(parse_path("schema_file", value)?),
+            "predicate_depth" => config.predicate_depth = parse_u32("predicate_depth", value)?,
+            "reference_check" => config.reference_check = parse_bool("reference_check", value)?,
+            "operator_weights" => {
+                for (operator, weight) in as_hash("test_config.operator_weights", value)? {
+                    let operator = as_key(operator)?;
+                    let weight = parse_u32(operator, weight)?;
+                    config
+                        .operator_weights
+                        .set(operator, weight)
+                        .map_err(|err| {
+                            format!("Unable to parse test_config.operator_weights: {err}")
+                        })?;
                 }
             }
+            "guard_undefined_ops" => {
+                config.guard_undefined_ops = parse_bool("guard_undefined_ops", value)?
+            }
+            "mixed_type_ratio" => config.mixed_type_ratio = parse_ratio("mixed_type_ratio", value)?,
+            "edge_value_ratio" => config.edge_value_ratio = parse_ratio("edge_value_ratio", value)?,
+            "data_literal_ratio" => {
+                config.data_literal_ratio = parse_ratio("data_literal_ratio", value)?
+            }
+            "time_stamp_disorder" => config.time_stamp_disorder = parse_time_stamp_disorder(value)?,
+            "column_aliases" => config.column_aliases = parse_column_aliases(value)?,
+            "tolerance" => {
+                config.tolerance =
+                    parse_tolerance("test_config.tolerance", value, Tolerance::default())?
+            }
+            "tolerance_overrides" => tolerance_overrides = Some(value),
+            err => return Err(format!("Unknown key test_config.{err}.")),
+        }
+    }
+    // missing keys of an override are taken from the tolerance, wherever it is in the file
+    config.tolerance_overrides = HashMap::new();
+    if let Some(overrides) = tolerance_overrides {
+        for (oracle, tolerance) in as_hash("test_config.tolerance_overrides", overrides)? {
+            let strategy = QueryGenStrategy::try_from(oracle)
+                .map_err(|err| format!("Unable to parse test_config.tolerance_overrides: {err}"))?;
+            let tolerance = parse_tolerance(
+                "test_config.tolerance_overrides",
+                tolerance,
+                config.tolerance,
+            )?;
+            config.tolerance_overrides.insert(strategy, tolerance);
         }
     }
+    for oracle in AVERAGE_ORACLES {
+        config
+            .tolerance_overrides
+            .entry(oracle)
+            .or_insert(config.tolerance.average());
+    }
+    Ok(config)
+}
 
-    config
+fn parse_tolerance(name: &str, yaml: &Yaml, base: Tolerance) -> Result<Tolerance, String> {
+    let mut tolerance = base;
+    for (key, value) in as_hash(name, yaml)? {
+        match as_key(key)? {
+            "absolute" => tolerance.absolute = parse_non_negative("absolute", value)?,
+            "relative" => tolerance.relative = parse_non_negative("relative", value)?,
+            "ulps" => tolerance.ulps = parse_u32("ulps", value)?.into(),
+            "nan_equals_nan" => tolerance.nan_equals_nan = parse_bool("nan_equals_nan", value)?,
+            "signed_zeros_equal" => {
+                tolerance.signed_zeros_equal = parse_bool("signed_zeros_equal", value)?
+            }
+            err => return Err(format!("Unknown key {name}.{err}.")),
+        }
+    }
+    Ok(tolerance)
 }
 
-fn parse_test_config(yaml: &Yaml) -> TestConfig {
-    let mut config = TestConfig::default();
-    if let Yaml::Hash(ref hash) = yaml {
-        for (key, value) in hash {
-            if let Yaml::String(ref key_str) = key {
-                match key_str.as_str() {
-                    // "oracles" => {
-                    //     config.oracles = parse_string_array(value);
-                    // }
-                    "test_run_count" => {
-                        let Some(test_run_count) = value.as_i64() else {
-                            log::error!("Unable to parse test_run_count");
-                            continue;
-                        };
-                        config.test_run_count = test_run_count as u32;
-                    }
-                    "oracle_reps" => {
-                        let Some(oracle_reps) = value.as_i64() else {
-                            log::error!("Unable to parse oracle_reps");
-                            continue;
-                        };
-                        config.oracle_reps = oracle_reps as u32;
-                    }
-                    "test_case_count" => {
-                        let Some(test_case_count) = value.as_i64() else {
-                            log::error!("Unable to parse test_case_count");
-                            continue;
-                        };
-                        config.test_case_count = test_case_count as u32;
-                    }
-                    "field_count" => {
-                        let Some(field_count) = value.as_i64() else {
-                            log::error!("Unable to parse field_count");
-                            continue;
-                        };
-                        config.field_count = field_count as u32;
-                    }
-                    "record_count" => {
-                        let Some(record_count) = value.as_i64() else {
-                            log::error!("Unable to parse record_count");
-                            continue;
-                        };
-                        config.record_count = record_count as u32;
-                    }
-                    "physical_source_count" => {
-                        let Some(physical_source_count) = value.as_i64() else {
-                            log::error!("Unable to parse physical_source_count");
-                            continue;
-                        };
-                        config.physical_source_count = physical_source_count as u32;
-                    }
-                    "predicate_depth" => {
-                        let Some(predicate_depth) = value.as_i64() else {
-                            log::error!("Unable to parse predicate_depth");
-                            continue;
-                        };
-                        config.predicate_depth = predicate_depth as u32;
-                    }
-                    _ => {}
-                }
+fn parse_time_stamp_disorder(yaml: &Yaml) -> Result<TimeStampDisorder, String> {
+    let mut disorder = TimeStampDisorder::default();
+    for (key, value) in as_hash("test_config.time_stamp_disorder", yaml)? {
+        match as_key(key)? {
+            "max_jitter" => disorder.max_jitter = parse_u32("max_jitter", value)?,
+            "late_ratio" => disorder.late_ratio = parse_ratio("late_ratio", value)?,
+            "max_late_delay" => disorder.max_late_delay = parse_u32("max_late_delay", value)?,
+            "gap_ratio" => disorder.gap_ratio = parse_ratio("gap_ratio", value)?,
+            "max_gap" => disorder.max_gap = parse_u32("max_gap", value)?,
+            "shared_time_stamps" => {
+                disorder.shared_time_stamps = parse_bool("shared_time_stamps", value)?
             }
+            "allowed_lateness" => disorder.allowed_lateness = parse_u32("allowed_lateness", value)?,
+            err => {
+                return Err(format!(
+                    "Unknown key test_config.time_stamp_disorder.{err}."
+                ))
+            }
+        }
+    }
+    if disorder.max_gap > MAX_TIME_STAMP_GAP {
+        return Err(format!(
+            "max_gap {} exceeds the maximal gap of {MAX_TIME_STAMP_GAP} ms.",
+            disorder.max_gap
+        ));
+    }
+    Ok(disorder)
+}
+
+/// Parses a mapping from oracles to a mapping from aliases to field names.
+fn parse_column_aliases(yaml: &Yaml) -> Result<HashMap<QueryGenStrategy, ColumnAliases>, String> {
+    let mut column_aliases = HashMap::new();
+    for (oracle, aliases) in as_hash("test_config.column_aliases", yaml)? {
+        let strategy = QueryGenStrategy::try_from(oracle)
+            .map_err(|err| format!("Unable to parse test_config.column_aliases: {err}"))?;
+        let mut oracle_aliases = ColumnAliases::default();
+        for (alias, name) in as_hash("test_config.column_aliases", aliases)? {
+            let alias = as_key(alias)?;
+            oracle_aliases.insert(alias, parse_string(alias, name)?);
         }
+        column_aliases.insert(strategy, oracle_aliases);
     }
-    config
+    Ok(column_aliases)
 }
 
-fn parse_path_config(yaml: &Yaml) -> FilePathConfig {
+fn parse_path_config(yaml: &Yaml) -> Result<FilePathConfig, String> {
     let mut config = FilePathConfig::default();
-    if let Yaml::Hash(ref hash) = yaml {
-        for (key, value) in hash {
-            if let Yaml::String(ref key_str) = key {
-                match key_str.as_str() {
-                    "base" => {
-                        let Some(base) = value.as_str() else {
-                            log::error!("Unable to parse base");
-                            continue;
-                        };
-                        config.base = PathBuf::from(base);
-                    }
-                    "test_run" => {
-                        let Some(test_run) = value.as_str() else {
-                            log::error!("Unable to parse test_run");
-                            continue;
-                        };
-                        config.test_run = PathBuf::from(test_run);
-                    }
-                    "stream_config" => {
-                        let Some(stream_config) = value.as_str() else {
-                            log::error!("Unable to parse stream_config");
-                            continue;
-                        };
-                        config.stream_config = PathBuf::from(stream_config);
-                    }
-                    "results" => {
-                        let Some(results) = value.as_str() else {
-                            log::error!("Unable to parse results");
-                            continue;
-                        };
-                        config.results = PathBuf::from(results);
-                    }
-                    "coordinator_config_file" => {
-                        let Some(coordinator_config_file) = value.as_str() else {
-                            log::error!("Unable to parse coordinator_config_file");
-                            continue;
-                        };
-                        config.coordinator_config_file = PathBuf::from(coordinator_config_file);
-                    }
-                    "worker_configs" => {
-                        let Some(worker_configs) = value.as_str() else {
-                            log::error!("Unable to parse worker_configs");
-                            continue;
-                        };
-                        config.worker_configs = PathBuf::from(worker_configs);
-                    }
-                    "test_sets_file" => {
-                        let Some(test_sets_file) = value.as_str() else {
-                            log::error!("Unable to parse test_sets_file");
-                            continue;
-                        };
-                        config.test_sets_file = PathBuf::from(test_sets_file);
-                    }
-                    "test_set_execs_file" => {
-                        let Some(test_set_execs_file) = value.as_str() else {
-                            log::error!("Unable to parse test_set_execs_file");
-                            continue;
-                        };
-                        config.test_set_execs_file = PathBuf::from(test_set_execs_file);
-                    }
-                    "test_set_results_file" => {
-                        let Some(test_set_results_file) = value.as_str() else {
-                            log::error!("Unable to parse test_set_results_file");
-                            continue;
-                        };
-                        config.test_set_results_file = PathBuf::from(test_set_results_file);
-                    }
-                    _ => {}
-                }
-            }
+    for (key, value) in as_hash("path_config", yaml)? {
+        let key_str = as_key(key)?;
+        let path = parse_path(key_str, value)?;
+        match key_str {
+            "base" => config.base = path,
+            "test_run" => config.test_run = path,
+            "stream_config" => config.stream_config = path,
+            "results" => config.results = path,
+            "coordinator_config_file" => config.coordinator_config_file = path,
+            "worker_configs" => config.worker_configs = path,
+            "clusters" => config.clusters = path,
+            "checkpoints" => config.checkpoints = path,
+            "completed_stage_file" => config.completed_stage_file = path,
+            "seed_file" => config.seed_file = path,
+            "test_sets_file" => config.test_sets_file = path,
+            "test_set_execs_file" => config.test_set_execs_file = path,
+            "test_set_results_file" => config.test_set_results_file = path,
+            "diff_reports_file" => config.diff_reports_file = path,
+            err => return Err(format!("Unknown key path_config.{err}.")),
         }
     }
-    config
+    Ok(config)
 }
 
-fn parse_query_comp_config(yaml: &Yaml) -> NesQueryCompilerConfig {
+fn parse_query_comp_config(yaml: &Yaml) -> Result<NesQueryCompilerConfig, String> {
     let mut config = NesQueryCompilerConfig::default();
-    if let Yaml::Hash(ref hash) = yaml {
-        for (key, value) in hash {
-            if let Yaml::String(ref key_str) = key {
-                match key_str.as_str() {
-                    "pipelining_strategy" => {
-                        let pipelining_strategy = match value.try_into() {
-                            Ok(ok) => ok,
-                            Err(err) => {
-                                log::error!("Unable to parse compilation_strategy: {err}");
-                                continue;
-                            }
-                        };
-                        config.pipelining_strategy = pipelining_strategy;
-                    }
-
-                    "compilation_strategy" => {
-                        let compilation_strategy = match value.try_into() {
-                            Ok(ok) => ok,
-                            Err(err) => {
-                                log::error!("Unable to parse compilation_strategy: {err}");
-                                continue;
-                            }
-                        };
-                        config.compilation_strategy = compilation_strategy;
-                    }
-
-                    "output_buffer_optimization_level" => {
-                        let output_buffer_optimization_level = match value.try_into() {
-                            Ok(ok) => ok,
-                            Err(err) => {
-                                log::error!("Unable to parse output_buffer_optimization_level: {err}");
-                                continue;
-                            }
-                        };
-                        config.output_buffer_optimization_level = output_buffer_optimization_level;
-                    }
-
-                    "windowing_strategy" => {
-                        let windowing_strategy = match value.try_into() {
-                            Ok(ok) => ok,
-                            Err(err) => {
-                                log::error!("Unable to parse windowing_strategy: {err}");
-                                continue;
-                            }
-                        };
-                        config.windowing_strategy = windowing_strategy;
-                    }
-
-                    "query_compiler_type" => {
-                        let query_compiler_type = match value.try_into() {
-                            Ok(ok) => ok,
-                            Err(err) => {
-                                log::error!("Unable to parse query_compiler_type: {err}");
-                                continue;
-                            }
-                        };
-                        config.query_compiler_type = query_compiler_type;
-                    }
-                    _ => {}
-                }
+    for (key, value) in as_hash("query_comp_config", yaml)? {
+        match as_key(key)? {
+            "pipelining_strategy" => {
+                config.pipelining_strategy = parse_try_from("pipelining_strategy", value)?
+            }
+            "compilation_strategy" => {
+                config.compilation_strategy = parse_try_from("compilation_strategy", value)?
+            }
+            "output_buffer_optimization_level" => {
+                config.output_buffer_optimization_level =
+                    parse_try_from("output_buffer_optimization_level", value)?
+            }
+            "windowing_strategy" => {
+                config.windowing_strategy = parse_try_from("windowing_strategy", value)?
+            }
+            "query_compiler_type" => {
+                config.query_compiler_type = parse_try_from("query_compiler_type", value)?
+            }
+            err => return Err(format!("Unknown key query_comp_config.{err}.")),
+        }
+    }
+    Ok(config)
+}
+
+fn parse_opt_config(yaml: &Yaml) -> Result<NesOptConfig, String> {
+    let mut config = NesOptConfig::default();
+    for (key, value) in as_hash("opt_config", yaml)? {
+        match as_key(key)? {
+            "join_optimization_mode" => {
+                config.join_optimization_mode = parse_try_from("join_optimization_mode", value)?
+            }
+            "enable_incremental_placement" => {
+                config.enable_incremental_placement =
+                    parse_bool("enable_incremental_placement", value)?
+            }
+            "placement_amendment_thread_count" => {
+                config.placement_amendment_thread_count =
+                    parse_u32("placement_amendment_thread_count", value)?
+            }
+            "placement_amendment_mode" => {
+                config.placement_amendment_mode = parse_try_from("placement_amendment_mode", value)?
+            }
+            "enable_nemo_placement" => {
+                config.enable_nemo_placement = parse_bool("enable_nemo_placement", value)?
+            }
+            "perform_only_source_operator_expansion" => {
+                config.perform_only_source_operator_expansion =
+                    parse_bool("perform_only_source_operator_expansion", value)?
+            }
+            "perform_advance_semantic_validation" => {
+                config.perform_advance_semantic_validation =
+                    parse_bool("perform_advance_semantic_validation", value)?
             }
+            "memory_layout_policy" => {
+                config.memory_layout_policy = parse_try_from("memory_layout_policy", value)?
+            }
+            "allow_exhaustive_containment_check" => {
+                config.allow_exhaustive_containment_check =
+                    parse_bool("allow_exhaustive_containment_check", value)?
+            }
+            "query_merger_rule" => {
+                config.query_merger_rule = parse_try_from("query_merger_rule", value)?
+            }
+            err => return Err(format!("Unknown key opt_config.{err}.")),
+        }
+    }
+    Ok(config)
+}
+
+fn parse_runner_config(yaml: &Yaml) -> Result<RunnerConfig, String> {
+    let mut config = LancerConfig::default().runner_config;
+    for (key, value) in as_hash("runner_config", yaml)? {
+        match as_key(key)? {
+            "coordinator_exec_path" => {
+                config.coordinator_exec_path = parse_path("coordinator_exec_path", value)?
+            }
+            "worker_exec_path" => config.worker_exec_path = parse_path("worker_exec_path", value)?,
+            "output_io" => config.output_io = parse_try_from("output_io", value)?,
+            "startup_timeout" => {
+                config.readiness.startup_timeout = parse_duration("startup_timeout", value)?
+            }
+            "initial_backoff" => {
+                config.readiness.initial_backoff = parse_duration("initial_backoff", value)?
+            }
+            "max_backoff" => config.readiness.max_backoff = parse_duration("max_backoff", value)?,
+            err => return Err(format!("Unknown key runner_config.{err}.")),
+        }
+    }
+    Ok(config)
+}
+
+fn parse_net_config(yaml: &Yaml) -> Result<NetworkConfig, String> {
+    let mut config = NetworkConfig::default();
+    for (key, value) in as_hash("net_config", yaml)? {
+        match as_key(key)? {
+            "coord_ip" => config.coord_ip = parse_from_str::<Ipv4Addr>("coord_ip", value)?,
+            "coord_rest_port" => config.coord_rest_port = parse_u16("coord_rest_port", value)?,
+            "coord_rpc_port" => config.coord_rpc_port = parse_u16("coord_rpc_port", value)?,
+            "cluster_count" => config.cluster_count = parse_u16("cluster_count", value)?,
+            "cluster_port_stride" => {
+                config.cluster_port_stride = parse_u16("cluster_port_stride", value)?
+            }
+            err => return Err(format!("Unknown key net_config.{err}.")),
         }
     }
-    config
+    // the coordinator ports of every cluster must fit, the worker ports are checked by StreamGen
+    config.check_ports(0)?;
+    Ok(config)
+}
+
+// helpers
+
+fn as_hash<'a>(name: &str, yaml: &'a Yaml) -> Result<&'a Hash, String> {
+    let Yaml::Hash(hash) = yaml else {
+        return Err(format!("Unable to parse {name}: Expected a mapping."));
+    };
+    Ok(hash)
+}
+
+fn as_key(key: &Yaml) -> Result<&str, String> {
+    let Yaml::String(key_str) = key else {
+        return Err(format!("Unable to parse key {:?}: Expected a string.", key));
+    };
+    Ok(key_str.as_str())
+}
+
+/// Parses a seed of the whole `u64` range. Seeds above `i64::MAX` are no yaml integers, so they
+/// can also be given as strings.
+fn parse_seed(value: &Yaml) -> Result<u64, String> {
+    let seed = match value {
+        Yaml::Integer(seed) => u64::try_from(*seed).ok(),
+        Yaml::Real(seed) | Yaml::String(seed) => seed.trim().parse::<u64>().ok(),
+        _ => return Err("Unable to parse seed: Expected an integer.".into()),
+    };
+    seed.ok_or_else(|| {
+        format!(
+            "Unable to parse seed: {value:?} is not between 0 and {}.",
+            u64::MAX
+        )
+    })
+}
+
+fn parse_u32(name: &str, value: &Yaml) -> Result<u32, String> {
+    let Some(int) = value.as_i64() else {
+        return Err(format!("Unable to parse {name}: Expected an integer."));
+    };
+    u32::try_from(int).map_err(|_| format!("Unable to parse {name}: {int} is out of range."))
+}
+
+fn parse_u16(name: &str, value: &Yaml) -> Result<u16, String> {
+    let Some(int) = value.as_i64() else {
+        return Err(format!("Unable to parse {name}: Expected an integer."));
+    };
+    u16::try_from(int).map_err(|_| format!("Unable to parse {name}: {int} is out of range."))
+}
+
+fn parse_bool(name: &str, value: &Yaml) -> Result<bool, String> {
+    let Some(bool) = value.as_bool() else {
+        return Err(format!("Unable to parse {name}: Expected a boolean."));
+    };
+    Ok(bool)
+}
+
+/// Parses a probability between 0 and 1.
+fn parse_ratio(name: &str, value: &Yaml) -> Result<f64, String> {
+    let ratio = match value {
+        Yaml::Integer(ratio) => *ratio as f64,
+        Yaml::Real(_) => value.as_f64().unwrap_or(-1.0),
+        _ => return Err(format!("Unable to parse {name}: Expected a number.")),
+    };
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(format!(
+            "Unable to parse {name}: {ratio} is not between 0 and 1."
+        ));
+    }
+    Ok(ratio)
+}
+
+fn parse_non_negative(name: &str, value: &Yaml) -> Result<f64, String> {
+    let number = match value {
+        Yaml::Integer(number) => *number as f64,
+        Yaml::Real(_) => value.as_f64().unwrap_or(-1.0),
+        _ => return Err(format!("Unable to parse {name}: Expected a number.")),
+    };
+    if number.is_nan() || number < 0.0 {
+        return Err(format!("Unable to parse {name}: {number} is negative."));
+    }
+    Ok(number)
+}
+
+fn parse_string<'a>(name: &str, value: &'a Yaml) -> Result<&'a str, String> {
+    value
+        .as_str()
+        .ok_or_else(|| format!("Unable to parse {name}: Expected a string."))
+}
+
+fn parse_path(name: &str, value: &Yaml) -> Result<PathBuf, String> {
+    let Some(path) = value.as_str() else {
+        return Err(format!("Unable to parse {name}: Expected a string."));
+    };
+    Ok(PathBuf::from(path))
+}
+
+/// Parses a duration given in seconds. Fractional seconds are allowed.
+fn parse_duration(name: &str, value: &Yaml) -> Result<Duration, String> {
+    let secs = match value {
+        Yaml::Integer(secs) => *secs as f64,
+        Yaml::Real(_) => value.as_f64().unwrap_or(-1.0),
+        _ => return Err(format!("Unable to parse {name}: Expected seconds.")),
+    };
+    Duration::try_from_secs_f64(secs).map_err(|_| {
+        format!("Unable to parse {name}: Expected a finite, positive number of seconds.")
+    })
+}
+
+fn parse_from_str<T>(name: &str, value: &Yaml) -> Result<T, String>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let Some(str) = value.as_str() else {
+        return Err(format!("Unable to parse {name}: Expected a string."));
+    };
+    str.parse()
+        .map_err(|err| format!("Unable to parse {name}: {err}"))
+}
+
+fn parse_try_from<T>(name: &str, value: &Yaml) -> Result<T, String>
+where
+    for<'a> T: TryFrom<&'a Yaml, Error = String>,
+{
+    T::try_from(value).map_err(|err| format!("Unable to parse {name}: {err}"))
+}
+
+#[cfg(test)]
+mod load_config_tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use yaml_rust2::YamlLoader;
+
+    use crate::{
+        runner::runner_config::OutputIO, stages::Stages, test_case_gen::oracle::QueryGenStrategy,
+    };
+
+    use super::parse_config;
+
+    fn parse(content: &str) -> Result<crate::LancerConfig, String> {
+        let docs = YamlLoader::load_from_str(content).unwrap();
+        parse_config(&docs[0])
+    }
+
+    #[test]
+    fn full_config() {
+        let config = parse(
+            r#"
+test_config:
+  oracles: [Filter, KeyAggAvg]
+  seed: 1234
+  test_run_count: 3
+  predicate_depth: 6
+  operator_weights:
+    Remainder: 3
+    Negate: 0
+  mixed_type_ratio: 0.25
+  edge_value_ratio: 0
+  schema_file: "configs/schema.yml"
+  time_stamp_disorder:
+    max_jitter: 50
+    allowed_lateness: 100
+    shared_time_stamps: true
+  column_aliases:
+    WinPartMin:
+      ts: start
+  tolerance_overrides:
+    AggSum: {ulps: 16}
+  tolerance:
+    relative: 1.0e-3
+    nan_equals_nan: false
+path_config:
+  base: "other_files"
+runner_config:
+  coordinator_exec_path: "/nes/nesCoordinator"
+  output_io: Print
+  startup_timeout: 10
+net_config:
+  coord_ip: "127.0.0.2"
+  coord_rest_port: 8081
+  cluster_count: 4
+opt_config:
+  enable_nemo_placement: true
+  query_merger_rule: HybridCompleteQueryMergerRule
+query_comp_config:
+  windowing_strategy: SLICING
+test_case_timeout: 1.5
+skip_to_stage: QueryExec
+resume: true
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.test_config.oracles,
+            vec![QueryGenStrategy::Filter, QueryGenStrategy::KeyAggAvg]
+        );
+        assert_eq!(config.test_config.seed, 1234);
+        assert_eq!(config.test_config.test_run_count, 3);
+        assert_eq!(config.test_config.predicate_depth, 6);
+        assert_eq!(config.test_config.operator_weights.weight("Remainder"), 3);
+        assert_eq!(config.test_config.operator_weights.weight("Negate"), 0);
+        assert_eq!(config.test_config.operator_weights.weight("Absolute"), 1);
+        assert_eq!(config.test_config.mixed_type_ratio, 0.25);
+        assert_eq!(config.test_config.edge_value_ratio, 0.0);
+        assert_eq!(
+            config.test_config.schema_file,
+            Some(PathBuf::from("configs/schema.yml"))
+        );
+        let tolerance = config.test_config.tolerance(QueryGenStrategy::AggSum);
+        assert_eq!(tolerance.ulps, 16);
+        assert_eq!(tolerance.relative, 1e-3);
+        assert!(!tolerance.nan_equals_nan);
+        assert_eq!(
+            config
+                .test_config
+                .tolerance(QueryGenStrategy::AggAvg)
+                .relative,
+            1e-3
+        );
+        let disorder = &config.test_config.time_stamp_disorder;
+        assert_eq!(disorder.max_jitter, 50);
+        assert!(disorder.shared_time_stamps);
+        assert!(!disorder.has_late_records());
+        let aliases = config
+            .test_config
+            .column_aliases(QueryGenStrategy::WinPartMin);
+        assert_eq!(aliases.resolve("ts"), "start");
+        assert_eq!(aliases.resolve("end"), "end");
+        assert_eq!(
+            config.test_config.column_aliases(QueryGenStrategy::Filter),
+            Default::default()
+        );
+        assert_eq!(config.path_config.base.to_str(), Some("other_files"));
+        assert_eq!(
+            config.runner_config.coordinator_exec_path.to_str(),
+            Some("/nes/nesCoordinator")
+        );
+        assert!(config.runner_config.output_io == OutputIO::Print);
+        assert_eq!(
+            config.runner_config.readiness.startup_timeout,
+            Duration::from_secs(10)
+        );
+        assert_eq!(config.net_config.coord_ip.to_string(), "127.0.0.2");
+        assert_eq!(config.net_config.coord_rest_port, 8081);
+        assert_eq!(config.net_config.cluster_count, 4);
+        assert!(config.opt_config.enable_nemo_placement);
+        assert_eq!(config.test_case_timeout, Duration::from_millis(1500));
+        assert_eq!(config.skip_to_stage, Stages::QueryExec);
+        assert!(config.resume);
+    }
+
+    #[test]
+    fn cluster_ports_exceeding_the_port_range_are_rejected() {
+        let net_config = |cluster_count| {
+            parse(&format!(
+                "net_config:\n  coord_rest_port: 4050\n  cluster_count: {cluster_count}\n"
+            ))
+        };
+        let config = net_config(500).unwrap();
+        assert!(config.net_config.cluster(499).is_ok());
+        assert!(config.net_config.cluster(700).is_err());
+        assert!(net_config(700).is_err());
+    }
+
+    #[test]
+    fn cluster_ports_overlapping_other_clusters_are_rejected() {
+        // the RPC port of cluster 40 is the REST port 8000 of cluster 0
+        assert!(parse("net_config:\n  cluster_count: 40\n").is_ok());
+        assert!(parse("net_config:\n  cluster_count: 41\n").is_err());
+        let config = parse("net_config:\n  cluster_count: 2\n").unwrap();
+        assert!(config.net_config.check_ports(49).is_ok());
+        assert!(config.net_config.check_ports(50).is_err());
+        let config = parse("net_config:\n  coord_rest_port: 4010\n").unwrap();
+        assert!(config.net_config.check_ports(4).is_ok());
+        assert!(config.net_config.check_ports(5).is_err());
+    }
+
+    #[test]
+    fn seeds_cover_the_u64_range() {
+        let seed = |yaml: &str| parse(&format!("test_config:\n  seed: {yaml}\n"));
+        assert_eq!(seed("0").unwrap().test_config.seed, 0);
+        assert_eq!(
+            seed("18446744073709551615").unwrap().test_config.seed,
+            u64::MAX
+        );
+        assert_eq!(
+            seed("\"9223372036854775808\"").unwrap().test_config.seed,
+            1 << 63
+        );
+        assert!(seed("-1").is_err());
+        assert!(seed("18446744073709551616").is_err());
+        assert!(seed("1.5").is_err());
+        assert!(seed("seed").is_err());
+    }
+
+    #[test]
+    fn average_tolerances_derive_from_the_tolerance() {
+        let config = parse("test_config:\n  tolerance: {relative: 1.0e-8, ulps: 2}\n").unwrap();
+        let average = config.test_config.tolerance(QueryGenStrategy::KeyAggAvg);
+        assert_eq!(average.relative, 1e-4);
+        assert_eq!(average.ulps, 2);
+
+        let config = parse(
+            "test_config:\n  tolerance_overrides: {AggAvg: {ulps: 8}}\n  tolerance: {ulps: 2}\n",
+        )
+        .unwrap();
+        let test_config = &config.test_config;
+        assert_eq!(test_config.tolerance(QueryGenStrategy::AggAvg).ulps, 8);
+        assert_eq!(test_config.tolerance(QueryGenStrategy::KeyAggAvg).ulps, 2);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(parse("test_confg:\n  test_run_count: 1\n").is_err());
+        assert!(parse("test_config:\n  test_run_cnt: 1\n").is_err());
+        assert!(parse("net_config:\n  coord_port: 1\n").is_err());
+        assert!(parse("test_config:\n  operator_weights:\n    Modulo: 1\n").is_err());
+        assert!(parse("test_config:\n  mixed_type_ratio: 1.5\n").is_err());
+        assert!(parse("test_config:\n  tolerance:\n    epsilon: 1\n").is_err());
+        assert!(parse("test_config:\n  tolerance:\n    relative: -1\n").is_err());
+    }
+
+    #[test]
+    fn wrong_types_are_rejected() {
+        assert!(parse("test_config:\n  test_run_count: many\n").is_err());
+        assert!(parse("test_config:\n  oracles: [NoSuchOracle]\n").is_err());
+        assert!(parse("net_config:\n  coord_rest_port: 70000\n").is_err());
+        assert!(parse("test_case_timeout: -1\n").is_err());
+        assert!(parse("test_case_timeout: .inf\n").is_err());
+        assert!(parse("test_case_timeout: .nan\n").is_err());
+    }
+
+    #[test]
+    fn time_stamp_gaps_are_bounded() {
+        let gap = |max_gap: u32| {
+            parse(&format!(
+                "test_config:\n  time_stamp_disorder:\n    max_gap: {max_gap}\n"
+            ))
+        };
+        assert!(gap(86_400_000).is_ok());
+        assert!(gap(86_400_001).is_err());
+    }
 }
@@ -1,111 +1,206 @@
-use std::{env::args, fs, path::{Path, PathBuf}};
+use std::{env::args, fs, path::Path, process};
 
 use achilles::{
-    check_test_sets, eval::check_results::write_test_set_results_to_file, extract_diffs_operation::extract_diffs_operatoion, generate_files, generate_test_sets, load_config::load_config, process_test_case::process_test_sets::process_test_sets, replay_exec::{replay_exec, ReplayExec, TestSetLocation}, stages::Stages, stream_schema::read_stream_schema_from_file, summery::summary_operation, test_case_exec::{read_test_set_execs_from_file, write_test_set_execs_to_file}, test_case_gen::{
-        query_id::TestCaseId,
-        test_case::{read_test_sets_to_file, write_test_sets_to_file},
-    }, LancerConfig
+    check_test_sets,
+    checkpoint::{clear_completed_stage, read_completed_stage, write_completed_stage},
+    cli::{parse_args, CliParseResult, OperationMode, USAGE},
+    eval::{
+        check_reference::check_references,
+        check_results::write_test_set_results_to_file,
+        diff_report::{diff_test_sets, write_diff_reports_to_file},
+    },
+    extract_diffs_operation::extract_diffs_operatoion,
+    generate_files, generate_test_sets,
+    load_config::load_config,
+    process_test_case::process_test_sets::process_test_sets,
+    reducer::reduce_test_set::reduce_operation,
+    replay_exec::replay_exec,
+    seed::{read_master_seed, resume_master_seed, write_master_seed},
+    stages::Stages,
+    stream_schema::read_stream_schema_from_file,
+    summery::summary_operation,
+    test_case_exec::{read_test_set_execs_from_file, write_test_set_execs_to_file},
+    test_case_gen::test_case::{read_test_sets_to_file, write_test_sets_to_file},
+    AchillesError, LancerConfig,
 };
 
-#[derive(Default, Clone)]
-pub enum OperationMode {
-    #[default]
-    Default,
-    ReplayExec(ReplayExec),
-    Summary,
-    ExtractDiffs,
-}
-
 #[tokio::main]
 async fn main() {
     simple_logger::init_with_level(log::Level::Debug)
         .expect("Simple Logger should not fail to init!");
+    let cli_args = match parse_args(args().skip(1)) {
+        Ok(CliParseResult::Args(cli_args)) => cli_args,
+        Ok(CliParseResult::Help) => {
+            println!("{USAGE}");
+            return;
+        }
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            process::exit(1);
+        }
+    };
     let mut config = LancerConfig::default();
-    if let Some(str) = args().collect::<Vec<String>>().get(1) {
-        log::info!("Use config path: {str}");
-        let config_path = Path::new(str);
-        config = load_config(&config_path);
+    if let Some(path) = &cli_args.config_path {
+        log::info!("Use config path: {:?}", path);
+        config = match load_config(Path::new(path)) {
+            Ok(config) => config,
+            Err(err) => {
+                log::error!("{err}");
+                process::exit(1);
+            }
+        };
     }
-    let operation_mode = OperationMode::default();
-    // let operation_mode = OperationMode::Summary;
-    // let operation_mode = OperationMode::ReplayExec(ReplayExec::test_set(0, 2));
-    // let operation_mode = OperationMode::ExtractDiffs;
-    match operation_mode {
+    let operation_mode = match cli_args.apply(&mut config) {
+        Ok(operation_mode) => operation_mode,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            process::exit(1);
+        }
+    };
+    let result = match operation_mode {
         OperationMode::Default => default_operation(&config).await,
         OperationMode::ReplayExec(replay) => replay_exec(&replay, &config).await,
-        OperationMode::Summary => summary_operation(&config),
+        OperationMode::Reduce(target) => reduce_operation(&target, &config).await,
+        OperationMode::Summary(export_dir) => summary_operation(&config, export_dir.as_deref()),
         OperationMode::ExtractDiffs => extract_diffs_operatoion(&config),
+    };
+    if let Err(err) = result {
+        log::error!("{err}");
+        process::exit(1);
     }
 }
 
-async fn default_operation(config: &LancerConfig) {
-    if config.skip_to_stage <= Stages::StreamGen {
-        reset_base_dir(config);
+/// Executes all test runs. A failing test run is logged and the campaign continues with the next one.
+/// With `resume` the existing files are kept and test runs that already completed
+/// `stop_after_stage` are skipped.
+async fn default_operation(config: &LancerConfig) -> Result<(), AchillesError> {
+    let mut config = config.clone();
+    if config.resume {
+        let path = config.path_config.base();
+        fs::create_dir_all(path).map_err(|err| AchillesError::io(path, err))?;
+        resume_master_seed(&mut config)?;
+    } else if config.skip_to_stage <= Stages::StreamGen {
+        reset_base_dir(&config)?;
+        write_master_seed(&config)?;
+    } else {
+        reuse_master_seed(&mut config)?;
     }
+    log::info!("Using seed {}.", config.test_config.seed);
+    let config = &config;
+    let mut failed_runs = Vec::new();
     for id in 0..config.test_config.test_run_count {
+        if config.resume && read_completed_stage(id, config) >= Some(config.stop_after_stage) {
+            log::info!("Skipping finished test run {id}.");
+            continue;
+        }
         log::info!("Starting test run {id}.");
-        test_run(id, &config).await;
+        if let Err(err) = test_run(id, config).await {
+            log::error!("Test run {id} failed: {err}");
+            failed_runs.push(id);
+        }
+    }
+    if !failed_runs.is_empty() {
+        log::warn!(
+            "{} of {} test runs failed: {:?}",
+            failed_runs.len(),
+            config.test_config.test_run_count,
+            failed_runs
+        );
+    }
+    Ok(())
+}
+
+/// A campaign that keeps its files reuses its persisted master seed, so the stages that run again
+/// derive the same seeds as the existing files.
+fn reuse_master_seed(config: &mut LancerConfig) -> Result<(), AchillesError> {
+    match read_master_seed(config)? {
+        Some(seed) => {
+            if seed != config.test_config.seed {
+                log::info!("Reusing seed {seed} of the existing campaign.");
+            }
+            config.test_config.seed = seed;
+        }
+        None => log::warn!(
+            "No seed found in {:?}, the existing files may have been generated with another seed.",
+            config.path_config.base()
+        ),
     }
+    Ok(())
 }
 
-fn reset_base_dir(config: &LancerConfig) {
+fn reset_base_dir(config: &LancerConfig) -> Result<(), AchillesError> {
     let override_files = true;
     let path = config.path_config.base();
     if override_files && path.exists() {
         log::info!("Deleting existing files in path: {:?}", path);
-        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(path).map_err(|err| AchillesError::io(path, err))?;
     }
-    fs::create_dir(path).unwrap();
+    fs::create_dir(path).map_err(|err| AchillesError::io(path, err))
 }
 
-fn create_base_dir(test_run_id: u32, config: &LancerConfig) {
-    log::info!(
-        "Creating test-run-{test_run_id} directory in {:?}",
-        config.path_config.test_run(test_run_id)
-    );
-    if let Err(err) = fs::create_dir(config.path_config.test_run(test_run_id)) {
-        log::error!("{err}");
-        return;
-    }
-    log::info!(
-        "Creating result directory in {:?}",
-        config.path_config.result(test_run_id)
-    );
-    if let Err(err) = fs::create_dir(config.path_config.result(test_run_id)) {
-        log::error!("{err}");
-        return;
+fn create_base_dir(test_run_id: u32, config: &LancerConfig) -> Result<(), AchillesError> {
+    let path = config.path_config.test_run(test_run_id);
+    if path.exists() {
+        // left behind by a test run that was interrupted during StreamGen or QueryGen
+        log::info!("Deleting existing files in path: {:?}", path);
+        fs::remove_dir_all(&path).map_err(|err| AchillesError::io(&path, err))?;
     }
+    log::info!("Creating test-run-{test_run_id} directory in {:?}", path);
+    fs::create_dir(&path).map_err(|err| AchillesError::io(&path, err))?;
+    let path = config.path_config.result(test_run_id);
+    log::info!("Creating result directory in {:?}", path);
+    fs::create_dir(&path).map_err(|err| AchillesError::io(&path, err))
 }
 
-async fn test_run(id: u32, config: &LancerConfig) {
-    if config.skip_to_stage <= Stages::StreamGen {
-        create_base_dir(id, config);
-        generate_files(id, config);
+async fn test_run(id: u32, config: &LancerConfig) -> Result<(), AchillesError> {
+    // a resumed test run continues after the last stage it completed
+    let completed_stage = if config.resume {
+        read_completed_stage(id, config)
+    } else {
+        clear_completed_stage(id, config)?;
+        None
+    };
+    let is_stage_enabled =
+        |stage: Stages| config.is_stage_enabled(stage) && Some(stage) > completed_stage;
+
+    if is_stage_enabled(Stages::StreamGen) {
+        create_base_dir(id, config)?;
+        generate_files(id, config)?;
+        write_completed_stage(id, config, Stages::StreamGen)?;
     } else {
         log::info!("Skipping Stage StreamGen...");
     }
 
-    if config.skip_to_stage <= Stages::QueryGen {
-        let schema = read_stream_schema_from_file(id, &config);
-        let test_sets = generate_test_sets(id, &config, &schema);
-        write_test_sets_to_file(id, &config, &test_sets);
+    if is_stage_enabled(Stages::QueryGen) {
+        let schema = read_stream_schema_from_file(id, config)?;
+        let test_sets = generate_test_sets(id, config, &schema);
+        write_test_sets_to_file(id, config, &test_sets)?;
+        write_completed_stage(id, config, Stages::QueryGen)?;
     } else {
         log::info!("Skipping Stage QueryGen...");
     }
 
-    if config.skip_to_stage <= Stages::QueryExec {
-        let test_sets = read_test_sets_to_file(id, &config);
-        let test_set_execs = process_test_sets(id, &config, test_sets).await;
-        write_test_set_execs_to_file(id, &config, &test_set_execs);
+    if is_stage_enabled(Stages::QueryExec) {
+        let test_sets = read_test_sets_to_file(id, config)?;
+        let test_set_execs = process_test_sets(id, config, test_sets).await;
+        write_test_set_execs_to_file(id, config, &test_set_execs)?;
+        write_completed_stage(id, config, Stages::QueryExec)?;
     } else {
         log::info!("Skipping Stage QueryExec...");
     }
 
-    if config.skip_to_stage <= Stages::Evaluation {
-        let test_set_execs = read_test_set_execs_from_file(id, &config);
-        let test_set_results = check_test_sets(&test_set_execs);
-        write_test_set_results_to_file(id, &config, &test_set_results)
+    if is_stage_enabled(Stages::Evaluation) {
+        let test_set_execs = read_test_set_execs_from_file(id, config)?;
+        let mut test_set_results = check_test_sets(&test_set_execs, config);
+        if config.test_config.reference_check {
+            check_references(id, config, &test_set_execs, &mut test_set_results);
+        }
+        write_test_set_results_to_file(id, config, &test_set_results)?;
+        let diff_reports = diff_test_sets(&test_set_execs, &test_set_results, config);
+        write_diff_reports_to_file(id, config, &diff_reports)?;
+        write_completed_stage(id, config, Stages::Evaluation)?;
     } else {
         log::info!("Skipping Stage Evaluation...");
     }
+    Ok(())
 }
@@ -1,56 +1,136 @@
-use std::{
-    path::{Path, PathBuf},
-    thread,
-    time::{Duration, Instant},
-};
-
-use nes_rust_client::runtime::{
-    nebula_stream_runtime::{NebulaStreamRuntime, PlacementStrategy},
-    query_state::QueryState,
-};
+use std::{collections::VecDeque, sync::Mutex, thread, time::Instant};
 
 use crate::{
-    runner::runner::Runner,
+    checkpoint::{
+        clear_test_set_checkpoints, load_test_set_checkpoints, store_test_set_checkpoint,
+    },
+    cluster::{nes_cluster::NesCluster, ClusterFactory, ClusterManager, QueryRuntime},
+    error::AchillesError,
     test_case_exec::{TestCaseExec, TestCaseExecStatus, TestSetExec},
-    test_case_gen::test_case::{self, TestCase, TestSet},
+    test_case_gen::test_case::{TestCase, TestSet},
     LancerConfig,
 };
 
+/// Executes all test sets on NebulaStream, see `process_test_sets_with`.
 pub async fn process_test_sets(
     test_run_id: u32,
     config: &LancerConfig,
     test_sets: Vec<TestSet>,
+) -> Vec<TestSetExec> {
+    process_test_sets_with(&NesCluster, test_run_id, config, test_sets).await
+}
+
+/// Executes all test sets on the clusters of `clusters` and checkpoints every `TestSetExec` as soon
+/// as its test set is done. With `resume` the test sets with a checkpoint are not executed again.
+pub async fn process_test_sets_with(
+    clusters: &impl ClusterFactory,
+    test_run_id: u32,
+    config: &LancerConfig,
+    test_sets: Vec<TestSet>,
 ) -> Vec<TestSetExec> {
     let start_time = Instant::now();
+    let mut checkpoints = if config.resume {
+        load_test_set_checkpoints(test_run_id, config)
+    } else {
+        if let Err(err) = clear_test_set_checkpoints(test_run_id, config) {
+            log::warn!("Unable to clear checkpoints: {err}");
+        }
+        Default::default()
+    };
     let mut results = Vec::new();
-    for test_set in test_sets.into_iter() {
-        log::debug!("Starting test set {}.", test_set.id);
-        let result = process_test_set(test_run_id, test_set, config).await;
-        results.push(result);
+    let mut pending = Vec::new();
+    for test_set in test_sets {
+        match checkpoints.remove(&test_set.id) {
+            Some(exec) => results.push(exec),
+            None => pending.push(test_set),
+        }
+    }
+    if !results.is_empty() {
+        log::info!(
+            "Resuming with {} of {} test sets already executed.",
+            results.len(),
+            results.len() + pending.len()
+        );
+    }
+
+    let cluster_count = config.net_config.cluster_count.max(1);
+    if cluster_count > 1 {
+        results.extend(process_test_sets_on_clusters(
+            clusters,
+            test_run_id,
+            config,
+            pending,
+            cluster_count,
+        ));
+    } else {
+        for test_set in pending {
+            log::debug!("Starting test set {}.", test_set.id);
+            let result =
+                process_test_set_on_cluster(clusters, test_run_id, test_set, config, 0).await;
+            checkpoint(test_run_id, config, &result);
+            results.push(result);
+        }
     }
     log::info!("All test sets done in {:?}.", start_time.elapsed());
+    // keep the order of the sequential execution
+    results.sort_by_key(|result| result.id);
     results
 }
 
-fn files_in_dir(path: &Path) -> Vec<PathBuf> {
-    let mut files = Vec::new();
-    if !path.is_dir() {
-        log::warn!("{:?} is not a dir.", path);
-        return files;
+/// A failed checkpoint only costs the execution of the test set on resume, so it does not abort
+/// the test run.
+fn checkpoint(test_run_id: u32, config: &LancerConfig, test_set_exec: &TestSetExec) {
+    if let Err(err) = store_test_set_checkpoint(test_run_id, config, test_set_exec) {
+        log::warn!("Unable to checkpoint test set {}: {err}", test_set_exec.id);
     }
-    let Ok(entries) = std::fs::read_dir(path) else {
-        log::warn!("Failed to read {:?}", path);
-        return files;
-    };
-    for entry in entries {
-        if let Ok(entry) = entry {
-            let path = entry.path();
-            if path.is_file() {
-                files.push(path);
-            }
-        }
-    }
-    files
+}
+
+/// Distributes the test sets over `cluster_count` clusters. Every cluster runs on its own thread
+/// with its own runtime, because the runner blocks while waiting for NebulaStream. A cluster takes
+/// the next test set from a shared queue as soon as it is done with the previous one.
+fn process_test_sets_on_clusters(
+    clusters: &impl ClusterFactory,
+    test_run_id: u32,
+    config: &LancerConfig,
+    test_sets: Vec<TestSet>,
+    cluster_count: u16,
+) -> Vec<TestSetExec> {
+    let queue = Mutex::new(VecDeque::from(test_sets));
+    thread::scope(|scope| {
+        let handles = (0..cluster_count)
+            .map(|cluster_id| {
+                let queue = &queue;
+                scope.spawn(move || {
+                    let runtime = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .expect("Building the runtime of a cluster should not fail!");
+                    let mut results = Vec::new();
+                    loop {
+                        let next = queue.lock().unwrap().pop_front();
+                        let Some(test_set) = next else {
+                            break;
+                        };
+                        log::debug!("Starting test set {} on cluster {cluster_id}.", test_set.id);
+                        let result = runtime.block_on(process_test_set_on_cluster(
+                            clusters,
+                            test_run_id,
+                            test_set,
+                            config,
+                            cluster_id,
+                        ));
+                        checkpoint(test_run_id, config, &result);
+                        results.push(result);
+                    }
+                    results
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Cluster thread should not panic!"))
+            .collect::<Vec<_>>()
+    })
 }
 
 pub async fn process_single_test_case(
@@ -58,31 +138,24 @@ pub async fn process_single_test_case(
     test_case: TestCase,
     config: &LancerConfig,
 ) -> TestCaseExec {
-    let sleep_duration = Duration::from_secs(2);
-
-    //setup runner
-    // FIXME: This should be nicer
-    let mut runner_config = config.runner_config.clone();
-    runner_config.coordinator_config_path =
-        Some(config.path_config.coordinator_config(test_run_id));
-    runner_config.worker_config_path =
-        files_in_dir(&config.path_config.worker_configs(test_run_id));
-    let mut runner = Runner::new(runner_config);
-    runner.start_all();
-
-    //setup runtime
-    let runtime = NebulaStreamRuntime::new(
-        config.net_config.coord_ip.to_string(),
-        config.net_config.coord_rest_port.into(),
-    );
+    process_single_test_case_with(&NesCluster, test_run_id, test_case, config).await
+}
 
-    let test_case_exec = process_test_case_with_pre_check(
-        &runtime,
-        &mut runner,
-        test_case,
-        &config.test_case_timeout,
-    )
-    .await;
+pub async fn process_single_test_case_with(
+    clusters: &impl ClusterFactory,
+    test_run_id: u32,
+    test_case: TestCase,
+    config: &LancerConfig,
+) -> TestCaseExec {
+    let (mut runner, runtime) = match clusters.cluster(test_run_id, config, 0) {
+        Ok(cluster) => cluster,
+        Err(err) => return startup_failed(test_case, err.to_string()),
+    };
+    if let Err(err) = start_cluster(&mut runner, &runtime, config).await {
+        return startup_failed(test_case, err.to_string());
+    }
+    let test_case_exec =
+        process_test_case_with_pre_check(&runtime, &mut runner, test_case, config).await;
     runner.stop_all();
     test_case_exec
 }
@@ -92,66 +165,104 @@ pub async fn process_test_set(
     test_set: TestSet,
     config: &LancerConfig,
 ) -> TestSetExec {
-    let sleep_duration = Duration::from_secs(2);
-
-    //setup runner
-    // FIXME: This should be nicer
-    let mut runner_config = config.runner_config.clone();
-    runner_config.coordinator_config_path =
-        Some(config.path_config.coordinator_config(test_run_id));
-    runner_config.worker_config_path =
-        files_in_dir(&config.path_config.worker_configs(test_run_id));
-    let mut runner = Runner::new(runner_config);
-    runner.start_all();
-
-    //setup runtime
-    let runtime = NebulaStreamRuntime::new(
-        config.net_config.coord_ip.to_string(),
-        config.net_config.coord_rest_port.into(),
-    );
+    process_test_set_on_cluster(&NesCluster, test_run_id, test_set, config, 0).await
+}
 
-    // run test cases
-    let origin = process_test_case_with_pre_check(
-        &runtime,
-        &mut runner,
-        test_set.origin,
-        &config.test_case_timeout,
-    )
-    .await;
-    post_check_restart(&origin, &mut runner).await;
-    let mut others = Vec::new();
-    for other in test_set.others.into_iter() {
-        let other_exec = process_test_case_with_pre_check(
-            &runtime,
-            &mut runner,
-            other,
-            &config.test_case_timeout,
-        )
-        .await;
-        post_check_restart(&other_exec, &mut runner).await;
-        others.push(other_exec);
-        thread::sleep(sleep_duration);
+pub async fn process_test_set_on_cluster(
+    clusters: &impl ClusterFactory,
+    test_run_id: u32,
+    test_set: TestSet,
+    config: &LancerConfig,
+    cluster_id: u16,
+) -> TestSetExec {
+    let (mut runner, runtime) = match clusters.cluster(test_run_id, config, cluster_id) {
+        Ok(cluster) => cluster,
+        Err(err) => {
+            log::error!("Failed to set up cluster {cluster_id}: {err}");
+            let reason = err.to_string();
+            return TestSetExec {
+                id: test_set.id,
+                strategy: test_set.strategy,
+                origin: startup_failed(test_set.origin, reason.clone()),
+                others: test_set
+                    .others
+                    .into_iter()
+                    .map(|test_case| startup_failed(test_case, reason.clone()))
+                    .collect(),
+            };
+        }
+    };
+    let mut startup = start_cluster(&mut runner, &runtime, config).await;
+
+    // run test cases, once the cluster fails to start the remaining test cases are not executed
+    let mut execs = Vec::new();
+    for test_case in std::iter::once(test_set.origin).chain(test_set.others) {
+        let exec = match startup {
+            Ok(()) => {
+                process_test_case_with_pre_check(&runtime, &mut runner, test_case, config).await
+            }
+            Err(ref err) => startup_failed(test_case, err.to_string()),
+        };
+        if startup.is_ok() {
+            startup = post_check_restart(&exec, &mut runner, &runtime, config).await;
+        }
+        execs.push(exec);
+    }
+    if let Err(err) = startup {
+        log::error!("Failed to start NebulaStream on cluster {cluster_id}: {err}");
     }
     // clean up
     runner.stop_all();
+    let origin = execs.remove(0);
     TestSetExec {
         id: test_set.id,
         strategy: test_set.strategy,
         origin,
-        others,
+        others: execs,
+    }
+}
+
+/// Starts the runner and waits until the REST API of the coordinator accepts requests.
+async fn start_cluster(
+    runner: &mut impl ClusterManager,
+    runtime: &impl QueryRuntime,
+    config: &LancerConfig,
+) -> Result<(), AchillesError> {
+    runner.start_all()?;
+    let readiness = &config.runner_config.readiness;
+    let start_time = Instant::now();
+    let mut backoff = readiness.initial_backoff;
+    while !runtime.is_connected().await {
+        if start_time.elapsed() > readiness.startup_timeout {
+            runner.stop_all();
+            return Err(AchillesError::Runtime(
+                "Unable to connect to the REST API of the coordinator.".into(),
+            ));
+        }
+        thread::sleep(backoff);
+        backoff = readiness.next_backoff(backoff);
     }
+    Ok(())
+}
+
+fn startup_failed(test_case: TestCase, reason: String) -> TestCaseExec {
+    log::warn!("Failed to execute test case {}: {reason}", test_case.id);
+    TestCaseExec::from_with(test_case, TestCaseExecStatus::StartupFailed(reason))
 }
 
 /// returns false if the pre check encountered an error, that is if the runner encountered an error
 /// or if the runtime was unable to connect. If everything is working as expected this function
 /// returns true
 // FIXME: actually do something
-async fn pre_check(runner: &mut Runner, runtime: &NebulaStreamRuntime) -> bool {
-    if !runner.health_check().unwrap().all_running() {
+async fn pre_check(runner: &mut impl ClusterManager, runtime: &impl QueryRuntime) -> bool {
+    if !runner
+        .health_check()
+        .is_ok_and(|status| status.all_running())
+    {
         log::warn!("NebulaStream crashed.");
         return false;
     }
-    if !runtime.check_connection().await {
+    if !runtime.is_connected().await {
         log::warn!("Unable to connect to NebulaStream.");
         return false;
     }
@@ -159,36 +270,39 @@ async fn pre_check(runner: &mut Runner, runtime: &NebulaStreamRuntime) -> bool {
 }
 
 /// check if the exec was correct, else restart the runner.
-async fn post_check_restart(exec: &TestCaseExec, runner: &mut Runner) {
+async fn post_check_restart(
+    exec: &TestCaseExec,
+    runner: &mut impl ClusterManager,
+    runtime: &impl QueryRuntime,
+    config: &LancerConfig,
+) -> Result<(), AchillesError> {
     if exec.status == TestCaseExecStatus::Success {
-        return;
+        return Ok(());
     }
     runner.stop_all();
-    runner.start_all();
+    start_cluster(runner, runtime, config).await
 }
 
 async fn process_test_case_with_pre_check(
-    runtime: &NebulaStreamRuntime,
-    runner: &mut Runner,
+    runtime: &impl QueryRuntime,
+    runner: &mut impl ClusterManager,
     test_case: TestCase,
-    timeout_duration: &Duration,
+    config: &LancerConfig,
 ) -> TestCaseExec {
-    if !pre_check(runner, &runtime).await {
+    if !pre_check(runner, runtime).await {
         log::warn!("Skipping test case.");
         return TestCaseExec::from_with(test_case, TestCaseExecStatus::Skipped);
     }
-    process_test_case(&runtime, runner, test_case, &timeout_duration).await
+    process_test_case(runtime, runner, test_case, config).await
 }
 
 async fn process_test_case(
-    runtime: &NebulaStreamRuntime,
-    runner: &mut Runner,
+    runtime: &impl QueryRuntime,
+    runner: &mut impl ClusterManager,
     test_case: TestCase,
-    timeout_duration: &Duration,
+    config: &LancerConfig,
 ) -> TestCaseExec {
-    let response = runtime
-        .execute_query(&test_case.query, PlacementStrategy::BottomUp)
-        .await;
+    let response = runtime.register_query(&test_case).await;
     let id = match response {
         Ok(id) => id,
         Err(err) => {
@@ -203,12 +317,21 @@ async fn process_test_case(
     );
 
     let start_time = Instant::now();
+    let readiness = &config.runner_config.readiness;
+    let mut poll_interval = readiness.initial_backoff;
     // Wait for query to stop
     loop {
-        thread::sleep(Duration::from_secs(2));
+        thread::sleep(poll_interval);
+        poll_interval = readiness.next_backoff(poll_interval);
         // first check if nes is still healthy
-        // TODO: Improve Check if runner is healthy
-        let runner_status = runner.health_check().unwrap();
+        let runner_status = match runner.health_check() {
+            Ok(runner_status) => runner_status,
+            Err(err) => {
+                let error_str = format!("Unable to check the health of NebulaStream: {err}");
+                log::warn!("Failed to execute test case {}: {error_str}", test_case.id);
+                return TestCaseExec::from_with(test_case, TestCaseExecStatus::Failed(error_str));
+            }
+        };
         if !runner_status.all_running() {
             log::warn!(
                 "Failed to execute test_case {}: NebulaStream Crashed.",
@@ -221,7 +344,7 @@ async fn process_test_case(
 
         // then get query state
         log::trace!("Checking if test_case {} has stopped", test_case.id);
-        let Ok(Some(status)) = runtime.query_status(id).await else {
+        let Ok(is_stopped) = runtime.is_query_stopped(id).await else {
             log::warn!(
                 "Failed to execute test_case {}: Unable to get query state.",
                 test_case.id
@@ -232,12 +355,12 @@ async fn process_test_case(
             );
         };
 
-        if status == QueryState::Stopped {
+        if is_stopped {
             log::info!("Executed test case {} successful.", test_case.id);
             return TestCaseExec::from_with(test_case, TestCaseExecStatus::Success);
         }
 
-        let is_timeout = start_time.elapsed() > *timeout_duration;
+        let is_timeout = start_time.elapsed() > config.test_case_timeout;
         if is_timeout {
             log::warn!("Failed to execute test case {}: Timed out.", test_case.id);
             // To stop the query is apparently not effective because nebula stream is stuck
@@ -0,0 +1,2 @@
+pub mod query_reducer;
+pub mod reduce_test_set;
@@ -0,0 +1,186 @@
+use crate::ron_tree::RonNode;
+
+/// Returns simplified variants of `query`, the RON tree of a serialized `Query`, ordered from the
+/// largest to the smallest simplification.
+///
+/// A simplification replaces every occurrence of an expression by one of its children with the
+/// same data type or shrinks a numeric literal towards zero. Replacing all occurrences at once keeps
+/// queries like `filter(p) union filter(!p)` a valid partition of the input. For the same reason an
+/// expression is never replaced by a child that also occurs outside of it, e.g. `!p` by `p`.
+pub fn query_candidates(query: &RonNode) -> Vec<RonNode> {
+    let mut exprs: Vec<&RonNode> = Vec::new();
+    let mut literals: Vec<&RonNode> = Vec::new();
+    query.walk(&mut |node| {
+        let list = match node.name() {
+            Some("Binary") | Some("Unary") => &mut exprs,
+            Some("Literal") => &mut literals,
+            _ => return,
+        };
+        if !list.contains(&node) {
+            list.push(node);
+        }
+    });
+
+    let mut candidates = Vec::new();
+    for expr in exprs {
+        let Some(expr_type) = data_type(expr) else {
+            continue;
+        };
+        for child in expr_children(expr) {
+            let only_inside_expr = query.count(child) == query.count(expr) * expr.count(child);
+            if data_type(child) == Some(expr_type) && only_inside_expr {
+                let mut candidate = query.clone();
+                candidate.replace_all(expr, child);
+                candidates.push(candidate);
+            }
+        }
+    }
+    for literal in literals {
+        for shrunk in shrink_literal(literal) {
+            let mut candidate = query.clone();
+            candidate.replace_all(literal, &shrunk);
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+fn expr_children(expr: &RonNode) -> Vec<&RonNode> {
+    let keys: &[&str] = match expr.name() {
+        Some("Binary") => &["lhs", "rhs"],
+        Some("Unary") => &["expr"],
+        _ => &[],
+    };
+    keys.iter().filter_map(|key| expr.field(key)).collect()
+}
+
+/// Returns the `data_type` of an expression. For fields and literals this is the first
+/// `data_type` found in their body.
+fn data_type(expr: &RonNode) -> Option<&RonNode> {
+    match expr.name() {
+        Some("Binary") | Some("Unary") => expr.field("data_type"),
+        Some("Field") | Some("Literal") => {
+            let mut found = None;
+            expr.walk(&mut |node| {
+                if found.is_none() {
+                    if let RonNode::Struct(fields) = node {
+                        found = fields
+                            .iter()
+                            .find(|(k, _)| k == "data_type")
+                            .map(|(_, v)| v);
+                    }
+                }
+            });
+            found
+        }
+        _ => None,
+    }
+}
+
+/// Returns copies of `literal` whose numeric value is moved towards zero.
+fn shrink_literal(literal: &RonNode) -> Vec<RonNode> {
+    let mut value = None;
+    literal.walk(&mut |node| {
+        if let RonNode::Literal(raw) = node {
+            if value.is_none() && parse_number(raw).is_some() {
+                value = Some(node);
+            }
+        }
+    });
+    let Some(value @ RonNode::Literal(raw)) = value else {
+        return Vec::new();
+    };
+    shrink_number(raw)
+        .into_iter()
+        .map(|shrunk| {
+            let mut literal = literal.clone();
+            literal.replace_all(value, &RonNode::Literal(shrunk));
+            literal
+        })
+        .collect()
+}
+
+/// Strips the quotes of a string literal and returns its content if it is a number.
+fn parse_number(raw: &str) -> Option<&str> {
+    let number = raw
+        .strip_prefix('"')
+        .and_then(|raw| raw.strip_suffix('"'))
+        .unwrap_or(raw);
+    number.parse::<f64>().is_ok().then_some(number)
+}
+
+fn shrink_number(raw: &str) -> Vec<String> {
+    let quoted = raw.starts_with('"');
+    let Some(number) = parse_number(raw) else {
+        return Vec::new();
+    };
+    let mut shrunk = Vec::new();
+    if let Ok(int) = number.parse::<i128>() {
+        for candidate in [0, int / 2] {
+            if candidate.abs() < int.abs() && !shrunk.contains(&candidate.to_string()) {
+                shrunk.push(candidate.to_string());
+            }
+        }
+    } else if let Ok(float) = number.parse::<f64>() {
+        for candidate in [0.0, float.trunc(), (float / 2.0).trunc()] {
+            if candidate.abs() < float.abs() && !shrunk.contains(&format!("{candidate:.1}")) {
+                shrunk.push(format!("{candidate:.1}"));
+            }
+        }
+    }
+    shrunk
+        .into_iter()
+        .map(|number| match quoted {
+            true => format!("\"{number}\""),
+            false => number,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod query_reducer_tests {
+    use super::{query_candidates, shrink_number, RonNode};
+
+    fn parse(input: &str) -> RonNode {
+        RonNode::parse(input).unwrap()
+    }
+
+    #[test]
+    fn replaces_all_occurrences_with_children() {
+        let p = "Binary((lhs:Field((name:\"a\",data_type:Bool)),rhs:Field((name:\"b\",data_type:Bool)),data_type:Bool,operator:And))";
+        let query = parse(&format!(
+            "(ops:[Filter({p}),Filter(Unary((expr:{p},data_type:Bool,operator:Negate)))])"
+        ));
+        let candidates: Vec<String> = query_candidates(&query)
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let a = "Field((name:\"a\",data_type:Bool))";
+        assert!(candidates.contains(&format!(
+            "(ops:[Filter({a}),Filter(Unary((expr:{a},data_type:Bool,operator:Negate)))])"
+        )));
+        // the negation cannot be replaced because it would break the partition
+        assert!(candidates.iter().all(|c| c.matches("Negate").count() == 1));
+    }
+
+    #[test]
+    fn keeps_data_types() {
+        let query = parse("Binary((lhs:Field((name:\"a\",data_type:Int)),rhs:Literal((value:\"8\",data_type:Int)),data_type:Bool,operator:Less))");
+        let candidates: Vec<String> = query_candidates(&query)
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(2, candidates.len());
+        assert!(candidates[0].contains("value:\"0\""));
+        assert!(candidates[1].contains("value:\"4\""));
+    }
+
+    #[test]
+    fn shrink_numbers() {
+        assert_eq!(vec!["0", "-5"], shrink_number("-11"));
+        assert_eq!(vec!["\"0\""], shrink_number("\"1\""));
+        assert_eq!(vec!["0.0", "7.0", "3.0"], shrink_number("7.5"));
+        assert!(shrink_number("0").is_empty());
+        assert!(shrink_number("\"abc\"").is_empty());
+    }
+}
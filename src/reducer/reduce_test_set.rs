@@ -0,0 +1,433 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use csv::StringRecord;
+use nes_rust_client::query::{sink::Sink, Query};
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::{
+    eval::{
+        check_results::{check_test_set, read_test_set_results_from_file},
+        evaluator::ResultRelation,
+    },
+    process_test_case::process_test_sets::process_test_set,
+    ron_tree::RonNode,
+    test_case_exec::TestCaseExecStatus,
+    test_case_gen::{
+        oracle::QueryGenStrategy,
+        query_id::TestCaseId,
+        test_case::{write_test_sets_to_file, TestCase, TestSet},
+    },
+    yaml_util::load_yaml_array,
+    AchillesError, LancerConfig,
+};
+
+use super::query_reducer::query_candidates;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 200;
+
+/// Location of the test case to reduce. If no test case is given the first other test case with a
+/// `Diff` in `test_set_results.yml` is reduced.
+#[derive(Clone)]
+pub struct ReduceTestSet {
+    run_id: u32,
+    test_set_id: u32,
+    test_case_id: Option<TestCaseId>,
+    max_attempts: u32,
+}
+
+impl ReduceTestSet {
+    pub fn new(
+        run_id: u32,
+        test_set_id: u32,
+        test_case_id: Option<TestCaseId>,
+        max_attempts: Option<u32>,
+    ) -> Self {
+        Self {
+            run_id,
+            test_set_id,
+            test_case_id,
+            max_attempts: max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS),
+        }
+    }
+}
+
+/// Worker config and data file of a physical source of the reproducer.
+#[derive(Clone)]
+struct SourceData {
+    worker_file_name: String,
+    worker_config: Yaml,
+    data_path: PathBuf,
+    header: StringRecord,
+    records: Vec<StringRecord>,
+}
+
+struct Reducer {
+    run_id: u32,
+    /// Config whose `path_config` points to the reproducer directory.
+    config: LancerConfig,
+    test_set_id: u32,
+    seed: u64,
+    strategy: QueryGenStrategy,
+    origin: TestCase,
+    other_id: TestCaseId,
+    other_result_path: PathBuf,
+    attempts: u32,
+    max_attempts: u32,
+}
+
+/// Reduces a test case with a `Diff` result to a minimal reproducer. The predicates of the other
+/// query are simplified, unnecessary physical sources are dropped and the records of the data files
+/// are bisected as long as the `Diff` still reproduces. The result is written as a standalone base
+/// directory that can be replayed with `replay --base <DIR>`.
+pub async fn reduce_operation(
+    target: &ReduceTestSet,
+    config: &LancerConfig,
+) -> Result<(), AchillesError> {
+    let path = reduce_test_set(target, config)
+        .await
+        .map_err(|err| AchillesError::Reduce(format!("Failed to reduce test set: {err}")))?;
+    log::info!(
+        "Reduced test set written to {:?}. Replay it with: replay --base {} --run {} --set {}",
+        path,
+        path.display(),
+        target.run_id,
+        target.test_set_id
+    );
+    Ok(())
+}
+
+async fn reduce_test_set(target: &ReduceTestSet, config: &LancerConfig) -> Result<PathBuf, String> {
+    let test_sets = load_yaml_array::<TestSet>(&config.path_config.test_sets(target.run_id))
+        .map_err(|err| err.to_string())?;
+    let Some(test_set) = test_sets
+        .into_iter()
+        .find(|set| set.id == target.test_set_id)
+    else {
+        return Err(format!("Unable to load test set {}", target.test_set_id));
+    };
+    let other_id = match target.test_case_id {
+        Some(id) => id,
+        None => first_diff(target, config)?,
+    };
+    let TestCaseId::Other(other_num) = other_id else {
+        return Err("Only other test cases can be reduced.".into());
+    };
+    let Some(other) = test_set.test_case(other_id) else {
+        return Err(format!("Unable to load test case {other_id}"));
+    };
+    let query = ron::to_string(other.query()).map_err(|err| err.to_string())?;
+    let query = RonNode::parse(&query)?;
+
+    let mut reduce_config = config.clone();
+    reduce_config.path_config.base = PathBuf::from(format!(
+        "./reduced/test-run-{}-set-{}-other{other_num}",
+        target.run_id, target.test_set_id
+    ));
+    let sources = create_reproducer_dir(target.run_id, config, &reduce_config)?;
+
+    let results_path = reduce_config.path_config.result(target.run_id);
+    let origin_result_path = results_path.join(format!("test-set{}-origin.csv", test_set.id));
+    let mut origin_query = test_set.origin.query.clone();
+    origin_query.set_sink(Sink::csv_file(&origin_result_path, false));
+    let mut reducer = Reducer {
+        run_id: target.run_id,
+        config: reduce_config,
+        test_set_id: test_set.id,
+        seed: test_set.seed,
+        strategy: test_set.strategy,
+        origin: TestCase::origin(origin_query, origin_result_path),
+        other_id,
+        other_result_path: results_path
+            .join(format!("test-set{}-other{other_num}.csv", test_set.id)),
+        attempts: 0,
+        max_attempts: target.max_attempts,
+    };
+    reducer.reduce(query, sources).await?;
+    Ok(reducer.config.path_config.base.clone())
+}
+
+fn first_diff(target: &ReduceTestSet, config: &LancerConfig) -> Result<TestCaseId, String> {
+    if !config.path_config.test_set_results(target.run_id).exists() {
+        return Err("No test set results found, run the Evaluation stage or pass --case.".into());
+    }
+    read_test_set_results_from_file(target.run_id, config)
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .filter(|set| set.id == target.test_set_id)
+        .flat_map(|set| set.test_cases)
+        .find(|test_case| test_case.relation == ResultRelation::Diff)
+        .map(|test_case| test_case.id)
+        .ok_or_else(|| format!("Test set {} has no Diff.", target.test_set_id))
+}
+
+/// Creates the directory layout of a test run in the base of `reduce_config` and loads the
+/// physical sources of the original test run.
+fn create_reproducer_dir(
+    run_id: u32,
+    config: &LancerConfig,
+    reduce_config: &LancerConfig,
+) -> Result<Vec<SourceData>, String> {
+    let base = reduce_config.path_config.base();
+    if base.exists() {
+        log::info!("Deleting existing files in path: {:?}", base);
+        fs::remove_dir_all(base).map_err(|err| err.to_string())?;
+    }
+    let data_dir = reduce_config.path_config.test_run(run_id).join("data");
+    for dir in [
+        reduce_config.path_config.result(run_id),
+        reduce_config.path_config.worker_configs(run_id),
+        data_dir.clone(),
+    ] {
+        fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    }
+    fs::copy(
+        config.path_config.coordinator_config(run_id),
+        reduce_config.path_config.coordinator_config(run_id),
+    )
+    .map_err(|err| err.to_string())?;
+
+    let entries =
+        fs::read_dir(config.path_config.worker_configs(run_id)).map_err(|err| err.to_string())?;
+    let mut worker_paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    worker_paths.sort();
+    worker_paths
+        .iter()
+        .map(|path| load_source(path, &data_dir))
+        .collect()
+}
+
+/// Loads a worker config and its data file and points the config to a file in `data_dir`.
+fn load_source(worker_path: &Path, data_dir: &Path) -> Result<SourceData, String> {
+    let content = fs::read_to_string(worker_path).map_err(|err| err.to_string())?;
+    let mut docs = YamlLoader::load_from_str(&content).map_err(|err| err.to_string())?;
+    let Some(mut worker_config) = docs.pop() else {
+        return Err(format!("Worker config {:?} is empty.", worker_path));
+    };
+    let file_path = physical_source_file_path(&mut worker_config)
+        .ok_or_else(|| format!("Worker config {:?} needs one CSV source.", worker_path))?;
+    let original_path = PathBuf::from(file_path.as_str());
+    let Some(file_name) = original_path.file_name() else {
+        return Err(format!("Invalid filePath in {:?}.", worker_path));
+    };
+    let data_path = data_dir.join(file_name);
+    *file_path = data_path.to_string_lossy().to_string();
+
+    let mut reader = csv::Reader::from_path(&original_path).map_err(|err| err.to_string())?;
+    let header = reader.headers().map_err(|err| err.to_string())?.clone();
+    let records = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    Ok(SourceData {
+        worker_file_name: worker_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        worker_config,
+        data_path,
+        header,
+        records,
+    })
+}
+
+/// Returns `physicalSources[0].configuration.filePath` of a worker config with exactly one
+/// physical source.
+fn physical_source_file_path(worker_config: &mut Yaml) -> Option<&mut String> {
+    let Yaml::Hash(worker) = worker_config else {
+        return None;
+    };
+    let Some(Yaml::Array(sources)) = worker.get_mut(&Yaml::String("physicalSources".into())) else {
+        return None;
+    };
+    let [Yaml::Hash(source)] = sources.as_mut_slice() else {
+        return None;
+    };
+    let Some(Yaml::Hash(configuration)) = source.get_mut(&Yaml::String("configuration".into()))
+    else {
+        return None;
+    };
+    match configuration.get_mut(&Yaml::String("filePath".into())) {
+        Some(Yaml::String(path)) => Some(path),
+        _ => None,
+    }
+}
+
+impl Reducer {
+    async fn reduce(
+        &mut self,
+        mut query: RonNode,
+        mut sources: Vec<SourceData>,
+    ) -> Result<(), String> {
+        if !self.execute(&query, &sources).await? {
+            return Err(format!(
+                "The Diff of test case {} does not reproduce.",
+                self.other_id
+            ));
+        }
+        let query_size = query.size();
+        let record_count: usize = sources.iter().map(|source| source.records.len()).sum();
+        let source_count = sources.len();
+
+        self.reduce_query(&mut query, &sources).await;
+        self.reduce_sources(&query, &mut sources).await;
+        self.reduce_records(&query, &mut sources).await;
+        if self.attempts >= self.max_attempts {
+            log::warn!("Stopped reduction after {} attempts.", self.attempts);
+        }
+
+        log::info!(
+            "Reduced query from {query_size} to {} nodes, physical sources from {source_count} to {} and records from {record_count} to {}.",
+            query.size(),
+            sources.len(),
+            sources.iter().map(|source| source.records.len()).sum::<usize>()
+        );
+        // leave the reproducer with the result files of the reduced test set
+        if !self.execute(&query, &sources).await? {
+            log::warn!("The reduced test set did not reproduce the Diff in its last execution.");
+        }
+        let test_set = self.test_set(self.parse_query(&query)?);
+        write_test_sets_to_file(self.run_id, &self.config, &[test_set])
+            .map_err(|err| err.to_string())
+    }
+
+    async fn reduce_query(&mut self, query: &mut RonNode, sources: &[SourceData]) {
+        'reduce: while !self.is_exhausted() {
+            for candidate in query_candidates(query) {
+                if self.reproduces(&candidate, sources).await {
+                    *query = candidate;
+                    continue 'reduce;
+                }
+            }
+            break;
+        }
+    }
+
+    async fn reduce_sources(&mut self, query: &RonNode, sources: &mut Vec<SourceData>) {
+        let mut index = 0;
+        while index < sources.len() && sources.len() > 1 && !self.is_exhausted() {
+            let mut candidate = sources.clone();
+            candidate.remove(index);
+            if self.reproduces(query, &candidate).await {
+                *sources = candidate;
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Removes chunks of records from each data file, halving the chunk size until single records
+    /// are removed.
+    async fn reduce_records(&mut self, query: &RonNode, sources: &mut Vec<SourceData>) {
+        for source_index in 0..sources.len() {
+            let mut chunk_size = (sources[source_index].records.len() / 2).max(1);
+            while !sources[source_index].records.is_empty() && !self.is_exhausted() {
+                let mut start = 0;
+                while start < sources[source_index].records.len() && !self.is_exhausted() {
+                    let end = (start + chunk_size).min(sources[source_index].records.len());
+                    let mut candidate = sources.clone();
+                    candidate[source_index].records.drain(start..end);
+                    if self.reproduces(query, &candidate).await {
+                        *sources = candidate;
+                    } else {
+                        start = end;
+                    }
+                }
+                if chunk_size == 1 {
+                    break;
+                }
+                chunk_size /= 2;
+            }
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.attempts >= self.max_attempts
+    }
+
+    /// Returns true if the `Diff` reproduces with `query` and `sources`. Every execution counts
+    /// towards `max_attempts`.
+    async fn reproduces(&mut self, query: &RonNode, sources: &[SourceData]) -> bool {
+        if self.is_exhausted() {
+            return false;
+        }
+        self.attempts += 1;
+        match self.execute(query, sources).await {
+            Ok(reproduces) => reproduces,
+            Err(err) => {
+                log::debug!("Skipping reduction candidate: {err}");
+                false
+            }
+        }
+    }
+
+    async fn execute(&self, query: &RonNode, sources: &[SourceData]) -> Result<bool, String> {
+        let query = self.parse_query(query)?;
+        self.write_sources(sources)?;
+        let test_set_exec = process_test_set(self.run_id, self.test_set(query), &self.config).await;
+        if test_set_exec.origin.status != TestCaseExecStatus::Success {
+            return Ok(false);
+        }
+        let reproduces = check_test_set(&test_set_exec, &self.config)
+            .iter()
+            .any(|result| result.id == self.other_id && result.relation == ResultRelation::Diff);
+        Ok(reproduces)
+    }
+
+    fn parse_query(&self, query: &RonNode) -> Result<Query, String> {
+        let mut query: Query =
+            ron::from_str(&query.to_string()).map_err(|err| format!("Invalid query: {err}"))?;
+        query.set_sink(Sink::csv_file(&self.other_result_path, false));
+        Ok(query)
+    }
+
+    fn test_set(&self, query: Query) -> TestSet {
+        TestSet {
+            id: self.test_set_id,
+            seed: self.seed,
+            strategy: self.strategy,
+            origin: self.origin.clone(),
+            others: vec![TestCase::new(
+                self.other_id,
+                query,
+                self.other_result_path.clone(),
+            )],
+        }
+    }
+
+    /// Replaces the worker configs and data files of the reproducer with `sources`.
+    fn write_sources(&self, sources: &[SourceData]) -> Result<(), String> {
+        let worker_dir = self.config.path_config.worker_configs(self.run_id);
+        let data_dir = self.config.path_config.test_run(self.run_id).join("data");
+        for dir in [&worker_dir, &data_dir] {
+            fs::remove_dir_all(dir).map_err(|err| err.to_string())?;
+            fs::create_dir(dir).map_err(|err| err.to_string())?;
+        }
+        for source in sources {
+            let mut out_str = String::new();
+            let mut emitter = YamlEmitter::new(&mut out_str);
+            emitter
+                .dump(&source.worker_config)
+                .map_err(|err| err.to_string())?;
+            let mut file = fs::File::create(worker_dir.join(&source.worker_file_name))
+                .map_err(|err| err.to_string())?;
+            write!(file, "{out_str}").map_err(|err| err.to_string())?;
+
+            let mut wtr =
+                csv::Writer::from_path(&source.data_path).map_err(|err| err.to_string())?;
+            wtr.write_record(&source.header)
+                .map_err(|err| err.to_string())?;
+            for record in source.records.iter() {
+                wtr.write_record(record).map_err(|err| err.to_string())?;
+            }
+            wtr.flush().map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+}
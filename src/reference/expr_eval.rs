@@ -0,0 +1,265 @@
+use std::cmp::Ordering;
+
+use nes_rust_client::{
+    expression::{
+        binary_expression::{BinaryExpr, BinaryOp},
+        expression::RawExpr,
+        literal::Literal,
+    },
+    prelude::{UnaryExpr, UnaryOp},
+};
+use nes_types::{IntType, NesType};
+
+use crate::{
+    expr_gen::expr_gen::{infer_type, promote},
+    ron_tree::RonNode,
+};
+
+use super::value::Value;
+
+/// Resolves field names to values of the current record.
+pub trait FieldLookup {
+    fn lookup(&self, name: &str) -> Option<Value>;
+}
+
+pub fn eval_expr(expr: &RawExpr, record: &impl FieldLookup) -> Result<Value, String> {
+    match expr {
+        RawExpr::Field(field) => {
+            let name = field.name().to_string();
+            record
+                .lookup(&name)
+                .ok_or_else(|| format!("Unknown field {name}."))
+        }
+        RawExpr::Literal(literal) => Value::parse(&literal_value(literal)?, literal.data_type()),
+        RawExpr::Binary(binary) => eval_binary(binary, record),
+        RawExpr::Unary(unary) => eval_unary(unary, record),
+    }
+}
+
+/// Returns the declared output type of `expr`. `Undefined` if the type is inferred by
+/// NebulaStream, e.g. for untyped fields.
+pub fn expr_data_type(expr: &RawExpr) -> NesType {
+    match expr {
+        RawExpr::Field(field) => field.data_type(),
+        RawExpr::Literal(literal) => literal.data_type(),
+        RawExpr::Binary(binary) => binary.data_type,
+        RawExpr::Unary(unary) => unary.data_type,
+    }
+}
+
+/// Returns the value of a literal as written in the query. The client only exposes the value through
+/// its serialized form, so it is read from the first string in the literal's RON.
+fn literal_value(literal: &Literal) -> Result<String, String> {
+    let ron = ron::to_string(literal).map_err(|err| err.to_string())?;
+    let mut value = None;
+    RonNode::parse(&ron)?.walk(&mut |node| {
+        if let RonNode::Literal(raw) = node {
+            if value.is_none() {
+                value = raw
+                    .strip_prefix('"')
+                    .and_then(|raw| raw.strip_suffix('"'))
+                    .map(|raw| raw.to_string());
+            }
+        }
+    });
+    value.ok_or_else(|| format!("Unable to read literal value from {ron}."))
+}
+
+/// Arithmetic binary operators, evaluated on integers or floats depending on the result type.
+#[derive(Debug, Clone, Copy)]
+enum Arithmetic {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+}
+
+fn eval_binary(binary: &BinaryExpr, record: &impl FieldLookup) -> Result<Value, String> {
+    let lhs = eval_expr(&binary.lhs, record)?;
+    let rhs = eval_expr(&binary.rhs, record)?;
+    let (lhs, rhs) = promote_operands(binary, lhs, rhs)?;
+    let ordering = || lhs.compare(rhs);
+    let arithmetic = match binary.operator {
+        BinaryOp::And => return Ok(Value::Bool(lhs.as_bool()? && rhs.as_bool()?)),
+        BinaryOp::Or => return Ok(Value::Bool(lhs.as_bool()? || rhs.as_bool()?)),
+        BinaryOp::Equals => return Ok(Value::Bool(ordering()? == Some(Ordering::Equal))),
+        BinaryOp::NotEquals => return Ok(Value::Bool(ordering()? != Some(Ordering::Equal))),
+        BinaryOp::Greater => return Ok(Value::Bool(ordering()? == Some(Ordering::Greater))),
+        BinaryOp::GreaterEquals => {
+            return Ok(Value::Bool(matches!(
+                ordering()?,
+                Some(Ordering::Greater | Ordering::Equal)
+            )))
+        }
+        BinaryOp::Less => return Ok(Value::Bool(ordering()? == Some(Ordering::Less))),
+        BinaryOp::LessEquals => {
+            return Ok(Value::Bool(matches!(
+                ordering()?,
+                Some(Ordering::Less | Ordering::Equal)
+            )))
+        }
+        BinaryOp::Add => Arithmetic::Add,
+        BinaryOp::Subtract => Arithmetic::Subtract,
+        BinaryOp::Multiply => Arithmetic::Multiply,
+        BinaryOp::Divide => Arithmetic::Divide,
+        BinaryOp::Remainder => Arithmetic::Remainder,
+        BinaryOp::Power => Arithmetic::Power,
+    };
+    let data_type = arithmetic_type(binary, lhs, rhs);
+    eval_arithmetic(arithmetic, lhs, rhs, data_type)?.cast(data_type)
+}
+
+/// NebulaStream converts both operands to their common type, see `promote`, before it compares or
+/// computes them. Integers wrap, so e.g. `u64::MAX == -1i64` holds and an `u64` divided by an `i64`
+/// is divided as an `i64`. Operands of untyped fields are left as they are.
+fn promote_operands(binary: &BinaryExpr, lhs: Value, rhs: Value) -> Result<(Value, Value), String> {
+    match promote(infer_type(&binary.lhs), infer_type(&binary.rhs)) {
+        Some(data_type) => Ok((lhs.cast(data_type)?, rhs.cast(data_type)?)),
+        None => Ok((lhs, rhs)),
+    }
+}
+
+/// NebulaStream infers the type of expressions without a declared type from its operands, see
+/// `promote`. If the operands depend on untyped fields, the result is a float if one of the
+/// operand values is a float, like in C++.
+fn arithmetic_type(binary: &BinaryExpr, lhs: Value, rhs: Value) -> NesType {
+    let declared = match binary.data_type {
+        NesType::Undefined => {
+            promote(infer_type(&binary.lhs), infer_type(&binary.rhs)).unwrap_or(NesType::Undefined)
+        }
+        declared => declared,
+    };
+    match declared {
+        NesType::Undefined if lhs.is_float() || rhs.is_float() => NesType::f64(),
+        NesType::Undefined => NesType::Int(IntType::Signed64),
+        declared => declared,
+    }
+}
+
+fn eval_arithmetic(
+    operator: Arithmetic,
+    lhs: Value,
+    rhs: Value,
+    data_type: NesType,
+) -> Result<Value, String> {
+    if let NesType::Float(_) = data_type {
+        let (lhs, rhs) = (lhs.as_f64()?, rhs.as_f64()?);
+        let value = match operator {
+            Arithmetic::Add => lhs + rhs,
+            Arithmetic::Subtract => lhs - rhs,
+            Arithmetic::Multiply => lhs * rhs,
+            Arithmetic::Divide => lhs / rhs,
+            Arithmetic::Remainder => lhs % rhs,
+            Arithmetic::Power => lhs.powf(rhs),
+        };
+        return Ok(Value::Float(value));
+    }
+    let (lhs, rhs) = (lhs.as_i128()?, rhs.as_i128()?);
+    let value = match operator {
+        Arithmetic::Add => lhs.wrapping_add(rhs),
+        Arithmetic::Subtract => lhs.wrapping_sub(rhs),
+        Arithmetic::Multiply => lhs.wrapping_mul(rhs),
+        Arithmetic::Divide | Arithmetic::Remainder if rhs == 0 => {
+            return Err("Integer division by zero.".into())
+        }
+        Arithmetic::Divide => lhs.wrapping_div(rhs),
+        Arithmetic::Remainder => lhs.wrapping_rem(rhs),
+        Arithmetic::Power => (lhs as f64).powf(rhs as f64) as i128,
+    };
+    Ok(Value::Int(value))
+}
+
+fn eval_unary(unary: &UnaryExpr, record: &impl FieldLookup) -> Result<Value, String> {
+    let value = eval_expr(&unary.expr, record)?;
+    match unary.operator {
+        UnaryOp::Negate => Ok(Value::Bool(!value.as_bool()?)),
+        UnaryOp::Absolute => {
+            let value = match value {
+                Value::Float(f) => Value::Float(f.abs()),
+                other => Value::Int(other.as_i128()?.abs()),
+            };
+            value.cast(unary.data_type)
+        }
+    }
+}
+
+#[cfg(test)]
+mod expr_eval_tests {
+    use std::collections::HashMap;
+
+    use nes_rust_client::expression::{
+        binary_expression::{BinaryExpr, BinaryOp},
+        expression::RawExpr,
+        literal::Literal,
+        Field,
+    };
+    use nes_types::{IntType, NesType};
+
+    use crate::reference::value::Value;
+
+    use super::{eval_expr, FieldLookup};
+
+    const U64: NesType = NesType::Int(IntType::Unsigned64);
+    const I64: NesType = NesType::Int(IntType::Signed64);
+    const U32: NesType = NesType::Int(IntType::Unsigned32);
+    const I16: NesType = NesType::Int(IntType::Signed16);
+    const I32: NesType = NesType::Int(IntType::Signed32);
+
+    struct Record(HashMap<String, Value>);
+
+    impl FieldLookup for Record {
+        fn lookup(&self, name: &str) -> Option<Value> {
+            self.0.get(name).copied()
+        }
+    }
+
+    /// Evaluates `x operator literal` for a field `x` of `x_type` with the value `x`.
+    fn eval(
+        x: i128,
+        x_type: NesType,
+        operator: BinaryOp,
+        literal: i128,
+        literal_type: NesType,
+        data_type: NesType,
+    ) -> Value {
+        let expr = RawExpr::Binary(BinaryExpr {
+            lhs: Box::new(RawExpr::Field(Field::typed("x", x_type))),
+            rhs: Box::new(RawExpr::Literal(Literal::typed(
+                literal.to_string(),
+                literal_type,
+            ))),
+            data_type,
+            operator,
+        });
+        let record = Record(HashMap::from([("x".to_string(), Value::Int(x))]));
+        eval_expr(&expr, &record).unwrap()
+    }
+
+    #[test]
+    fn u64_and_i64_operands_are_converted_to_i64() {
+        let max = u64::MAX.into();
+        let equals = eval(max, U64, BinaryOp::Equals, -1, I64, NesType::Bool);
+        assert_eq!(equals, Value::Bool(true));
+        let less = eval(max, U64, BinaryOp::Less, 0, I64, NesType::Bool);
+        assert_eq!(less, Value::Bool(true));
+        let quotient = eval((1 << 63) + 2, U64, BinaryOp::Divide, 2, I64, I64);
+        assert_eq!(quotient, Value::Int(-(1 << 62) + 1));
+        let sum = eval(max, U64, BinaryOp::Add, 2, I64, I64);
+        assert_eq!(sum, Value::Int(1));
+    }
+
+    #[test]
+    fn u32_and_i16_operands_are_converted_to_i32() {
+        let max = u32::MAX.into();
+        let equals = eval(max, U32, BinaryOp::Equals, -1, I16, NesType::Bool);
+        assert_eq!(equals, Value::Bool(true));
+        let greater = eval(3_000_000_000, U32, BinaryOp::Greater, 0, I16, NesType::Bool);
+        assert_eq!(greater, Value::Bool(false));
+        let sum = eval(40_000, U32, BinaryOp::Add, -1, I16, I32);
+        assert_eq!(sum, Value::Int(39_999));
+        let quotient = eval(max, U32, BinaryOp::Divide, -1, I16, I32);
+        assert_eq!(quotient, Value::Int(1));
+    }
+}
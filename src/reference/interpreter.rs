@@ -0,0 +1,604 @@
+use std::{cmp::Ordering, collections::HashMap, fs, path::Path};
+
+use nes_types::{IntType, NesType};
+use yaml_rust2::YamlLoader;
+
+use crate::{stream_schema::read_stream_schema_from_file, LancerConfig};
+
+use super::{
+    expr_eval::{eval_expr, expr_data_type, FieldLookup},
+    plan::{AggregationKind, ProjectedField, RefAggregation, RefOperator, RefWindow},
+    value::{nes_type_string, Value},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefField {
+    pub name: String,
+    pub data_type: NesType,
+}
+
+/// Intermediate and final results of the reference interpreter.
+#[derive(Debug, Clone)]
+pub struct Table {
+    /// Prefix of the field names in the header of the result file.
+    pub source_name: String,
+    pub fields: Vec<RefField>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+struct Row<'a> {
+    fields: &'a [RefField],
+    values: &'a [Value],
+}
+
+impl FieldLookup for Row<'_> {
+    fn lookup(&self, name: &str) -> Option<Value> {
+        field_index(self.fields, name).map(|index| self.values[index])
+    }
+}
+
+/// Finds a field by name. Names qualified with a source, e.g. `source$field`, match the unqualified
+/// field name.
+fn field_index(fields: &[RefField], name: &str) -> Option<usize> {
+    let unqualified = name.rsplit('$').next().unwrap_or(name);
+    fields
+        .iter()
+        .position(|field| field.name == name)
+        .or_else(|| fields.iter().position(|field| field.name == unqualified))
+}
+
+impl Table {
+    fn index_of(&self, name: &str) -> Result<usize, String> {
+        field_index(&self.fields, name).ok_or_else(|| format!("Unknown field {name}."))
+    }
+
+    /// Writes the table in the format of NebulaStream's CSV sink.
+    pub fn write_csv(&self, path: &Path) -> Result<(), String> {
+        let mut wtr = csv::Writer::from_path(path).map_err(|err| err.to_string())?;
+        let header = self.fields.iter().map(|field| {
+            format!(
+                "{}${}:{}",
+                self.source_name,
+                field.name,
+                nes_type_string(field.data_type)
+            )
+        });
+        wtr.write_record(header).map_err(|err| err.to_string())?;
+        for row in self.rows.iter() {
+            wtr.write_record(row.iter().map(|value| value.to_string()))
+                .map_err(|err| err.to_string())?;
+        }
+        wtr.flush().map_err(|err| err.to_string())
+    }
+}
+
+/// Loads the data of every logical source of a test run. The rows of all physical sources of a
+/// logical source are concatenated.
+pub fn load_source_tables(
+    test_run_id: u32,
+    config: &LancerConfig,
+) -> Result<HashMap<String, Table>, String> {
+    let empty_tables = source_tables(test_run_id, config)?;
+    let mut tables = empty_tables.clone();
+    for_each_source_row(test_run_id, config, &empty_tables, |source_name, row| {
+        if let Some(table) = tables.get_mut(source_name) {
+            table.rows.push(row);
+        }
+    })?;
+    Ok(tables)
+}
+
+/// Returns a table without rows for every logical source of a test run.
+pub fn source_tables(
+    test_run_id: u32,
+    config: &LancerConfig,
+) -> Result<HashMap<String, Table>, String> {
+    let schema =
+        read_stream_schema_from_file(test_run_id, config).map_err(|err| err.to_string())?;
+    let tables = schema
+        .logical_sources
+        .iter()
+        .map(|source| {
+            let fields = source
+                .fields
+                .iter()
+                .map(|field| RefField {
+                    name: field.name().to_string(),
+                    data_type: field.data_type(),
+                })
+                .collect();
+            let table = Table {
+                source_name: source.source_name.clone(),
+                fields,
+                rows: Vec::new(),
+            };
+            (source.source_name.clone(), table)
+        })
+        .collect();
+    Ok(tables)
+}
+
+/// Reads the data files of a test run one row at a time and passes every row with the name of
+/// its logical source to `f`. The rows have the fields of the source in `tables`. Physical sources
+/// are read in the order of the sorted worker configs, so the order of the rows is deterministic.
+pub fn for_each_source_row(
+    test_run_id: u32,
+    config: &LancerConfig,
+    tables: &HashMap<String, Table>,
+    mut f: impl FnMut(&str, Vec<Value>),
+) -> Result<(), String> {
+    let worker_dir = config.path_config.worker_configs(test_run_id);
+    let mut worker_paths = fs::read_dir(&worker_dir)
+        .map_err(|err| err.to_string())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    worker_paths.sort();
+    for worker_path in worker_paths {
+        let content = fs::read_to_string(&worker_path).map_err(|err| err.to_string())?;
+        let docs = YamlLoader::load_from_str(&content).map_err(|err| err.to_string())?;
+        let Some(worker) = docs.first() else {
+            continue;
+        };
+        let Some(physical_sources) = worker["physicalSources"].as_vec() else {
+            continue;
+        };
+        for physical_source in physical_sources {
+            let (Some(source_name), Some(file_path)) = (
+                physical_source["logicalSourceName"].as_str(),
+                physical_source["configuration"]["filePath"].as_str(),
+            ) else {
+                return Err(format!("Invalid physical source in {:?}.", worker_path));
+            };
+            let Some(table) = tables.get(source_name) else {
+                return Err(format!("Unknown logical source {source_name}."));
+            };
+            read_rows(Path::new(file_path), &table.fields, |row| {
+                f(source_name, row)
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn read_rows(
+    path: &Path,
+    fields: &[RefField],
+    mut f: impl FnMut(Vec<Value>),
+) -> Result<(), String> {
+    let mut rdr = csv::Reader::from_path(path).map_err(|err| err.to_string())?;
+    let header = rdr.headers().map_err(|err| err.to_string())?.clone();
+    let columns = fields
+        .iter()
+        .map(|field| {
+            header
+                .iter()
+                .position(|name| name == field.name)
+                .ok_or_else(|| format!("Field {} is missing in {:?}.", field.name, path))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    for record in rdr.records() {
+        let record = record.map_err(|err| err.to_string())?;
+        let row = columns
+            .iter()
+            .zip(fields.iter())
+            .map(|(&column, field)| Value::parse(&record[column], field.data_type))
+            .collect::<Result<Vec<_>, String>>()?;
+        f(row);
+    }
+    Ok(())
+}
+
+/// Evaluates `operator` on the `sources` and returns the expected result.
+pub fn execute(operator: &RefOperator, sources: &HashMap<String, Table>) -> Result<Table, String> {
+    match operator {
+        RefOperator::Source(name) => sources
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown logical source {name}.")),
+        RefOperator::Filter { input, predicate } => {
+            let mut table = execute(input, sources)?;
+            let mut rows = Vec::new();
+            for values in table.rows {
+                let row = Row {
+                    fields: &table.fields,
+                    values: &values,
+                };
+                if eval_expr(predicate, &row)?.as_bool()? {
+                    rows.push(values);
+                }
+            }
+            table.rows = rows;
+            Ok(table)
+        }
+        RefOperator::Map {
+            input,
+            field_name,
+            expr,
+        } => {
+            let mut table = execute(input, sources)?;
+            let mut results = Vec::new();
+            for values in table.rows.iter() {
+                let row = Row {
+                    fields: &table.fields,
+                    values,
+                };
+                results.push(eval_expr(expr, &row)?);
+            }
+            let data_type = match expr_data_type(expr) {
+                NesType::Undefined if results.iter().any(|value| value.is_float()) => {
+                    NesType::f64()
+                }
+                NesType::Undefined => NesType::Int(IntType::Signed64),
+                data_type => data_type,
+            };
+            let index = match field_index(&table.fields, field_name) {
+                Some(index) => {
+                    table.fields[index].data_type = data_type;
+                    index
+                }
+                None => {
+                    table.fields.push(RefField {
+                        name: field_name.clone(),
+                        data_type,
+                    });
+                    table
+                        .rows
+                        .iter_mut()
+                        .for_each(|row| row.push(Value::Int(0)));
+                    table.fields.len() - 1
+                }
+            };
+            for (row, result) in table.rows.iter_mut().zip(results) {
+                row[index] = result.cast(data_type)?;
+            }
+            Ok(table)
+        }
+        RefOperator::Union(lhs, rhs) => {
+            let mut lhs = execute(lhs, sources)?;
+            let rhs = execute(rhs, sources)?;
+            let lhs_types = lhs.fields.iter().map(|field| field.data_type);
+            if !lhs_types.eq(rhs.fields.iter().map(|field| field.data_type)) {
+                return Err("Union of tables with different schemas.".into());
+            }
+            lhs.rows.extend(rhs.rows);
+            Ok(lhs)
+        }
+        RefOperator::Project { input, fields } => project(execute(input, sources)?, fields),
+        RefOperator::Window {
+            input,
+            window,
+            keys,
+            aggregations,
+        } => aggregate(execute(input, sources)?, window, keys, aggregations),
+    }
+}
+
+fn project(table: Table, fields: &[ProjectedField]) -> Result<Table, String> {
+    let indices = fields
+        .iter()
+        .map(|field| table.index_of(&field.name))
+        .collect::<Result<Vec<_>, String>>()?;
+    let projected_fields = fields
+        .iter()
+        .zip(indices.iter())
+        .map(|(field, &index)| RefField {
+            name: field.rename.clone().unwrap_or_else(|| field.name.clone()),
+            data_type: table.fields[index].data_type,
+        })
+        .collect();
+    let rows = table
+        .rows
+        .iter()
+        .map(|row| indices.iter().map(|&index| row[index]).collect())
+        .collect();
+    Ok(Table {
+        source_name: table.source_name,
+        fields: projected_fields,
+        rows,
+    })
+}
+
+/// Evaluates a tumbling window aggregation. Only windows that contain at least one record produce
+/// a result, the result contains the fields `start`, `end`, the keys and the aggregations.
+fn aggregate(
+    table: Table,
+    window: &RefWindow,
+    keys: &[String],
+    aggregations: &[RefAggregation],
+) -> Result<Table, String> {
+    if window.size_ms == 0 {
+        return Err("Window size must not be zero.".into());
+    }
+    let ts_index = table.index_of(&window.time_field)?;
+    let key_indices = keys
+        .iter()
+        .map(|key| table.index_of(key))
+        .collect::<Result<Vec<_>, String>>()?;
+    let agg_indices = aggregations
+        .iter()
+        .map(|agg| {
+            agg.field
+                .as_ref()
+                .map(|field| table.index_of(field))
+                .transpose()
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let time_unit_ms = i128::from(window.time_unit_ms);
+    let size_ms = i128::from(window.size_ms);
+    // (window start, key values) -> rows
+    let mut groups: Vec<((i128, Vec<Value>), Vec<&Vec<Value>>)> = Vec::new();
+    for row in table.rows.iter() {
+        let ts_ms = row[ts_index].as_i128()? * time_unit_ms;
+        let start = ts_ms - ts_ms.rem_euclid(size_ms);
+        let group_key = (start, key_indices.iter().map(|&i| row[i]).collect());
+        match groups.iter_mut().find(|(key, _)| *key == group_key) {
+            Some((_, rows)) => rows.push(row),
+            None => groups.push((group_key, vec![row])),
+        }
+    }
+
+    let time_type = NesType::Int(IntType::Unsigned64);
+    let mut fields = vec![
+        RefField {
+            name: "start".into(),
+            data_type: time_type,
+        },
+        RefField {
+            name: "end".into(),
+            data_type: time_type,
+        },
+    ];
+    fields.extend(key_indices.iter().map(|&i| table.fields[i].clone()));
+    for (agg, index) in aggregations.iter().zip(agg_indices.iter()) {
+        let input_type = index.map(|i| table.fields[i].data_type);
+        fields.push(RefField {
+            name: agg.output.clone(),
+            data_type: aggregation_type(agg.kind, input_type),
+        });
+    }
+
+    let mut rows = Vec::new();
+    for ((start, key_values), group) in groups {
+        let mut row = vec![
+            Value::Int(start / time_unit_ms),
+            Value::Int((start + size_ms) / time_unit_ms),
+        ];
+        row.extend(key_values);
+        for (agg, index) in aggregations.iter().zip(agg_indices.iter()) {
+            let input_type = index.map(|i| table.fields[i].data_type);
+            let values = group.iter().filter_map(|row| index.map(|i| row[i]));
+            let value = eval_aggregation(agg.kind, values, group.len())?;
+            row.push(value.cast(aggregation_type(agg.kind, input_type))?);
+        }
+        rows.push(row);
+    }
+    Ok(Table {
+        source_name: table.source_name,
+        fields,
+        rows,
+    })
+}
+
+fn aggregation_type(kind: AggregationKind, input_type: Option<NesType>) -> NesType {
+    match (kind, input_type) {
+        (AggregationKind::Count, _) => NesType::Int(IntType::Unsigned64),
+        (AggregationKind::Avg, _) => NesType::f64(),
+        (_, Some(data_type)) => data_type,
+        (_, None) => NesType::Undefined,
+    }
+}
+
+fn eval_aggregation(
+    kind: AggregationKind,
+    values: impl Iterator<Item = Value>,
+    count: usize,
+) -> Result<Value, String> {
+    match kind {
+        AggregationKind::Count => Ok(Value::Int(count as i128)),
+        AggregationKind::Sum => fold_values(values, |acc, value| {
+            Ok(match (acc, value) {
+                (Value::Int(a), Value::Int(b)) => Value::Int(a.wrapping_add(b)),
+                (a, b) => Value::Float(a.as_f64()? + b.as_f64()?),
+            })
+        }),
+        AggregationKind::Min => fold_values(values, |acc, value| {
+            Ok(match value.compare(acc)? {
+                Some(Ordering::Less) => value,
+                _ => acc,
+            })
+        }),
+        AggregationKind::Max => fold_values(values, |acc, value| {
+            Ok(match value.compare(acc)? {
+                Some(Ordering::Greater) => value,
+                _ => acc,
+            })
+        }),
+        AggregationKind::Avg => {
+            let sum = fold_values(values, |acc, value| {
+                Ok(Value::Float(acc.as_f64()? + value.as_f64()?))
+            })?;
+            Ok(Value::Float(sum.as_f64()? / count as f64))
+        }
+    }
+}
+
+fn fold_values(
+    mut values: impl Iterator<Item = Value>,
+    f: impl FnMut(Value, Value) -> Result<Value, String>,
+) -> Result<Value, String> {
+    let Some(first) = values.next() else {
+        return Err("Aggregation over an empty window.".into());
+    };
+    values.try_fold(first, f)
+}
+
+#[cfg(test)]
+mod interpreter_tests {
+    use std::collections::HashMap;
+
+    use nes_rust_client::{
+        expression::{
+            binary_expression::{BinaryExpr, BinaryOp},
+            expression::RawExpr,
+            literal::Literal,
+            ArithmeticExpr, Field, LogicalExpr,
+        },
+        prelude::{Aggregation, QueryBuilder, Sink},
+        query::{
+            time::{Duration, TimeCharacteristic, TimeUnit},
+            window::window_descriptor::WindowDescriptor,
+        },
+    };
+    use nes_types::{IntType, NesType};
+
+    use crate::reference::{plan::RefOperator, value::Value};
+
+    use super::{execute, RefField, Table};
+
+    const I64: NesType = NesType::Int(IntType::Signed64);
+
+    fn sources() -> HashMap<String, Table> {
+        let field = |name: &str, data_type| RefField {
+            name: name.into(),
+            data_type,
+        };
+        let rows = [
+            (0, 1, 5),
+            (50, 2, -3),
+            (120, 1, 7),
+            (150, 1, 1),
+            (230, 2, 4),
+        ]
+        .into_iter()
+        .map(|(ts, key, x)| vec![Value::Int(ts), Value::Int(key), Value::Int(x)])
+        .collect();
+        let table = Table {
+            source_name: "src".into(),
+            fields: vec![
+                field("ts", NesType::Int(IntType::Unsigned64)),
+                field("key", NesType::Int(IntType::Signed32)),
+                field("x", I64),
+            ],
+            rows,
+        };
+        HashMap::from([("src".to_string(), table)])
+    }
+
+    fn run(builder: QueryBuilder) -> Table {
+        let query = builder.sink(Sink::csv_file("out.csv", false));
+        let plan = RefOperator::try_from(&query).unwrap();
+        execute(&plan, &sources()).unwrap()
+    }
+
+    fn binary(lhs: RawExpr, operator: BinaryOp, rhs: RawExpr, data_type: NesType) -> RawExpr {
+        RawExpr::Binary(BinaryExpr {
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            data_type,
+            operator,
+        })
+    }
+
+    fn x() -> RawExpr {
+        RawExpr::Field(Field::typed("x", I64))
+    }
+
+    fn int(value: i64) -> RawExpr {
+        RawExpr::Literal(Literal::typed(value.to_string(), I64))
+    }
+
+    fn window(size_ms: u32) -> WindowDescriptor {
+        WindowDescriptor::TumblingWindow {
+            duration: Duration::from_milliseconds(size_ms),
+            time_character: TimeCharacteristic::EventTime {
+                field_name: "ts".into(),
+                unit: TimeUnit::Milliseconds,
+            },
+        }
+    }
+
+    fn ints(rows: &[&[i128]]) -> Vec<Vec<Value>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&value| Value::Int(value)).collect())
+            .collect()
+    }
+
+    fn names(table: &Table) -> Vec<&str> {
+        table
+            .fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn filter() {
+        let predicate = binary(x(), BinaryOp::Greater, int(0), NesType::Bool);
+        let result = run(QueryBuilder::from_source("src").filter(LogicalExpr(predicate)));
+        let expected = ints(&[&[0, 1, 5], &[120, 1, 7], &[150, 1, 1], &[230, 2, 4]]);
+        assert_eq!(result.rows, expected);
+    }
+
+    #[test]
+    fn map() {
+        let expr = binary(x(), BinaryOp::Multiply, int(2), I64);
+        let result = run(QueryBuilder::from_source("src").map("y", ArithmeticExpr(expr)));
+        assert_eq!(names(&result), ["ts", "key", "x", "y"]);
+        assert_eq!(result.fields[3].data_type, I64);
+        let y = result.rows.iter().map(|row| row[3]).collect::<Vec<_>>();
+        assert_eq!(y, [10, -6, 14, 2, 8].map(Value::Int));
+    }
+
+    #[test]
+    fn project() {
+        let result = run(QueryBuilder::from_source("src")
+            .project([Field::from("x").rename("value"), Field::from("ts")]));
+        assert_eq!(names(&result), ["value", "ts"]);
+        let expected = ints(&[&[5, 0], &[-3, 50], &[7, 120], &[1, 150], &[4, 230]]);
+        assert_eq!(result.rows, expected);
+    }
+
+    #[test]
+    fn union() {
+        let builder = QueryBuilder::from_source("src");
+        let result = run(builder.clone().union(builder));
+        let mut expected = sources().remove("src").unwrap().rows;
+        expected.extend(expected.clone());
+        assert_eq!(result.rows, expected);
+    }
+
+    #[test]
+    fn tumbling_window() {
+        let result = run(QueryBuilder::from_source("src")
+            .window(window(100))
+            .apply([Aggregation::sum("x".to_string())]));
+        assert_eq!(names(&result), ["start", "end", "x"]);
+        let expected = ints(&[&[0, 100, 2], &[100, 200, 8], &[200, 300, 4]]);
+        assert_eq!(result.rows, expected);
+    }
+
+    #[test]
+    fn keyed_window() {
+        let result = run(QueryBuilder::from_source("src")
+            .window(window(100))
+            .by_key("key")
+            .apply([
+                Aggregation::count().as_field("n"),
+                Aggregation::average("x".to_string()).as_field("avg"),
+            ]));
+        assert_eq!(names(&result), ["start", "end", "key", "n", "avg"]);
+        let row = |start, key, n, avg| {
+            let mut row = ints(&[&[start, start + 100, key, n]]).remove(0);
+            row.push(Value::Float(avg));
+            row
+        };
+        let expected = vec![
+            row(0, 1, 1, 5.0),
+            row(0, 2, 1, -3.0),
+            row(100, 1, 2, 4.0),
+            row(200, 2, 1, 4.0),
+        ];
+        assert_eq!(result.rows, expected);
+    }
+}
@@ -0,0 +1,6 @@
+//! Reference interpreter that computes the expected result of a query from the generated data
+//! files without NebulaStream.
+pub mod expr_eval;
+pub mod interpreter;
+pub mod plan;
+pub mod value;
@@ -0,0 +1,628 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+
+use nes_rust_client::{
+    expression::{expression::RawExpr, ArithmeticExpr, Field, LogicalExpr},
+    prelude::{Aggregation, QueryBuilder, Sink},
+    query::{
+        time::{Duration, TimeCharacteristic, TimeUnit},
+        window::window_descriptor::WindowDescriptor,
+        Query,
+    },
+};
+
+use crate::ron_tree::RonNode;
+
+/// Operator tree of a query as understood by the reference interpreter.
+#[derive(Debug, Clone)]
+pub enum RefOperator {
+    Source(String),
+    Filter {
+        input: Box<RefOperator>,
+        predicate: RawExpr,
+    },
+    Map {
+        input: Box<RefOperator>,
+        field_name: String,
+        expr: RawExpr,
+    },
+    Union(Box<RefOperator>, Box<RefOperator>),
+    Project {
+        input: Box<RefOperator>,
+        fields: Vec<ProjectedField>,
+    },
+    Window {
+        input: Box<RefOperator>,
+        window: RefWindow,
+        keys: Vec<String>,
+        aggregations: Vec<RefAggregation>,
+    },
+}
+
+impl RefOperator {
+    pub fn has_window(&self) -> bool {
+        match self {
+            RefOperator::Source(_) => false,
+            RefOperator::Window { .. } => true,
+            RefOperator::Filter { input, .. }
+            | RefOperator::Map { input, .. }
+            | RefOperator::Project { input, .. } => input.has_window(),
+            RefOperator::Union(lhs, rhs) => lhs.has_window() || rhs.has_window(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectedField {
+    pub name: String,
+    pub rename: Option<String>,
+}
+
+/// Tumbling event time window.
+#[derive(Debug, Clone)]
+pub struct RefWindow {
+    pub size_ms: u64,
+    pub time_field: String,
+    /// Milliseconds per unit of `time_field`.
+    pub time_unit_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregationKind {
+    Min,
+    Max,
+    Sum,
+    Count,
+    Avg,
+}
+
+impl AggregationKind {
+    const ALL: [AggregationKind; 5] = [
+        AggregationKind::Min,
+        AggregationKind::Max,
+        AggregationKind::Sum,
+        AggregationKind::Count,
+        AggregationKind::Avg,
+    ];
+
+    /// The client's aggregation of this kind. `None` if a count has a field or another kind has
+    /// none.
+    fn client_aggregation(self, field: Option<&str>) -> Option<Aggregation> {
+        let aggregation = match (self, field) {
+            (AggregationKind::Count, None) => Aggregation::count(),
+            (AggregationKind::Count, Some(_)) | (_, None) => return None,
+            (AggregationKind::Min, Some(field)) => Aggregation::min(field.to_string()),
+            (AggregationKind::Max, Some(field)) => Aggregation::max(field.to_string()),
+            (AggregationKind::Sum, Some(field)) => Aggregation::sum(field.to_string()),
+            (AggregationKind::Avg, Some(field)) => Aggregation::average(field.to_string()),
+        };
+        Some(aggregation)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RefAggregation {
+    pub kind: AggregationKind,
+    pub field: Option<String>,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OperatorKind {
+    Source,
+    Filter,
+    Map,
+    Union,
+    Project,
+    Window,
+}
+
+/// Source and field name of the probe queries.
+const PROBE_NAME: &str = "probe";
+
+/// Identifiers of RON that are no variants of the client's operators.
+const RON_IDENTIFIERS: [&str; 4] = ["Some", "None", "true", "false"];
+
+impl OperatorKind {
+    const ALL: [OperatorKind; 6] = [
+        OperatorKind::Source,
+        OperatorKind::Filter,
+        OperatorKind::Map,
+        OperatorKind::Union,
+        OperatorKind::Project,
+        OperatorKind::Window,
+    ];
+
+    /// Queries with a single operator of this kind on top of a source and the RON of the
+    /// arguments of the operator. Windows are probed with and without keys.
+    fn probes(self) -> Result<Vec<(QueryBuilder, Vec<String>)>, String> {
+        let source = || QueryBuilder::from_source(PROBE_NAME);
+        let field = || Field::from(PROBE_NAME);
+        let to_ron = |result: ron::Result<String>| result.map_err(|err| err.to_string());
+        let probes = match self {
+            OperatorKind::Source => vec![(source(), Vec::new())],
+            OperatorKind::Filter => {
+                let predicate = LogicalExpr(RawExpr::Field(field()));
+                let arguments = vec![to_ron(ron::to_string(&predicate))?];
+                vec![(source().filter(predicate), arguments)]
+            }
+            OperatorKind::Map => {
+                let expr = ArithmeticExpr(RawExpr::Field(field()));
+                let arguments = vec![to_ron(ron::to_string(&expr))?];
+                vec![(source().map(PROBE_NAME, expr), arguments)]
+            }
+            OperatorKind::Union => vec![(source().union(source()), Vec::new())],
+            OperatorKind::Project => {
+                let arguments = vec![to_ron(ron::to_string(&[field()]))?];
+                vec![(source().project([field()]), arguments)]
+            }
+            OperatorKind::Window => {
+                let window = || WindowDescriptor::TumblingWindow {
+                    duration: Duration::from_milliseconds(1),
+                    time_character: TimeCharacteristic::EventTime {
+                        field_name: PROBE_NAME.into(),
+                        unit: TimeUnit::Milliseconds,
+                    },
+                };
+                let arguments = vec![
+                    to_ron(ron::to_string(&window()))?,
+                    to_ron(ron::to_string(&[Aggregation::count()]))?,
+                ];
+                vec![
+                    (
+                        source().window(window()).apply([Aggregation::count()]),
+                        arguments.clone(),
+                    ),
+                    (
+                        source()
+                            .window(window())
+                            .by_key(PROBE_NAME)
+                            .apply([Aggregation::count()]),
+                        arguments,
+                    ),
+                ]
+            }
+        };
+        Ok(probes)
+    }
+}
+
+/// The client only exposes the operators of a `Query` through its serialization, so the plan is
+/// read from its RON. Operators are identified by the exact names of their variants, see
+/// `operator_names`, and their arguments by deserializing them into the client's expression,
+/// field, aggregation and window types. Variants with other names that contain an operator are
+/// rejected, so an operator the interpreter does not know is never skipped.
+impl TryFrom<&Query> for RefOperator {
+    type Error = String;
+
+    fn try_from(query: &Query) -> Result<Self, Self::Error> {
+        let names = operator_names()?;
+        let ron = ron::to_string(query).map_err(|err| err.to_string())?;
+        let root = RonNode::parse(&ron)?;
+        find_operator(&root, names)?.ok_or_else(|| "No operator found in query.".to_string())
+    }
+}
+
+/// Maps the names of the client's operator variants to their kind. The names are not hardcoded but
+/// read from the RON of the `probes` built with `QueryBuilder`: the variant of an operator is the
+/// only variant that a probe adds to the RON of its input and its arguments.
+fn operator_names() -> Result<&'static HashMap<String, OperatorKind>, String> {
+    static NAMES: OnceLock<Result<HashMap<String, OperatorKind>, String>> = OnceLock::new();
+    NAMES
+        .get_or_init(probe_operator_names)
+        .as_ref()
+        .map_err(|err| err.clone())
+}
+
+fn probe_operator_names() -> Result<HashMap<String, OperatorKind>, String> {
+    let sink = || Sink::csv_file("probe.csv", false);
+    let query_names = |builder: QueryBuilder| {
+        let ron = ron::to_string(&builder.sink(sink())).map_err(|err| err.to_string())?;
+        variant_names(&ron)
+    };
+    let sink_names = variant_names(&ron::to_string(&sink()).map_err(|err| err.to_string())?)?;
+    let source_names = query_names(QueryBuilder::from_source(PROBE_NAME))?;
+
+    let mut names = HashMap::new();
+    for kind in OperatorKind::ALL {
+        for (probe, arguments) in kind.probes()? {
+            let mut known = match kind {
+                OperatorKind::Source => sink_names.clone(),
+                _ => source_names.clone(),
+            };
+            for argument in arguments {
+                known.extend(variant_names(&argument)?);
+            }
+            let added = query_names(probe)?
+                .into_iter()
+                .filter(|name| !known.contains(name) && !RON_IDENTIFIERS.contains(&name.as_str()))
+                .collect::<Vec<_>>();
+            let [name] = added.as_slice() else {
+                return Err(format!(
+                    "Unable to identify the {kind:?} operator of the client: {added:?}"
+                ));
+            };
+            if let Some(other) = names.insert(name.clone(), kind) {
+                if other != kind {
+                    return Err(format!(
+                        "{name} is both a {other:?} and a {kind:?} operator."
+                    ));
+                }
+            }
+        }
+    }
+    Ok(names)
+}
+
+fn variant_names(ron: &str) -> Result<HashSet<String>, String> {
+    let mut names = HashSet::new();
+    RonNode::parse(ron)?.walk(&mut |node| {
+        if let Some(name) = node.name() {
+            names.insert(name.to_string());
+        }
+    });
+    Ok(names)
+}
+
+/// Returns the first operator in pre-order. Only `Some` and untagged structs and tuples may wrap an
+/// operator without being one.
+fn find_operator(
+    node: &RonNode,
+    names: &HashMap<String, OperatorKind>,
+) -> Result<Option<RefOperator>, String> {
+    if let Some(&kind) = node.name().and_then(|name| names.get(name)) {
+        return parse_operator(kind, node, names).map(Some);
+    }
+    let mut operators = Vec::new();
+    for child in node.children() {
+        if let Some(operator) = find_operator(child, names)? {
+            operators.push(operator);
+        }
+    }
+    match (node.name(), operators.len()) {
+        (_, 0) => Ok(None),
+        (None | Some("Some"), 1) => Ok(operators.pop()),
+        (Some(name), _) => Err(format!("Unsupported operator {name}.")),
+        (None, count) => Err(format!("Expected one operator but found {count}.")),
+    }
+}
+
+/// Direct arguments of a variant, i.e. the values of its tuple or struct body.
+fn arguments(node: &RonNode) -> Vec<&RonNode> {
+    match node {
+        RonNode::Named(_, Some(body)) => arguments(body),
+        RonNode::Tuple(values)
+            if values.len() == 1 && matches!(values[0], RonNode::Struct(_) | RonNode::Tuple(_)) =>
+        {
+            arguments(&values[0])
+        }
+        RonNode::Tuple(values) => values.iter().collect(),
+        RonNode::Struct(fields) => fields.iter().map(|(_, value)| value).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn parse_operator(
+    kind: OperatorKind,
+    node: &RonNode,
+    names: &HashMap<String, OperatorKind>,
+) -> Result<RefOperator, String> {
+    let mut inputs = Vec::new();
+    let mut others = Vec::new();
+    for argument in arguments(node) {
+        match find_operator(argument, names)? {
+            Some(operator) => inputs.push(Box::new(operator)),
+            None => others.push(argument),
+        }
+    }
+    let name = node.name().unwrap_or_default();
+    let single_input = |mut inputs: Vec<Box<RefOperator>>| match inputs.len() {
+        1 => Ok(inputs.remove(0)),
+        len => Err(format!("Expected one input for {name} but found {len}.")),
+    };
+
+    match kind {
+        OperatorKind::Source => {
+            let Some((source_name, _)) = strings(node).into_iter().next() else {
+                return Err(format!("No source name found in {name}."));
+            };
+            Ok(RefOperator::Source(source_name))
+        }
+        OperatorKind::Filter => Ok(RefOperator::Filter {
+            input: single_input(inputs)?,
+            predicate: others
+                .iter()
+                .find_map(|arg| parse_expr(arg))
+                .ok_or_else(|| format!("No predicate found in {name}."))?,
+        }),
+        OperatorKind::Map => {
+            let expr = others.iter().find_map(|arg| parse_expr(arg));
+            let field_name = others.iter().find_map(|arg| match arg {
+                RonNode::Literal(_) => unquote(arg),
+                _ => ron::from_str::<Field>(&arg.to_string())
+                    .ok()
+                    .map(|field| field.name().to_string()),
+            });
+            let (Some(expr), Some(field_name)) = (expr, field_name) else {
+                return Err(format!("Unable to read field and expression of {name}."));
+            };
+            Ok(RefOperator::Map {
+                input: single_input(inputs)?,
+                field_name,
+                expr,
+            })
+        }
+        OperatorKind::Union => {
+            if inputs.len() != 2 {
+                return Err(format!("Expected two inputs for {name}."));
+            }
+            let rhs = inputs.remove(1);
+            Ok(RefOperator::Union(inputs.remove(0), rhs))
+        }
+        OperatorKind::Project => {
+            let fields = others
+                .iter()
+                .find_map(|arg| parse_projection(arg))
+                .ok_or_else(|| format!("No fields found in {name}."))?;
+            Ok(RefOperator::Project {
+                input: single_input(inputs)?,
+                fields,
+            })
+        }
+        OperatorKind::Window => {
+            let window = others
+                .iter()
+                .find_map(|arg| parse_window_descriptor(arg))
+                .ok_or_else(|| format!("No window descriptor found in {name}."))?;
+            let aggregations = others
+                .iter()
+                .find_map(|arg| parse_aggregations(arg))
+                .ok_or_else(|| format!("No aggregations found in {name}."))?;
+            let keys = others
+                .iter()
+                .filter(|arg| {
+                    parse_window_descriptor(arg).is_none() && parse_aggregations(arg).is_none()
+                })
+                .flat_map(|arg| strings(arg))
+                .map(|(key, _)| key)
+                .collect();
+            Ok(RefOperator::Window {
+                input: single_input(inputs)?,
+                window: window?,
+                keys,
+                aggregations: aggregations?,
+            })
+        }
+    }
+}
+
+fn parse_expr(node: &RonNode) -> Option<RawExpr> {
+    if let Ok(expr) = ron::from_str::<RawExpr>(&node.to_string()) {
+        return Some(expr);
+    }
+    // newtype wrappers like `LogicalExpr` are serialized as a tuple with one element
+    match node {
+        RonNode::Tuple(values) if values.len() == 1 => parse_expr(&values[0]),
+        _ => None,
+    }
+}
+
+fn parse_projection(node: &RonNode) -> Option<Vec<ProjectedField>> {
+    let RonNode::Seq(values) = node else {
+        return None;
+    };
+    values
+        .iter()
+        .map(|value| {
+            let field = ron::from_str::<Field>(&value.to_string()).ok()?;
+            let name = field.name().to_string();
+            let rename = strings(value)
+                .into_iter()
+                .find(|(rename, is_optional)| *is_optional && *rename != name)
+                .map(|(rename, _)| rename);
+            Some(ProjectedField { name, rename })
+        })
+        .collect()
+}
+
+fn parse_window_descriptor(node: &RonNode) -> Option<Result<RefWindow, String>> {
+    let descriptor = ron::from_str::<WindowDescriptor>(&node.to_string()).ok()?;
+    Some(ref_window(&descriptor))
+}
+
+fn ref_window(descriptor: &WindowDescriptor) -> Result<RefWindow, String> {
+    match descriptor {
+        WindowDescriptor::TumblingWindow {
+            duration,
+            time_character,
+        } => {
+            let (time_field, unit) = match time_character {
+                TimeCharacteristic::EventTime { field_name, unit } => (field_name, unit),
+            };
+            Ok(RefWindow {
+                size_ms: duration_ms(duration)?,
+                time_field: time_field.clone(),
+                time_unit_ms: unit_ms(unit)?,
+            })
+        }
+    }
+}
+
+/// Durations and time units are compared with the ones the client builds for milliseconds, the only
+/// unit of the generated windows.
+fn duration_ms(duration: &Duration) -> Result<u64, String> {
+    let ron = |duration: &Duration| ron::to_string(duration).map_err(|err| err.to_string());
+    let actual = ron(duration)?;
+    if actual != ron(&Duration::from_milliseconds(duration.amount))? {
+        return Err(format!("Unsupported duration {actual}."));
+    }
+    Ok(duration.amount.into())
+}
+
+fn unit_ms(unit: &TimeUnit) -> Result<u64, String> {
+    let ron = |unit: &TimeUnit| ron::to_string(unit).map_err(|err| err.to_string());
+    let actual = ron(unit)?;
+    if actual != ron(&TimeUnit::Milliseconds)? {
+        return Err(format!("Unsupported time unit {actual}."));
+    }
+    Ok(1)
+}
+
+fn parse_aggregations(node: &RonNode) -> Option<Result<Vec<RefAggregation>, String>> {
+    let RonNode::Seq(values) = node else {
+        return None;
+    };
+    ron::from_str::<Vec<Aggregation>>(&node.to_string()).ok()?;
+    Some(values.iter().map(ref_aggregation).collect())
+}
+
+/// Reads the input field and the optional output name of an aggregation and finds its kind by
+/// comparing it with the aggregations the client builds for them. Without an output name
+/// NebulaStream names the result after the input field, or `count` for counts.
+fn ref_aggregation(node: &RonNode) -> Result<RefAggregation, String> {
+    let strings = strings(node);
+    let field = strings
+        .iter()
+        .find(|(_, is_optional)| !is_optional)
+        .map(|(field, _)| field.clone());
+    let output = strings
+        .iter()
+        .find(|(_, is_optional)| *is_optional)
+        .map(|(output, _)| output.clone());
+    for kind in AggregationKind::ALL {
+        let Some(mut aggregation) = kind.client_aggregation(field.as_deref()) else {
+            continue;
+        };
+        if let Some(output) = &output {
+            aggregation = aggregation.as_field(output.as_str());
+        }
+        let ron = ron::to_string(&aggregation).map_err(|err| err.to_string())?;
+        if RonNode::parse(&ron)? == *node {
+            return Ok(RefAggregation {
+                kind,
+                output: output
+                    .or_else(|| field.clone())
+                    .unwrap_or_else(|| "count".to_string()),
+                field,
+            });
+        }
+    }
+    Err(format!("Unsupported aggregation {node}."))
+}
+
+/// All string literals in `node` and whether they are wrapped in `Some`.
+fn strings(node: &RonNode) -> Vec<(String, bool)> {
+    fn collect(node: &RonNode, is_optional: bool, strings: &mut Vec<(String, bool)>) {
+        if let Some(string) = unquote(node) {
+            strings.push((string, is_optional));
+        }
+        let is_optional = is_optional || node.name() == Some("Some");
+        for child in node.children() {
+            collect(child, is_optional, strings);
+        }
+    }
+    let mut strings = Vec::new();
+    collect(node, false, &mut strings);
+    strings
+}
+
+fn unquote(node: &RonNode) -> Option<String> {
+    let RonNode::Literal(raw) = node else {
+        return None;
+    };
+    raw.strip_prefix('"')
+        .and_then(|raw| raw.strip_suffix('"'))
+        .map(|raw| raw.to_string())
+}
+
+#[cfg(test)]
+mod plan_tests {
+    use nes_rust_client::{
+        expression::{expression::RawExpr, Field, LogicalExpr},
+        prelude::{Aggregation, QueryBuilder, Sink},
+        query::{
+            time::{Duration, TimeCharacteristic, TimeUnit},
+            window::window_descriptor::WindowDescriptor,
+        },
+    };
+    use nes_types::NesType;
+
+    use super::{operator_names, AggregationKind, OperatorKind, RefOperator};
+
+    fn lower(builder: QueryBuilder) -> RefOperator {
+        let query = builder.sink(Sink::csv_file("out.csv", false));
+        RefOperator::try_from(&query).unwrap()
+    }
+
+    #[test]
+    fn every_operator_has_a_name() {
+        let names = operator_names().unwrap();
+        for kind in OperatorKind::ALL {
+            assert!(names.values().any(|&other| other == kind), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn lower_union_of_filter_and_projection() {
+        let predicate = LogicalExpr(RawExpr::Field(Field::typed("b", NesType::Bool)));
+        let lhs = QueryBuilder::from_source("a").filter(predicate);
+        let rhs = QueryBuilder::from_source("a").project([Field::from("b").rename("c")]);
+        let RefOperator::Union(lhs, rhs) = lower(lhs.union(rhs)) else {
+            panic!("Expected a union.");
+        };
+        let RefOperator::Filter { input, .. } = *lhs else {
+            panic!("Expected a filter.");
+        };
+        assert!(matches!(*input, RefOperator::Source(name) if name == "a"));
+        let RefOperator::Project { fields, .. } = *rhs else {
+            panic!("Expected a projection.");
+        };
+        assert_eq!(fields[0].name, "b");
+        assert_eq!(fields[0].rename.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn lower_keyed_window() {
+        let window = WindowDescriptor::TumblingWindow {
+            duration: Duration::from_milliseconds(300),
+            time_character: TimeCharacteristic::EventTime {
+                field_name: "ts".into(),
+                unit: TimeUnit::Milliseconds,
+            },
+        };
+        let operator = lower(
+            QueryBuilder::from_source("a")
+                .window(window)
+                .by_key("k")
+                .apply([
+                    Aggregation::min("x".to_string()),
+                    Aggregation::count().as_field("n"),
+                ]),
+        );
+        let RefOperator::Window {
+            window,
+            keys,
+            aggregations,
+            ..
+        } = operator
+        else {
+            panic!("Expected a window.");
+        };
+        assert_eq!((window.size_ms, window.time_unit_ms), (300, 1));
+        assert_eq!(window.time_field, "ts");
+        assert_eq!(keys, ["k"]);
+        let aggregations = aggregations
+            .iter()
+            .map(|agg| (agg.kind, agg.field.as_deref(), agg.output.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            aggregations,
+            [
+                (AggregationKind::Min, Some("x"), "x"),
+                (AggregationKind::Count, None, "n"),
+            ]
+        );
+    }
+}
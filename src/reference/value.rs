@@ -0,0 +1,166 @@
+use std::{cmp::Ordering, fmt};
+
+use nes_types::{FloatType, IntType, NesType};
+
+/// A single field value of the reference evaluator. Integers of every width are stored as `i128`
+/// and wrapped to the width of their `NesType` by `cast`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Char(char),
+    Int(i128),
+    Float(f64),
+}
+
+impl Value {
+    pub fn parse(raw: &str, data_type: NesType) -> Result<Value, String> {
+        let raw = raw.trim();
+        let value = match data_type {
+            NesType::Bool => match raw {
+                "true" | "1" => Value::Bool(true),
+                "false" | "0" => Value::Bool(false),
+                err => return Err(format!("Unable to parse {err:?} as bool.")),
+            },
+            NesType::Char => match raw.chars().next() {
+                Some(c) => Value::Char(c),
+                None => return Err("Unable to parse empty char.".into()),
+            },
+            NesType::Int(_) => raw
+                .parse::<i128>()
+                .map(Value::Int)
+                .map_err(|err| format!("Unable to parse {raw:?} as int: {err}"))?,
+            NesType::Float(_) => raw
+                .parse::<f64>()
+                .map(Value::Float)
+                .map_err(|err| format!("Unable to parse {raw:?} as float: {err}"))?,
+            NesType::Undefined => return Err(format!("Unable to parse {raw:?} as undefined.")),
+        };
+        value.cast(data_type)
+    }
+
+    /// Converts the value to `data_type`. Integers wrap around like in NebulaStream's C++ runtime
+    /// and floats are truncated when converted to integers.
+    pub fn cast(self, data_type: NesType) -> Result<Value, String> {
+        let value = match data_type {
+            NesType::Undefined => self,
+            NesType::Bool => Value::Bool(self.as_bool()?),
+            NesType::Char => match self {
+                Value::Char(c) => Value::Char(c),
+                other => return Err(format!("Unable to cast {other} to char.")),
+            },
+            NesType::Int(t) => Value::Int(wrap_int(self.as_i128()?, t)),
+            NesType::Float(FloatType::Bit32) => Value::Float(self.as_f64()? as f32 as f64),
+            NesType::Float(FloatType::Bit64) => Value::Float(self.as_f64()?),
+        };
+        Ok(value)
+    }
+
+    pub fn as_bool(self) -> Result<bool, String> {
+        match self {
+            Value::Bool(b) => Ok(b),
+            Value::Int(i) => Ok(i != 0),
+            other => Err(format!("Expected bool but found {other}.")),
+        }
+    }
+
+    pub fn as_i128(self) -> Result<i128, String> {
+        match self {
+            Value::Bool(b) => Ok(b.into()),
+            Value::Char(c) => Ok(u32::from(c).into()),
+            Value::Int(i) => Ok(i),
+            Value::Float(f) => Ok(f as i128),
+        }
+    }
+
+    pub fn as_f64(self) -> Result<f64, String> {
+        match self {
+            Value::Float(f) => Ok(f),
+            other => Ok(other.as_i128()? as f64),
+        }
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, Value::Float(_))
+    }
+
+    /// Compares numerically, integers are only compared as floats if the other value is a float.
+    pub fn compare(self, other: Value) -> Result<Option<Ordering>, String> {
+        if self.is_float() || other.is_float() {
+            return Ok(self.as_f64()?.partial_cmp(&other.as_f64()?));
+        }
+        Ok(Some(self.as_i128()?.cmp(&other.as_i128()?)))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", *b as u8),
+            Value::Char(c) => write!(f, "{c}"),
+            Value::Int(i) => write!(f, "{i}"),
+            Value::Float(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+pub fn int_bits(data_type: IntType) -> (u32, bool) {
+    match data_type {
+        IntType::Signed8 => (8, true),
+        IntType::Unsigned8 => (8, false),
+        IntType::Signed16 => (16, true),
+        IntType::Unsigned16 => (16, false),
+        IntType::Signed32 => (32, true),
+        IntType::Unsigned32 => (32, false),
+        IntType::Signed64 => (64, true),
+        IntType::Unsigned64 => (64, false),
+    }
+}
+
+fn wrap_int(value: i128, data_type: IntType) -> i128 {
+    let (bits, signed) = int_bits(data_type);
+    let modulus = 1i128 << bits;
+    let wrapped = value.rem_euclid(modulus);
+    if signed && wrapped >= modulus / 2 {
+        wrapped - modulus
+    } else {
+        wrapped
+    }
+}
+
+/// Type name as it appears in the header of NebulaStream's result files.
+pub fn nes_type_string(data_type: NesType) -> String {
+    match data_type {
+        NesType::Undefined => "UNDEFINED".into(),
+        NesType::Bool => "BOOLEAN".into(),
+        NesType::Char => "CHAR".into(),
+        NesType::Int(t) => format!("INTEGER({} bits)", int_bits(t).0),
+        NesType::Float(FloatType::Bit32) => "FLOAT(32 bits)".into(),
+        NesType::Float(FloatType::Bit64) => "FLOAT(64 bits)".into(),
+    }
+}
+
+#[cfg(test)]
+mod value_tests {
+    use nes_types::{IntType, NesType};
+
+    use super::Value;
+
+    #[test]
+    fn ints_wrap() {
+        let i8_type = NesType::Int(IntType::Signed8);
+        let u8_type = NesType::Int(IntType::Unsigned8);
+        assert_eq!(Value::Int(-128), Value::Int(128).cast(i8_type).unwrap());
+        assert_eq!(Value::Int(255), Value::Int(-1).cast(u8_type).unwrap());
+        assert_eq!(Value::Int(3), Value::Float(3.9).cast(i8_type).unwrap());
+    }
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(
+            Value::Int(-5),
+            Value::parse("-5", NesType::Int(IntType::Signed64)).unwrap()
+        );
+        assert_eq!(Value::Bool(true), Value::parse("1", NesType::Bool).unwrap());
+        assert!(Value::parse("abc", NesType::f64()).is_err());
+    }
+}
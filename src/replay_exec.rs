@@ -1,4 +1,4 @@
-use std::fs;
+use std::{fs, path::PathBuf};
 
 use nes_rust_client::query::sink::Sink;
 
@@ -10,7 +10,7 @@ use crate::{
         test_case::{TestCase, TestSet},
     },
     yaml_util::load_yaml_array,
-    LancerConfig,
+    AchillesError, LancerConfig,
 };
 
 #[derive(Clone)]
@@ -49,42 +49,57 @@ impl ReplayExec {
     }
 }
 
-pub async fn replay_exec(replay_exec: &ReplayExec, config: &LancerConfig) {
+pub async fn replay_exec(
+    replay_exec: &ReplayExec,
+    config: &LancerConfig,
+) -> Result<(), AchillesError> {
     match replay_exec {
         ReplayExec::TestSet(location) => replay_exec_test_set(location, config).await,
         ReplayExec::TestCase(location) => replay_exec_test_case(location, config).await,
     }
 }
 
-fn create_replay_dir<T>(location: T, config: &LancerConfig) {}
-
-async fn replay_exec_test_set(location: &TestSetLocation, config: &LancerConfig) {
-    let Ok(test_sets) = load_yaml_array::<TestSet>(&config.path_config.test_sets(location.run_id))
-    else {
-        panic!("Unable to load test sets for run {}", location.run_id);
-    };
-    let Some(test_set) = test_sets
+fn load_test_set(
+    run_id: u32,
+    test_set_id: u32,
+    config: &LancerConfig,
+) -> Result<TestSet, AchillesError> {
+    let test_sets = load_yaml_array::<TestSet>(&config.path_config.test_sets(run_id))?;
+    test_sets
         .into_iter()
-        .find(|set| set.id == location.test_set_id)
-    else {
-        panic!("Unable to load test set {}", location.test_set_id);
-    };
+        .find(|set| set.id == test_set_id)
+        .ok_or_else(|| {
+            AchillesError::Config(format!(
+                "Unable to load test set {test_set_id} in run {run_id}"
+            ))
+        })
+}
+
+/// Recreates the `replay_results` directory of a test run and returns its path.
+fn create_replay_dir(run_id: u32, config: &LancerConfig) -> Result<PathBuf, AchillesError> {
+    let replay_results_path = config.path_config.test_run(run_id).join("replay_results");
+    if replay_results_path.exists() {
+        log::info!("Deleting existing files in path: {:?}", replay_results_path);
+        fs::remove_dir_all(&replay_results_path)
+            .map_err(|err| AchillesError::io(&replay_results_path, err))?;
+    }
+    fs::create_dir(&replay_results_path)
+        .map_err(|err| AchillesError::io(&replay_results_path, err))?;
+    Ok(replay_results_path)
+}
+
+async fn replay_exec_test_set(
+    location: &TestSetLocation,
+    config: &LancerConfig,
+) -> Result<(), AchillesError> {
+    let test_set = load_test_set(location.run_id, location.test_set_id, config)?;
     log::info!(
         "Loaded test set {} in run {}.",
         location.test_set_id,
         location.run_id
     );
 
-    // create replay folder
-    let replay_results_path = config
-        .path_config
-        .test_run(location.run_id)
-        .join("replay_results");
-    if replay_results_path.exists() {
-        log::info!("Deleting existing files in path: {:?}", replay_results_path);
-        fs::remove_dir_all(&replay_results_path).unwrap();
-    }
-    fs::create_dir(&replay_results_path).unwrap();
+    let replay_results_path = create_replay_dir(location.run_id, config)?;
     // update test set
     let origin_result_path = replay_results_path.join(format!("result-origin.csv"));
     let new_sink = Sink::csv_file(&origin_result_path, false);
@@ -112,43 +127,33 @@ async fn replay_exec_test_set(location: &TestSetLocation, config: &LancerConfig)
 
     let updated_test_set = TestSet {
         id: test_set.id,
+        seed: test_set.seed,
         strategy: test_set.strategy,
         origin,
         others,
     };
     let test_set_exec = process_test_set(location.run_id, updated_test_set, config).await;
-    check_test_set(&test_set_exec);
+    check_test_set(&test_set_exec, config);
+    Ok(())
 }
 
-async fn replay_exec_test_case(location: &TestCaseLocation, config: &LancerConfig) {
-    let Ok(test_sets) = load_yaml_array::<TestSet>(&config.path_config.test_sets(location.run_id))
-    else {
-        panic!("Unable to load test sets for run {}", location.run_id);
-    };
-    let Some(test_set) = test_sets
-        .into_iter()
-        .find(|set| set.id == location.test_set_id)
-    else {
-        panic!("Unable to load test set {}", location.test_set_id);
-    };
+async fn replay_exec_test_case(
+    location: &TestCaseLocation,
+    config: &LancerConfig,
+) -> Result<(), AchillesError> {
+    let test_set = load_test_set(location.run_id, location.test_set_id, config)?;
     let Some(test_case) = test_set.test_case(location.test_case_id) else {
-        panic!("Unable to load test case {}", location.test_case_id);
+        return Err(AchillesError::Config(format!(
+            "Unable to load test case {}",
+            location.test_case_id
+        )));
     };
     log::info!(
         "Loaded test set {} in run {}.",
         location.test_set_id,
         location.run_id
     );
-    // create new dir
-    let replay_results_path = config
-        .path_config
-        .test_run(location.run_id)
-        .join("replay_results");
-    if replay_results_path.exists() {
-        log::info!("Deleting existing files in path: {:?}", replay_results_path);
-        fs::remove_dir_all(&replay_results_path).unwrap();
-    }
-    fs::create_dir(&replay_results_path).unwrap();
+    let replay_results_path = create_replay_dir(location.run_id, config)?;
 
     let result_path = replay_results_path.join(format!("result.csv"));
 
@@ -162,4 +167,5 @@ async fn replay_exec_test_case(location: &TestCaseLocation, config: &LancerConfi
     };
     let test_case_exec = process_single_test_case(location.run_id, updated_test_case, config).await;
     log::info!("{:?}", test_case_exec);
+    Ok(())
 }
@@ -0,0 +1,399 @@
+use std::fmt;
+
+/// Untyped syntax tree of a RON document. In contrast to `ron::Value` it keeps the identifiers of
+/// enum variants and named structs, so a modified tree can be deserialized into the original type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RonNode {
+    /// An identifier like `None`, `true` or `Binary` that is optionally followed by a body in
+    /// parentheses, e.g. `Some(1)` or `Binary((lhs: ..))`.
+    Named(String, Option<Box<RonNode>>),
+    Tuple(Vec<RonNode>),
+    Struct(Vec<(String, RonNode)>),
+    Seq(Vec<RonNode>),
+    Map(Vec<(RonNode, RonNode)>),
+    /// Strings, chars and numbers are kept as they appear in the document.
+    Literal(String),
+    Unit,
+}
+
+impl RonNode {
+    pub fn parse(input: &str) -> Result<RonNode, String> {
+        let mut parser = Parser {
+            chars: input.chars().collect(),
+            pos: 0,
+        };
+        let node = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.chars.len() {
+            return Err(format!("Unexpected trailing input at {}.", parser.pos));
+        }
+        Ok(node)
+    }
+
+    /// Returns the value of the struct field `key` if this node is a struct or a named node whose
+    /// body is a struct or a tuple with a single struct.
+    pub fn field(&self, key: &str) -> Option<&RonNode> {
+        match self {
+            RonNode::Struct(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            RonNode::Named(_, Some(body)) => body.field(key),
+            RonNode::Tuple(values) if values.len() == 1 => values[0].field(key),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            RonNode::Named(name, _) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Number of nodes in the tree.
+    pub fn size(&self) -> usize {
+        1 + self.children().map(|child| child.size()).sum::<usize>()
+    }
+
+    pub fn children(&self) -> Box<dyn Iterator<Item = &RonNode> + '_> {
+        match self {
+            RonNode::Named(_, Some(body)) => Box::new(std::iter::once(body.as_ref())),
+            RonNode::Tuple(values) | RonNode::Seq(values) => Box::new(values.iter()),
+            RonNode::Struct(fields) => Box::new(fields.iter().map(|(_, v)| v)),
+            RonNode::Map(entries) => Box::new(entries.iter().flat_map(|(k, v)| [k, v])),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+
+    fn children_mut(&mut self) -> Box<dyn Iterator<Item = &mut RonNode> + '_> {
+        match self {
+            RonNode::Named(_, Some(body)) => Box::new(std::iter::once(body.as_mut())),
+            RonNode::Tuple(values) | RonNode::Seq(values) => Box::new(values.iter_mut()),
+            RonNode::Struct(fields) => Box::new(fields.iter_mut().map(|(_, v)| v)),
+            RonNode::Map(entries) => Box::new(entries.iter_mut().flat_map(|(k, v)| [k, v])),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Visits all nodes in pre-order.
+    pub fn walk<'a>(&'a self, visit: &mut impl FnMut(&'a RonNode)) {
+        visit(self);
+        for child in self.children() {
+            child.walk(visit);
+        }
+    }
+
+    /// Number of nodes in the tree that are equal to `target`.
+    pub fn count(&self, target: &RonNode) -> usize {
+        if self == target {
+            return 1;
+        }
+        self.children().map(|child| child.count(target)).sum()
+    }
+
+    /// Replaces every occurrence of `target` with `replacement` and returns the number of
+    /// replacements. Replacements are not searched again.
+    pub fn replace_all(&mut self, target: &RonNode, replacement: &RonNode) -> usize {
+        if self == target {
+            *self = replacement.clone();
+            return 1;
+        }
+        self.children_mut()
+            .map(|child| child.replace_all(target, replacement))
+            .sum()
+    }
+}
+
+impl fmt::Display for RonNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RonNode::Named(name, None) => write!(f, "{name}"),
+            RonNode::Named(name, Some(body)) => write!(f, "{name}{body}"),
+            RonNode::Tuple(values) => {
+                write!(f, "(")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, ")")
+            }
+            RonNode::Struct(fields) => {
+                write!(f, "(")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{key}:{value}")?;
+                }
+                write!(f, ")")
+            }
+            RonNode::Seq(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            RonNode::Map(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{key}:{value}")?;
+                }
+                write!(f, "}}")
+            }
+            RonNode::Literal(literal) => write!(f, "{literal}"),
+            RonNode::Unit => write!(f, "()"),
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.pos += 1;
+            } else if c == '/' && self.chars.get(self.pos + 1) == Some(&'/') {
+                while !matches!(self.peek(), Some('\n') | None) {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            other => Err(format!(
+                "Expected {expected:?} at {} but found {other:?}.",
+                self.pos
+            )),
+        }
+    }
+
+    /// Consumes a `,` if present and returns true if the closing delimiter `close` follows.
+    fn next_or_close(&mut self, close: char) -> Result<bool, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                Ok(self.peek() == Some(close))
+            }
+            Some(c) if c == close => Ok(true),
+            other => Err(format!(
+                "Expected ',' or {close:?} at {} but found {other:?}.",
+                self.pos
+            )),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<RonNode, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('(') => self.parse_parens(),
+            Some('[') => self.parse_seq(),
+            Some('{') => self.parse_map(),
+            Some('"') => self.parse_quoted('"'),
+            Some('\'') => self.parse_quoted('\''),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                Ok(self.parse_number())
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let ident = self.parse_ident();
+                if ident == "r" && matches!(self.peek(), Some('"') | Some('#')) {
+                    return Err("Raw strings are not supported.".into());
+                }
+                self.skip_whitespace();
+                if self.peek() == Some('(') {
+                    let body = self.parse_parens()?;
+                    return Ok(RonNode::Named(ident, Some(Box::new(body))));
+                }
+                Ok(RonNode::Named(ident, None))
+            }
+            other => Err(format!("Unexpected {other:?} at {}.", self.pos)),
+        }
+    }
+
+    fn parse_ident(&mut self) -> String {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '_' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn parse_number(&mut self) -> RonNode {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.' | '_') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        RonNode::Literal(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn parse_quoted(&mut self, quote: char) -> Result<RonNode, String> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                Some('\\') => self.pos += 2,
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => self.pos += 1,
+                None => return Err(format!("Unterminated literal starting at {start}.")),
+            }
+        }
+        Ok(RonNode::Literal(
+            self.chars[start..self.pos].iter().collect(),
+        ))
+    }
+
+    /// Parses `()`, a tuple `(a, b)` or a struct `(key: value, ..)`.
+    fn parse_parens(&mut self) -> Result<RonNode, String> {
+        self.expect('(')?;
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.pos += 1;
+            return Ok(RonNode::Unit);
+        }
+        if self.is_struct_key() {
+            let mut fields = Vec::new();
+            loop {
+                self.skip_whitespace();
+                let key = self.parse_ident();
+                self.expect(':')?;
+                fields.push((key, self.parse_value()?));
+                if self.next_or_close(')')? {
+                    break;
+                }
+            }
+            self.expect(')')?;
+            return Ok(RonNode::Struct(fields));
+        }
+        let mut values = Vec::new();
+        loop {
+            values.push(self.parse_value()?);
+            if self.next_or_close(')')? {
+                break;
+            }
+        }
+        self.expect(')')?;
+        Ok(RonNode::Tuple(values))
+    }
+
+    /// Looks ahead for `ident :` without consuming any input.
+    fn is_struct_key(&self) -> bool {
+        let mut pos = self.pos;
+        match self.chars.get(pos) {
+            Some(c) if c.is_alphabetic() || *c == '_' => (),
+            _ => return false,
+        }
+        while matches!(self.chars.get(pos), Some(c) if c.is_alphanumeric() || *c == '_') {
+            pos += 1;
+        }
+        while matches!(self.chars.get(pos), Some(c) if c.is_whitespace()) {
+            pos += 1;
+        }
+        self.chars.get(pos) == Some(&':')
+    }
+
+    fn parse_seq(&mut self) -> Result<RonNode, String> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() != Some(']') {
+            loop {
+                values.push(self.parse_value()?);
+                if self.next_or_close(']')? {
+                    break;
+                }
+            }
+        }
+        self.expect(']')?;
+        Ok(RonNode::Seq(values))
+    }
+
+    fn parse_map(&mut self) -> Result<RonNode, String> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() != Some('}') {
+            loop {
+                let key = self.parse_value()?;
+                self.expect(':')?;
+                entries.push((key, self.parse_value()?));
+                if self.next_or_close('}')? {
+                    break;
+                }
+            }
+        }
+        self.expect('}')?;
+        Ok(RonNode::Map(entries))
+    }
+}
+
+#[cfg(test)]
+mod ron_tree_tests {
+    use super::RonNode;
+
+    #[test]
+    fn roundtrip() {
+        let inputs = [
+            "()",
+            "Some(1)",
+            "[1,-2.5e3,\"a\\\"b\",'c']",
+            "{\"a\":(x:1,y:None),\"b\":[]}",
+            "(op:Binary((lhs:Field((name:\"f0\",data_type:Int(Signed64))),rhs:Literal((value:\"3\")),data_type:Bool,operator:Less)))",
+        ];
+        for input in inputs {
+            let node = RonNode::parse(input).unwrap();
+            assert_eq!(input, node.to_string());
+        }
+    }
+
+    #[test]
+    fn whitespace_and_trailing_commas() {
+        let node = RonNode::parse("( a : [1, 2, ], b: Some( ( x: 1, ) ), )").unwrap();
+        assert_eq!("(a:[1,2],b:Some((x:1)))", node.to_string());
+    }
+
+    #[test]
+    fn replace_all() {
+        let mut node = RonNode::parse("(a:Lit(1),b:Neg(Lit(1)),c:Lit(2))").unwrap();
+        let target = RonNode::parse("Lit(1)").unwrap();
+        let replacement = RonNode::parse("Lit(0)").unwrap();
+        assert_eq!(2, node.replace_all(&target, &replacement));
+        assert_eq!("(a:Lit(0),b:Neg(Lit(0)),c:Lit(2))", node.to_string());
+        assert_eq!(Some(&RonNode::parse("Lit(2)").unwrap()), node.field("c"));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod readiness;
 pub mod runner;
 pub mod runner_config;
 pub mod runner_status;
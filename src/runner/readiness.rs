@@ -0,0 +1,81 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+use yaml_rust2::YamlLoader;
+
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Returns true if a TCP connection to `address` can be established.
+pub fn is_port_open(address: SocketAddr) -> bool {
+    TcpStream::connect_timeout(&address, PROBE_TIMEOUT).is_ok()
+}
+
+/// Returns the number of workers in the topology of the coordinator with the REST API at
+/// `rest_address`. The coordinator itself is part of the topology and is not counted.
+pub fn registered_worker_count(rest_address: SocketAddr) -> Result<usize, String> {
+    let body = http_get(rest_address, "/v1/nes/topology")?;
+    let nodes = count_nodes(&body)?;
+    Ok(nodes.saturating_sub(1))
+}
+
+/// Counts the entries of the `nodes` array of a topology, e.g.
+/// `{"edges":[{"source":2,"target":1}],"nodes":[{"id":1,..},{"id":2,..}]}`. JSON is valid YAML, so
+/// the topology is parsed with the YAML parser.
+fn count_nodes(topology: &str) -> Result<usize, String> {
+    let docs = YamlLoader::load_from_str(topology)
+        .map_err(|err| format!("Unable to parse topology: {err}"))?;
+    let nodes = docs.first().and_then(|doc| doc["nodes"].as_vec());
+    let Some(nodes) = nodes else {
+        return Err(format!("Unexpected topology: {topology}"));
+    };
+    if !nodes.iter().all(|node| node["id"].as_i64().is_some()) {
+        return Err(format!("Topology node without id: {topology}"));
+    }
+    Ok(nodes.len())
+}
+
+/// Minimal HTTP/1.0 GET, which avoids chunked responses.
+fn http_get(address: SocketAddr, path: &str) -> Result<String, String> {
+    let mut stream =
+        TcpStream::connect_timeout(&address, PROBE_TIMEOUT).map_err(|err| err.to_string())?;
+    stream
+        .set_read_timeout(Some(PROBE_TIMEOUT))
+        .map_err(|err| err.to_string())?;
+    write!(stream, "GET {path} HTTP/1.0\r\nHost: {address}\r\n\r\n")
+        .map_err(|err| err.to_string())?;
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|err| err.to_string())?;
+    let Some((head, body)) = response.split_once("\r\n\r\n") else {
+        return Err(format!("Malformed response from {address}{path}."));
+    };
+    let status_line = head.lines().next().unwrap_or_default();
+    if !status_line.contains(" 200") {
+        return Err(format!("GET {address}{path} returned {status_line}."));
+    }
+    Ok(body.to_string())
+}
+
+#[cfg(test)]
+mod readiness_tests {
+    use super::count_nodes;
+
+    #[test]
+    fn counts_topology_nodes() {
+        let topology = r#"{"edges":[{"source":2,"target":1},{"source":3,"target":1}],"nodes":[{"available_resources":65535,"id":1,"ip_address":"127.0.0.1"},{"available_resources":1,"id":2,"ip_address":"127.0.0.1"},{"available_resources":1,"id":3,"ip_address":"127.0.0.1"}]}"#;
+        assert_eq!(count_nodes(topology), Ok(3));
+        assert!(count_nodes("{}").is_err());
+        assert!(count_nodes("{\"nodes\": [{\"id\": 1,").is_err());
+    }
+
+    #[test]
+    fn ids_outside_of_nodes_are_not_counted() {
+        let topology =
+            r#"{"nodes":[{"id":1,"children":[{"id":2}],"ip_address":"[::1]"}],"edges":[{"id":3}]}"#;
+        assert_eq!(count_nodes(topology), Ok(1));
+    }
+}
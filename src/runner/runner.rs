@@ -4,10 +4,16 @@ use std::{
     path::Path,
     process::{Child, Command, Stdio},
     thread,
-    time::Duration,
+    time::Instant,
 };
 
-use crate::runner::runner_status::{ProcessStatus, RunnerStatus};
+use crate::{
+    error::AchillesError,
+    runner::{
+        readiness::{is_port_open, registered_worker_count},
+        runner_status::{ProcessStatus, RunnerStatus},
+    },
+};
 
 use super::runner_config::{OutputIO, RunnerConfig};
 
@@ -26,40 +32,102 @@ impl Runner {
         }
     }
 
-    pub fn start_coordinator(&mut self) {
+    pub fn start_coordinator(&mut self) -> Result<(), AchillesError> {
         let child = self.start_sub_process(
             &self.config.coordinator_exec_path,
             self.config.coordinator_config_path.as_deref(),
-        );
+        )?;
         self.coordinator = Some(child);
+        Ok(())
     }
 
-    pub fn start_workers(&mut self) {
+    pub fn start_workers(&mut self) -> Result<(), AchillesError> {
         for path in &self.config.worker_config_path {
-            let child = self.start_sub_process(&self.config.worker_exec_path, Some(path));
+            let child = self.start_sub_process(&self.config.worker_exec_path, Some(path))?;
             self.workers.push(child);
         }
+        Ok(())
     }
 
-    pub fn start_all(&mut self) -> Result<RunnerStatus, io::Error> {
-        self.start_coordinator();
-        thread::sleep(Duration::from_secs(3));
-        self.start_workers();
-        thread::sleep(Duration::from_secs(3));
-        self.health_check()
+    /// Starts the coordinator and the workers and waits until the coordinator accepts connections
+    /// and all workers are registered in its topology. Stops everything if the startup fails.
+    pub fn start_all(&mut self) -> Result<(), AchillesError> {
+        let result = self.start_and_wait();
+        if result.is_err() {
+            self.stop_all();
+        }
+        result
+    }
+
+    fn start_and_wait(&mut self) -> Result<(), AchillesError> {
+        self.start_coordinator()?;
+        let rest_address = self.config.coordinator_rest_address;
+        let rpc_address = self.config.coordinator_rpc_address;
+        self.wait_until_ready("coordinator ports", || {
+            rest_address.map_or(true, is_port_open) && rpc_address.map_or(true, is_port_open)
+        })?;
+
+        self.start_workers()?;
+        let worker_count = self.workers.len();
+        self.wait_until_ready("worker registration", || {
+            rest_address.map_or(true, |address| {
+                registered_worker_count(address).is_ok_and(|count| count >= worker_count)
+            })
+        })
     }
 
-    fn start_sub_process(&self, exec_path: &Path, config_path: Option<&Path>) -> Child {
-        let exec_path = exec_path
-            .canonicalize()
-            .expect("Failed to get absolute path.");
+    /// Polls `is_ready` with exponential backoff until it returns true. Fails if a process exits
+    /// or the startup timeout is exceeded.
+    fn wait_until_ready(
+        &mut self,
+        probe_name: &str,
+        mut is_ready: impl FnMut() -> bool,
+    ) -> Result<(), AchillesError> {
+        let readiness = self.config.readiness.clone();
+        let start_time = Instant::now();
+        let mut backoff = readiness.initial_backoff;
+        loop {
+            let status = self
+                .health_check()
+                .map_err(|err| AchillesError::Runner(err.to_string()))?;
+            if !status.all_running() {
+                return Err(AchillesError::Runner(format!(
+                    "NebulaStream exited while waiting for {probe_name}: {:?}",
+                    status.collect_errors()
+                )));
+            }
+            if is_ready() {
+                log::debug!("{probe_name} ready after {:?}.", start_time.elapsed());
+                return Ok(());
+            }
+            if start_time.elapsed() > readiness.startup_timeout {
+                return Err(AchillesError::Runner(format!(
+                    "Timed out after {:?} waiting for {probe_name}.",
+                    readiness.startup_timeout
+                )));
+            }
+            thread::sleep(backoff);
+            backoff = readiness.next_backoff(backoff);
+        }
+    }
+
+    fn start_sub_process(
+        &self,
+        exec_path: &Path,
+        config_path: Option<&Path>,
+    ) -> Result<Child, AchillesError> {
+        let exec_path = exec_path.canonicalize().map_err(|err| {
+            AchillesError::Runner(format!(
+                "Failed to get absolute path of {exec_path:?}: {err}"
+            ))
+        })?;
         log::info!("Attempt starting executable in path {:?}", exec_path);
         let mut cmd = Command::new(exec_path);
         if let Some(path) = config_path {
             if let Err(err) = path.canonicalize() {
-                log::error!("Failed to canonicalize config path: {err}");
-                // TODO: This should return an error
-                // return Err(...);
+                return Err(AchillesError::Runner(format!(
+                    "Failed to get absolute path of config {path:?}: {err}"
+                )));
             }
             let arg = format!("--configPath={}", path.to_string_lossy());
             log::info!("with Argument: {}", &arg);
@@ -70,7 +138,8 @@ impl Runner {
             OutputIO::Null => cmd.stdout(Stdio::null()).stderr(Stdio::null()),
             OutputIO::Print => cmd.stdout(Stdio::inherit()).stderr(Stdio::inherit()),
         };
-        cmd.spawn().expect("Executable field should be there!")
+        cmd.spawn()
+            .map_err(|err| AchillesError::Runner(format!("Failed to start {exec_path:?}: {err}")))
     }
 
     pub fn stop_all(&mut self) {
@@ -82,7 +151,9 @@ impl Runner {
         log::info!("Attempting to stop workers:");
         let mut index = 0;
         while let Some(mut worker) = self.workers.pop() {
-            worker.kill().expect("Worker should be killable!");
+            if let Err(err) = worker.kill() {
+                log::warn!("Failed to kill worker: {err}");
+            }
             self.cleanup_subprocess(worker, format!("worker{index}_output.log"));
             index += 1;
         }
@@ -95,38 +166,40 @@ impl Runner {
             log::warn!("Coordinator does not exist!");
             return;
         };
-        coord.kill().expect("Coordinator should be killable!");
+        if let Err(err) = coord.kill() {
+            log::warn!("Failed to kill coordinator: {err}");
+        }
         self.cleanup_subprocess(coord, "coordinator_output.log".into());
         log::info!("Stopped coordinator.");
     }
 
-    fn cleanup_subprocess(&self, mut child_process: Child, file_name: String) {
-        if let OutputIO::ToFile(ref file_path) = &self.config.output_io {
-            let output = child_process
-                .wait_with_output()
-                .expect("Wait with output should not fail!");
-            let mut output_file = File::create(file_path.join(file_name))
-                .expect("Should not failt to create worker output file!");
-            output_file
-                .write_all(&output.stdout)
-                .expect("Should not fail to write stdout to output file!");
-            output_file
-                .write_all(&output.stderr)
-                .expect("Should not fail to write stderr to output file!");
-            return;
+    /// Waits for a killed process and writes its output to `file_name` if the output is written to
+    /// files. Failures are only logged, so the remaining processes are still stopped.
+    fn cleanup_subprocess(&self, child_process: Child, file_name: String) {
+        if let Err(err) = self.wait_and_write_output(child_process, &file_name) {
+            log::warn!("Failed to clean up process with output {file_name}: {err}");
         }
-        child_process.wait().expect("Wait should not fail!");
+    }
+
+    fn wait_and_write_output(&self, mut child_process: Child, file_name: &str) -> io::Result<()> {
+        let OutputIO::ToFile(ref file_path) = &self.config.output_io else {
+            child_process.wait()?;
+            return Ok(());
+        };
+        let output = child_process.wait_with_output()?;
+        let mut output_file = File::create(file_path.join(file_name))?;
+        output_file.write_all(&output.stdout)?;
+        output_file.write_all(&output.stderr)
     }
 
     pub fn health_check(&mut self) -> io::Result<RunnerStatus> {
         log::debug!("Check runner health:");
         log::trace!("Check coordinators health:");
-        let Some(ref mut coord) = self.coordinator else {
-            panic!("Coordinator should exist!");
+        let coordinator_status = match self.coordinator {
+            Some(ref mut coord) => ProcessStatus::try_from(coord)?,
+            None => ProcessStatus::Error("Coordinator is not started.".into()),
         };
 
-        let coordinator_status = ProcessStatus::try_from(coord)?;
-
         log::trace!("Check workers health:");
         let mut worker_status = Vec::new();
         for worker in self.workers.iter_mut() {
@@ -1,4 +1,10 @@
-use std::path::{Path, PathBuf};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use yaml_rust2::Yaml;
 
 // TODO: Enable this to be read from a file
 #[derive(Clone)]
@@ -8,6 +14,35 @@ pub struct RunnerConfig {
     pub coordinator_config_path: Option<PathBuf>,
     pub worker_config_path: Vec<PathBuf>,
     pub output_io: OutputIO,
+    /// REST and RPC address of the coordinator. Readiness is only probed if they are set.
+    pub coordinator_rest_address: Option<SocketAddr>,
+    pub coordinator_rpc_address: Option<SocketAddr>,
+    pub readiness: ReadinessConfig,
+}
+
+/// Timeouts of the readiness probes. Probes are retried with an exponential backoff between
+/// `initial_backoff` and `max_backoff`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadinessConfig {
+    pub startup_timeout: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            startup_timeout: Duration::from_secs(30),
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl ReadinessConfig {
+    pub fn next_backoff(&self, backoff: Duration) -> Duration {
+        (backoff * 2).min(self.max_backoff)
+    }
 }
 
 #[derive(PartialEq, Eq, Clone)]
@@ -22,3 +57,22 @@ impl RunnerConfig {
         todo!();
     }
 }
+
+/// `OutputIO` is either the string `Null` or `Print`, or a mapping `ToFile: <path>`.
+impl TryFrom<&Yaml> for OutputIO {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        if let Some(str) = value.as_str() {
+            return match str {
+                "Null" => Ok(OutputIO::Null),
+                "Print" => Ok(OutputIO::Print),
+                err => Err(format!("Unknown OutputIO: {err}")),
+            };
+        }
+        let Some(path) = value["ToFile"].as_str() else {
+            return Err("Failed to parse OutputIO: Expected Null, Print or ToFile.".into());
+        };
+        Ok(OutputIO::ToFile(PathBuf::from(path)))
+    }
+}
@@ -0,0 +1,171 @@
+//! Derivation of the sub-seeds used in the pipeline. Every random decision is made by a `StdRng`
+//! that is seeded with a seed derived from `TestConfig::seed`, so the same seed and config always
+//! reproduce the same data files and queries.
+use std::fs;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{AchillesError, LancerConfig};
+
+const STREAM_GEN_STREAM: u64 = 1;
+const QUERY_GEN_STREAM: u64 = 2;
+const DATA_VALUES_STREAM: u64 = 3;
+
+/// Mixes `seed` and `stream` into a new seed (SplitMix64 finalizer).
+pub fn derive_seed(seed: u64, stream: u64) -> u64 {
+    let mut z = seed
+        .wrapping_add(stream.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+pub fn test_run_seed(master_seed: u64, test_run_id: u32) -> u64 {
+    derive_seed(master_seed, test_run_id.into())
+}
+
+/// Seed for the schema generation of a test run.
+pub fn stream_gen_seed(test_run_seed: u64) -> u64 {
+    derive_seed(test_run_seed, STREAM_GEN_STREAM)
+}
+
+/// Seed for the data of a single physical source.
+pub fn physical_source_seed(stream_gen_seed: u64, source_id: u32, physical_source_id: u32) -> u64 {
+    derive_seed(
+        derive_seed(stream_gen_seed, source_id.into()),
+        physical_source_id.into(),
+    )
+}
+
+/// Seed for the queries of a single test set.
+pub fn test_set_seed(test_run_seed: u64, test_set_id: u32) -> u64 {
+    derive_seed(
+        derive_seed(test_run_seed, QUERY_GEN_STREAM),
+        test_set_id.into(),
+    )
+}
+
+/// Seed for sampling the data values that literals are drawn from.
+pub fn data_values_seed(test_run_seed: u64) -> u64 {
+    derive_seed(test_run_seed, DATA_VALUES_STREAM)
+}
+
+pub fn seeded_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// Stores the master seed in the base directory, so a campaign can be regenerated from its files.
+pub fn write_master_seed(config: &LancerConfig) -> Result<(), AchillesError> {
+    let path = config.path_config.seed();
+    fs::write(&path, config.test_config.seed.to_string())
+        .map_err(|err| AchillesError::io(&path, err))
+}
+
+/// Reads the master seed of an existing campaign, `None` if it has no seed file.
+pub fn read_master_seed(config: &LancerConfig) -> Result<Option<u64>, AchillesError> {
+    let path = config.path_config.seed();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|err| AchillesError::io(&path, err))?;
+    let seed = content.trim().parse().map_err(|err| {
+        AchillesError::Config(format!("Unable to parse the seed in {path:?}: {err}"))
+    })?;
+    Ok(Some(seed))
+}
+
+/// A resumed campaign continues with the master seed of its files. A campaign without a seed file
+/// is started with the configured seed, which is stored for the next resume.
+pub fn resume_master_seed(config: &mut LancerConfig) -> Result<(), AchillesError> {
+    match read_master_seed(config)? {
+        Some(seed) => {
+            if seed != config.test_config.seed {
+                log::info!("Resuming with seed {seed} of the existing campaign.");
+            }
+            config.test_config.seed = seed;
+            Ok(())
+        }
+        None => write_master_seed(config),
+    }
+}
+
+#[cfg(test)]
+mod seed_tests {
+    use nes_types::NesType;
+
+    use crate::stream_gen::data_generator::{
+        EdgeValueStrategy, FieldGeneratorStrategy, KeyStrategy, RandomStrategy, TimeStampStrategy,
+    };
+
+    use super::*;
+
+    fn generate(strategy: &mut dyn FieldGeneratorStrategy) -> Vec<String> {
+        (0..100).map(|_| strategy.generate_field()).collect()
+    }
+
+    #[test]
+    fn derived_seeds_are_stable_and_distinct() {
+        let run0 = test_run_seed(42, 0);
+        assert_eq!(run0, test_run_seed(42, 0));
+        assert_ne!(run0, test_run_seed(42, 1));
+        assert_ne!(run0, test_run_seed(43, 0));
+        assert_ne!(test_set_seed(run0, 0), test_set_seed(run0, 1));
+        let stream = stream_gen_seed(run0);
+        assert_ne!(
+            physical_source_seed(stream, 0, 1),
+            physical_source_seed(stream, 1, 0)
+        );
+    }
+
+    #[test]
+    fn master_seed_is_persisted() {
+        let mut config = crate::LancerConfig::default();
+        config.path_config.base = std::env::temp_dir().join("achilles-master-seed");
+        std::fs::create_dir_all(config.path_config.base()).unwrap();
+        let _ = std::fs::remove_file(config.path_config.seed());
+        assert_eq!(read_master_seed(&config).unwrap(), None);
+        config.test_config.seed = u64::MAX;
+        write_master_seed(&config).unwrap();
+        assert_eq!(read_master_seed(&config).unwrap(), Some(u64::MAX));
+    }
+
+    #[test]
+    fn resume_uses_the_persisted_seed() {
+        let mut config = crate::LancerConfig::default();
+        config.path_config.base = std::env::temp_dir().join("achilles-resume-seed");
+        std::fs::create_dir_all(config.path_config.base()).unwrap();
+        let _ = std::fs::remove_file(config.path_config.seed());
+        config.test_config.seed = 7;
+        resume_master_seed(&mut config).unwrap();
+        assert_eq!(read_master_seed(&config).unwrap(), Some(7));
+        config.test_config.seed = 8;
+        resume_master_seed(&mut config).unwrap();
+        assert_eq!(config.test_config.seed, 7);
+    }
+
+    #[test]
+    fn data_generators_are_deterministic() {
+        let data_type = NesType::f64();
+        assert_eq!(
+            generate(&mut RandomStrategy::new(data_type, 7)),
+            generate(&mut RandomStrategy::new(data_type, 7))
+        );
+        assert_ne!(
+            generate(&mut RandomStrategy::new(data_type, 7)),
+            generate(&mut RandomStrategy::new(data_type, 8))
+        );
+        assert_eq!(
+            generate(&mut EdgeValueStrategy::new(data_type, 0.5, 7)),
+            generate(&mut EdgeValueStrategy::new(data_type, 0.5, 7))
+        );
+        assert_eq!(
+            generate(&mut TimeStampStrategy::new(100, 7)),
+            generate(&mut TimeStampStrategy::new(100, 7))
+        );
+        assert_eq!(
+            generate(&mut KeyStrategy::new(0..21, 7)),
+            generate(&mut KeyStrategy::new(0..21, 7))
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::{fmt, str::FromStr};
+
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Stages {
     #[default]
@@ -6,3 +8,29 @@ pub enum Stages {
     QueryExec,
     Evaluation,
 }
+
+impl FromStr for Stages {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "StreamGen" => Ok(Stages::StreamGen),
+            "QueryGen" => Ok(Stages::QueryGen),
+            "QueryExec" => Ok(Stages::QueryExec),
+            "Evaluation" => Ok(Stages::Evaluation),
+            err => Err(format!("Unknown stage: {err}")),
+        }
+    }
+}
+
+impl fmt::Display for Stages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stages::StreamGen => "StreamGen",
+            Stages::QueryGen => "QueryGen",
+            Stages::QueryExec => "QueryExec",
+            Stages::Evaluation => "Evaluation",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -1,7 +1,14 @@
 use std::ops::Range;
 
 use nes_types::{FloatType, IntType, NesType};
-use rand::{rngs::ThreadRng, thread_rng, Rng};
+use rand::{
+    distributions::{Alphanumeric, Distribution, WeightedIndex},
+    rngs::StdRng,
+    seq::SliceRandom,
+    Rng, SeedableRng,
+};
+
+use crate::{edge_values::edge_values, reference::value::int_bits};
 
 pub struct RecordGenerator {
     pub field_generators: Vec<FieldGenerator>,
@@ -27,6 +34,18 @@ impl FieldGenerator {
         }
     }
 
+    pub fn with_boxed_strategy(
+        field_name: impl Into<String>,
+        data_type: NesType,
+        strategy: Box<dyn FieldGeneratorStrategy>,
+    ) -> Self {
+        Self {
+            field_name: field_name.into(),
+            data_type,
+            strategy,
+        }
+    }
+
     pub fn generate_field(&mut self) -> String {
         self.strategy.generate_field()
     }
@@ -41,43 +60,86 @@ impl RecordGenerator {
     }
 
     pub fn generate_record(&mut self) -> Vec<String> {
-        self.field_generators
-            .iter_mut()
-            .map(|field_gen| field_gen.generate_field())
-            .collect()
+        let mut record = Vec::with_capacity(self.field_generators.len());
+        for field_gen in self.field_generators.iter_mut() {
+            let value = field_gen.strategy.generate_field_in_record(&record);
+            record.push(value);
+        }
+        record
     }
 }
 
 pub trait FieldGeneratorStrategy {
     fn generate_field(&mut self) -> String;
+
+    /// Generates the value of a field of a record whose previous fields are `record`. Only
+    /// strategies that depend on other fields of the record override it.
+    fn generate_field_in_record(&mut self, _record: &[String]) -> String {
+        self.generate_field()
+    }
 }
 
 pub struct RandomStrategy {
     data_type: NesType,
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl RandomStrategy {
-    pub fn new(data_type: NesType) -> Self {
-        let rng = thread_rng();
+    pub fn new(data_type: NesType, seed: u64) -> Self {
+        let rng = StdRng::seed_from_u64(seed);
         Self { data_type, rng }
     }
 }
 
 impl FieldGeneratorStrategy for RandomStrategy {
-    // FIXME: implement this function
     fn generate_field(&mut self) -> String {
         match self.data_type {
             NesType::Undefined => panic!("FieldGenerator data_type cannot be Undefined"),
             NesType::Bool => self.rng.gen::<bool>().to_string(),
-            NesType::Char => panic!("FieldGenerator char is currently not supported!"),
+            NesType::Char => generate_char(&mut self.rng),
             NesType::Int(t) => generate_int(&mut self.rng, t),
             NesType::Float(t) => generate_float(&mut self.rng, t),
         }
     }
 }
 
-fn generate_int(rng: &mut ThreadRng, data_type: IntType) -> String {
+/// Draws values from the edge values of its type, see `edge_values`, with probability
+/// `edge_value_ratio` and uniformly from the whole range of the type otherwise. The edge values
+/// also make duplicates frequent.
+pub struct EdgeValueStrategy {
+    random: RandomStrategy,
+    edge_values: Vec<String>,
+    edge_value_ratio: f64,
+}
+
+impl EdgeValueStrategy {
+    pub fn new(data_type: NesType, edge_value_ratio: f64, seed: u64) -> Self {
+        Self {
+            random: RandomStrategy::new(data_type, seed),
+            edge_values: edge_values(data_type),
+            edge_value_ratio,
+        }
+    }
+}
+
+impl FieldGeneratorStrategy for EdgeValueStrategy {
+    fn generate_field(&mut self) -> String {
+        let rng = &mut self.random.rng;
+        if rng.gen_bool(self.edge_value_ratio) {
+            if let Some(value) = self.edge_values.choose(rng) {
+                return value.clone();
+            }
+        }
+        self.random.generate_field()
+    }
+}
+
+/// Alphanumeric, so the value never needs to be quoted or escaped in the csv files.
+pub fn generate_char(rng: &mut impl Rng) -> String {
+    char::from(rng.sample(Alphanumeric)).to_string()
+}
+
+fn generate_int(rng: &mut impl Rng, data_type: IntType) -> String {
     match data_type {
         IntType::Signed8 => rng.gen::<i8>().to_string(),
         IntType::Unsigned8 => rng.gen::<u8>().to_string(),
@@ -90,7 +152,7 @@ fn generate_int(rng: &mut ThreadRng, data_type: IntType) -> String {
     }
 }
 
-fn generate_float(rng: &mut ThreadRng, data_type: FloatType) -> String {
+fn generate_float(rng: &mut impl Rng, data_type: FloatType) -> String {
     match data_type {
         FloatType::Bit32 => rng.gen::<f32>().to_string(),
         FloatType::Bit64 => rng.gen::<f64>().to_string(),
@@ -117,23 +179,23 @@ impl FieldGeneratorStrategy for IncStrategy {
 
 /// This strategy generates bursty time stamps
 pub struct TimeStampStrategy {
-    current_time: u32,
+    current_time: u64,
     burst_remaining: u32,
     in_burst: bool,
     burst_range: Range<u32>,
     burst_interval_range: Range<u32>,
     quiet_interval_range: Range<u32>,
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl TimeStampStrategy {
-    pub fn new(start_time: u32) -> Self {
-        let mut rng = thread_rng();
+    pub fn new(start_time: u32, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
         let burst_range = 0..5;
         let burst_interval_range = 0..500;
         let quiet_interval_range = 500..3000;
         Self {
-            current_time: start_time,
+            current_time: start_time.into(),
             // inc_range,
             burst_remaining: rng.gen_range(burst_range.clone()),
             burst_interval_range,
@@ -145,33 +207,111 @@ impl TimeStampStrategy {
     }
 }
 
-impl FieldGeneratorStrategy for TimeStampStrategy {
-    fn generate_field(&mut self) -> String {
+impl TimeStampStrategy {
+    fn next_time_stamp(&mut self) -> u64 {
         if self.in_burst {
-            self.current_time += self.rng.gen_range(self.burst_interval_range.clone());
+            self.current_time += u64::from(self.rng.gen_range(self.burst_interval_range.clone()));
             if self.burst_remaining > 0 {
                 self.burst_remaining -= 1;
-                return self.current_time.to_string();
+                return self.current_time;
             }
             self.in_burst = false;
             self.burst_remaining = self.rng.gen_range(self.burst_range.clone());
-            return self.current_time.to_string();
+            return self.current_time;
         }
-        self.current_time += self.rng.gen_range(self.quiet_interval_range.clone());
+        self.current_time += u64::from(self.rng.gen_range(self.quiet_interval_range.clone()));
         self.in_burst = true;
-        self.current_time.to_string()
+        self.current_time
+    }
+}
+
+impl FieldGeneratorStrategy for TimeStampStrategy {
+    fn generate_field(&mut self) -> String {
+        self.next_time_stamp().to_string()
+    }
+}
+
+/// Upper bound of `TimeStampDisorder::max_gap`, one day. Time stamps are `u64`, so even 2^32
+/// records with gaps of this size cannot overflow.
+pub const MAX_TIME_STAMP_GAP: u32 = 24 * 60 * 60 * 1000;
+
+/// Disorder of the generated time stamps in milliseconds. The default generates monotonically
+/// increasing time stamps.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeStampDisorder {
+    /// Every record is delayed by up to `max_jitter`, so the time stamps are out of order by at
+    /// most this bound.
+    pub max_jitter: u32,
+    /// Probability that a record is late, i.e. delayed by more than `allowed_lateness`.
+    pub late_ratio: f64,
+    /// Maximal delay of late records beyond `allowed_lateness`.
+    pub max_late_delay: u32,
+    /// Probability of a large gap before a record.
+    pub gap_ratio: f64,
+    /// Maximal size of a gap, at most `MAX_TIME_STAMP_GAP`.
+    pub max_gap: u32,
+    /// All physical sources of a logical source generate the same time stamps.
+    pub shared_time_stamps: bool,
+    /// Allowed lateness of the event time watermarks of NebulaStream. Records that are delayed by
+    /// at most the allowed lateness are not dropped by windows.
+    pub allowed_lateness: u32,
+}
+
+impl TimeStampDisorder {
+    /// Returns if windows may drop records, because they arrive after the watermark passed their
+    /// window. Windows of different sizes drop different records then.
+    pub fn has_late_records(&self) -> bool {
+        self.max_jitter > self.allowed_lateness
+            || (self.late_ratio > 0.0 && self.max_late_delay > 0)
+    }
+}
+
+/// Applies a `TimeStampDisorder` to the bursty time stamps of a `TimeStampStrategy`. The disorder
+/// draws from its own rng, so the undisturbed time stamps do not depend on the disorder.
+pub struct DisorderedTimeStampStrategy {
+    time_stamps: TimeStampStrategy,
+    disorder: TimeStampDisorder,
+    rng: StdRng,
+}
+
+impl DisorderedTimeStampStrategy {
+    pub fn new(time_stamps: TimeStampStrategy, disorder: TimeStampDisorder, seed: u64) -> Self {
+        let rng = StdRng::seed_from_u64(seed);
+        Self {
+            time_stamps,
+            disorder,
+            rng,
+        }
+    }
+}
+
+impl FieldGeneratorStrategy for DisorderedTimeStampStrategy {
+    fn generate_field(&mut self) -> String {
+        let disorder = &self.disorder;
+        if disorder.max_gap > 0 && self.rng.gen_bool(disorder.gap_ratio) {
+            self.time_stamps.current_time += u64::from(self.rng.gen_range(1..=disorder.max_gap));
+        }
+        let time_stamp = self.time_stamps.next_time_stamp();
+        let delay = if disorder.max_late_delay > 0 && self.rng.gen_bool(disorder.late_ratio) {
+            disorder
+                .allowed_lateness
+                .saturating_add(self.rng.gen_range(1..=disorder.max_late_delay))
+        } else {
+            self.rng.gen_range(0..=disorder.max_jitter)
+        };
+        time_stamp.saturating_sub(delay.into()).to_string()
     }
 }
 
 /// This strategy generates bursty time stamps
 pub struct KeyStrategy {
     key_range: Range<u32>,
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl KeyStrategy {
-    pub fn new(key_range: Range<u32>) -> Self {
-        let rng = thread_rng();
+    pub fn new(key_range: Range<u32>, seed: u64) -> Self {
+        let rng = StdRng::seed_from_u64(seed);
         Self { key_range, rng }
     }
 }
@@ -181,3 +321,306 @@ impl FieldGeneratorStrategy for KeyStrategy {
         self.rng.gen_range(self.key_range.clone()).to_string()
     }
 }
+
+/// Draws the integers `0..n` following a Zipf distribution with `exponent`. 0 is the most frequent
+/// value, which makes keys skewed.
+pub struct ZipfStrategy {
+    distribution: WeightedIndex<f64>,
+    rng: StdRng,
+}
+
+impl ZipfStrategy {
+    pub fn new(n: u32, exponent: f64, seed: u64) -> Result<Self, String> {
+        let weights = (1..=n).map(|rank| 1.0 / f64::from(rank).powf(exponent));
+        let distribution = WeightedIndex::new(weights)
+            .map_err(|err| format!("Invalid Zipf distribution with n = {n}: {err}"))?;
+        let rng = StdRng::seed_from_u64(seed);
+        Ok(Self { distribution, rng })
+    }
+}
+
+impl FieldGeneratorStrategy for ZipfStrategy {
+    fn generate_field(&mut self) -> String {
+        self.distribution.sample(&mut self.rng).to_string()
+    }
+}
+
+/// Draws normally distributed values that are clamped to `min..=max` and converted to `data_type`.
+pub struct NormalStrategy {
+    data_type: NesType,
+    mean: f64,
+    std_dev: f64,
+    min: f64,
+    max: f64,
+    rng: StdRng,
+}
+
+impl NormalStrategy {
+    pub fn new(data_type: NesType, mean: f64, std_dev: f64, min: f64, max: f64, seed: u64) -> Self {
+        let rng = StdRng::seed_from_u64(seed);
+        Self {
+            data_type,
+            mean,
+            std_dev,
+            min,
+            max,
+            rng,
+        }
+    }
+}
+
+impl FieldGeneratorStrategy for NormalStrategy {
+    fn generate_field(&mut self) -> String {
+        let value = self.mean + self.std_dev * standard_normal(&mut self.rng);
+        format_number(value.clamp(self.min, self.max), self.data_type)
+    }
+}
+
+/// Generates `start`, `start + step`, ... and starts again after `cycle` values if `cycle` is
+/// not 0.
+pub struct SequentialStrategy {
+    data_type: NesType,
+    start: f64,
+    step: f64,
+    cycle: u64,
+    index: u64,
+}
+
+impl SequentialStrategy {
+    pub fn new(data_type: NesType, start: f64, step: f64, cycle: u64) -> Self {
+        Self {
+            data_type,
+            start,
+            step,
+            cycle,
+            index: 0,
+        }
+    }
+}
+
+impl FieldGeneratorStrategy for SequentialStrategy {
+    fn generate_field(&mut self) -> String {
+        let value = self.start + self.step * self.index as f64;
+        self.index += 1;
+        if self.cycle != 0 && self.index >= self.cycle {
+            self.index = 0;
+        }
+        format_number(value, self.data_type)
+    }
+}
+
+/// Draws uniformly from a small set of values.
+pub struct CategoricalStrategy {
+    values: Vec<String>,
+    rng: StdRng,
+}
+
+impl CategoricalStrategy {
+    pub fn new(values: Vec<String>, seed: u64) -> Self {
+        let rng = StdRng::seed_from_u64(seed);
+        Self { values, rng }
+    }
+
+    /// Draws `cardinality` random values of `data_type` as categories.
+    pub fn random(data_type: NesType, cardinality: u32, seed: u64) -> Self {
+        let mut random = RandomStrategy::new(data_type, seed);
+        let values = (0..cardinality).map(|_| random.generate_field()).collect();
+        Self::new(values, seed)
+    }
+}
+
+impl FieldGeneratorStrategy for CategoricalStrategy {
+    fn generate_field(&mut self) -> String {
+        self.values
+            .choose(&mut self.rng)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Generates `factor * source + offset` plus normally distributed noise, where `source` is the
+/// value of the field at `source_index` of the same record. The source field must come before
+/// this field.
+pub struct CorrelatedStrategy {
+    data_type: NesType,
+    source_index: usize,
+    factor: f64,
+    offset: f64,
+    noise: f64,
+    rng: StdRng,
+}
+
+impl CorrelatedStrategy {
+    pub fn new(
+        data_type: NesType,
+        source_index: usize,
+        factor: f64,
+        offset: f64,
+        noise: f64,
+        seed: u64,
+    ) -> Self {
+        let rng = StdRng::seed_from_u64(seed);
+        Self {
+            data_type,
+            source_index,
+            factor,
+            offset,
+            noise,
+            rng,
+        }
+    }
+}
+
+impl FieldGeneratorStrategy for CorrelatedStrategy {
+    fn generate_field(&mut self) -> String {
+        self.generate_field_in_record(&[])
+    }
+
+    fn generate_field_in_record(&mut self, record: &[String]) -> String {
+        let source = record
+            .get(self.source_index)
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or_default();
+        let noise = self.noise * standard_normal(&mut self.rng);
+        format_number(self.factor * source + self.offset + noise, self.data_type)
+    }
+}
+
+/// Box-Muller transform of two uniform samples.
+fn standard_normal(rng: &mut impl Rng) -> f64 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// Formats `value` as a value of `data_type`. Integers are rounded and saturate at the bounds of
+/// their type.
+fn format_number(value: f64, data_type: NesType) -> String {
+    match data_type {
+        NesType::Bool => (value >= 0.5).to_string(),
+        NesType::Int(int_type) => {
+            let (bits, signed) = int_bits(int_type);
+            let (min, max) = if signed {
+                (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+            } else {
+                (0, (1i128 << bits) - 1)
+            };
+            // `as` saturates, NaN becomes 0
+            (value.round() as i128).clamp(min, max).to_string()
+        }
+        NesType::Float(FloatType::Bit32) => (value as f32).to_string(),
+        NesType::Float(FloatType::Bit64) | NesType::Undefined | NesType::Char => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod data_generator_tests {
+    use super::*;
+
+    /// Delays of the disordered time stamps relative to the undisturbed time stamps.
+    fn delays(disorder: TimeStampDisorder) -> Vec<u64> {
+        let mut time_stamps = TimeStampStrategy::new(10_000, 3);
+        let mut disordered =
+            DisorderedTimeStampStrategy::new(TimeStampStrategy::new(10_000, 3), disorder, 4);
+        (0..1000)
+            .map(|_| {
+                let time_stamp = time_stamps.next_time_stamp();
+                let disordered = disordered.generate_field().parse::<u64>().unwrap();
+                time_stamp - disordered
+            })
+            .collect()
+    }
+
+    #[test]
+    fn jitter_stays_within_max_jitter() {
+        let delays = delays(TimeStampDisorder {
+            max_jitter: 20,
+            ..Default::default()
+        });
+        assert!(delays.iter().all(|&delay| delay <= 20));
+        assert!(delays.iter().any(|&delay| delay > 0));
+    }
+
+    #[test]
+    fn large_gaps_exceed_u32_without_overflow() {
+        let disorder = TimeStampDisorder {
+            gap_ratio: 1.0,
+            max_gap: MAX_TIME_STAMP_GAP,
+            ..Default::default()
+        };
+        let mut disordered =
+            DisorderedTimeStampStrategy::new(TimeStampStrategy::new(u32::MAX, 3), disorder, 4);
+        let time_stamps = (0..1000)
+            .map(|_| disordered.generate_field().parse::<u64>().unwrap())
+            .collect::<Vec<_>>();
+        assert!(time_stamps.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(time_stamps[0] > u32::MAX.into());
+    }
+
+    #[test]
+    fn late_records_exceed_allowed_lateness() {
+        let delays = delays(TimeStampDisorder {
+            max_jitter: 10,
+            late_ratio: 0.2,
+            max_late_delay: 100,
+            allowed_lateness: 50,
+            ..Default::default()
+        });
+        let late = delays
+            .iter()
+            .filter(|&&delay| delay > 10)
+            .collect::<Vec<_>>();
+        assert!(!late.is_empty());
+        assert!(late.iter().all(|&&delay| delay > 50 && delay <= 150));
+    }
+
+    #[test]
+    fn zipf_prefers_small_values() {
+        let mut zipf = ZipfStrategy::new(10, 1.5, 5).unwrap();
+        let mut counts = [0; 10];
+        for _ in 0..1000 {
+            counts[zipf.generate_field().parse::<usize>().unwrap()] += 1;
+        }
+        assert!(counts.windows(2).take(2).all(|pair| pair[0] > pair[1]));
+        assert!(counts[0] > counts[9] * 5);
+    }
+
+    #[test]
+    fn normal_values_are_clamped() {
+        let mut normal = NormalStrategy::new(NesType::f64(), 0.0, 100.0, -5.0, 5.0, 6);
+        let values = (0..1000)
+            .map(|_| normal.generate_field().parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        assert!(values.iter().all(|value| (-5.0..=5.0).contains(value)));
+        assert!(values.contains(&-5.0) && values.contains(&5.0));
+    }
+
+    #[test]
+    fn sequential_values_cycle() {
+        let mut sequential = SequentialStrategy::new(NesType::i32(), 10.0, -2.0, 3);
+        let values = (0..7)
+            .map(|_| sequential.generate_field())
+            .collect::<Vec<_>>();
+        assert_eq!(values, ["10", "8", "6", "10", "8", "6", "10"]);
+    }
+
+    #[test]
+    fn correlated_values_follow_their_source() {
+        let mut correlated = CorrelatedStrategy::new(NesType::i64(), 1, 3.0, 1.0, 0.0, 7);
+        let record = ["ignored".to_string(), "4".to_string()];
+        assert_eq!(correlated.generate_field_in_record(&record), "13");
+
+        let mut noisy = CorrelatedStrategy::new(NesType::f64(), 0, 1.0, 0.0, 0.5, 7);
+        let record = ["100".to_string()];
+        let values = (0..1000)
+            .map(|_| {
+                noisy
+                    .generate_field_in_record(&record)
+                    .parse::<f64>()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(values.iter().any(|&value| value != 100.0));
+        assert!(values.iter().all(|value| (95.0..=105.0).contains(value)));
+    }
+}
@@ -1,5 +1,6 @@
 pub mod data_generator;
 pub mod physical_source;
+pub mod source_schema;
 pub mod logical_source;
 pub mod stream_gen;
 pub mod stream_gen_builder;
@@ -0,0 +1,876 @@
+use nes_types::NesType;
+use yaml_rust2::{yaml::Hash, Yaml};
+
+use crate::{
+    config::TestConfig,
+    reference::value::{int_bits, nes_type_string, Value},
+    seed::{derive_seed, physical_source_seed},
+};
+use nes_rust_client::expression::Field;
+
+use super::{
+    data_generator::{
+        CategoricalStrategy, CorrelatedStrategy, DisorderedTimeStampStrategy, EdgeValueStrategy,
+        FieldGenerator, FieldGeneratorStrategy, IncStrategy, KeyStrategy, NormalStrategy,
+        RecordGenerator, SequentialStrategy, TimeStampStrategy, ZipfStrategy,
+    },
+    yaml::nes_type::YamlNesType,
+    LogicalSource, PhysicalSource, SourceBundle,
+};
+
+/// Declarative description of the generator of a field, e.g. `Random` or
+/// `{kind: Zipf, n: 100, exponent: 1.2}` in yaml. Parameters that are omitted keep their default.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldStrategySpec {
+    /// Uniform values of the whole range of the type with edge values, see `EdgeValueStrategy`.
+    /// Without a ratio the `edge_value_ratio` of the `TestConfig` is used.
+    Random {
+        edge_value_ratio: Option<f64>,
+    },
+    Increment {
+        start: u32,
+    },
+    /// Bursty time stamps with the `time_stamp_disorder` of the `TestConfig`.
+    TimeStamp {
+        start: u32,
+    },
+    /// Uniform keys in `min..max`.
+    Key {
+        min: u32,
+        max: u32,
+    },
+    /// Skewed keys in `0..n`.
+    Zipf {
+        n: u32,
+        exponent: f64,
+    },
+    Normal {
+        mean: f64,
+        std_dev: f64,
+        min: f64,
+        max: f64,
+    },
+    /// `start`, `start + step`, ... restarting after `cycle` values, never if `cycle` is 0.
+    Sequential {
+        start: f64,
+        step: f64,
+        cycle: u64,
+    },
+    /// Uniform choice from `values`. Without values `cardinality` random values are chosen.
+    Categorical {
+        values: Vec<String>,
+        cardinality: u32,
+    },
+    /// `factor * field + offset` plus normally distributed noise with standard deviation `noise`,
+    /// where `field` is a field before this field.
+    Correlated {
+        field: String,
+        factor: f64,
+        offset: f64,
+        noise: f64,
+    },
+}
+
+impl Default for FieldStrategySpec {
+    fn default() -> Self {
+        FieldStrategySpec::Random {
+            edge_value_ratio: None,
+        }
+    }
+}
+
+impl FieldStrategySpec {
+    fn kind(&self) -> &'static str {
+        match self {
+            FieldStrategySpec::Random { .. } => "Random",
+            FieldStrategySpec::Increment { .. } => "Increment",
+            FieldStrategySpec::TimeStamp { .. } => "TimeStamp",
+            FieldStrategySpec::Key { .. } => "Key",
+            FieldStrategySpec::Zipf { .. } => "Zipf",
+            FieldStrategySpec::Normal { .. } => "Normal",
+            FieldStrategySpec::Sequential { .. } => "Sequential",
+            FieldStrategySpec::Categorical { .. } => "Categorical",
+            FieldStrategySpec::Correlated { .. } => "Correlated",
+        }
+    }
+
+    fn with_defaults(kind: &str) -> Result<Self, String> {
+        let spec = match kind {
+            "Random" => FieldStrategySpec::default(),
+            "Increment" => FieldStrategySpec::Increment { start: 0 },
+            "TimeStamp" => FieldStrategySpec::TimeStamp { start: 100 },
+            "Key" => FieldStrategySpec::Key { min: 0, max: 21 },
+            "Zipf" => FieldStrategySpec::Zipf {
+                n: 100,
+                exponent: 1.0,
+            },
+            "Normal" => FieldStrategySpec::Normal {
+                mean: 0.0,
+                std_dev: 1.0,
+                min: f64::MIN,
+                max: f64::MAX,
+            },
+            "Sequential" => FieldStrategySpec::Sequential {
+                start: 0.0,
+                step: 1.0,
+                cycle: 0,
+            },
+            "Categorical" => FieldStrategySpec::Categorical {
+                values: Vec::new(),
+                cardinality: 5,
+            },
+            "Correlated" => FieldStrategySpec::Correlated {
+                field: String::new(),
+                factor: 1.0,
+                offset: 0.0,
+                noise: 0.0,
+            },
+            err => return Err(format!("Unknown field generator {err}.")),
+        };
+        Ok(spec)
+    }
+
+    /// Creates the generator of a field of `data_type`. `fields` are the fields before the field
+    /// in the record. `seed` is the seed of the field in its physical source and `shared_seed` the
+    /// seed of the field in the first physical source of the logical source.
+    pub fn build(
+        &self,
+        data_type: NesType,
+        fields: &[FieldSchema],
+        seed: u64,
+        shared_seed: u64,
+        config: &TestConfig,
+    ) -> Result<Box<dyn FieldGeneratorStrategy>, String> {
+        let strategy: Box<dyn FieldGeneratorStrategy> = match self {
+            FieldStrategySpec::Random { edge_value_ratio } => Box::new(EdgeValueStrategy::new(
+                data_type,
+                edge_value_ratio.unwrap_or(config.edge_value_ratio),
+                seed,
+            )),
+            FieldStrategySpec::Increment { start } => {
+                check_fits("Increment", data_type, *start)?;
+                Box::new(IncStrategy::new(*start))
+            }
+            FieldStrategySpec::TimeStamp { start } => {
+                check_fits("TimeStamp", data_type, *start)?;
+                let disorder = &config.time_stamp_disorder;
+                // physical sources with shared time stamps duplicate the first one, including its
+                // disorder
+                let time_stamp_seed = if disorder.shared_time_stamps {
+                    shared_seed
+                } else {
+                    seed
+                };
+                Box::new(DisorderedTimeStampStrategy::new(
+                    TimeStampStrategy::new(*start, time_stamp_seed),
+                    disorder.clone(),
+                    derive_seed(time_stamp_seed, 1),
+                ))
+            }
+            FieldStrategySpec::Key { min, max } => {
+                if min >= max {
+                    return Err(format!("Key generator has an empty range {min}..{max}."));
+                }
+                check_fits("Key", data_type, *max - 1)?;
+                Box::new(KeyStrategy::new(*min..*max, seed))
+            }
+            FieldStrategySpec::Zipf { n, exponent } => {
+                check_fits("Zipf", data_type, n.saturating_sub(1))?;
+                Box::new(ZipfStrategy::new(*n, *exponent, seed)?)
+            }
+            FieldStrategySpec::Normal {
+                mean,
+                std_dev,
+                min,
+                max,
+            } => {
+                if min > max {
+                    return Err(format!(
+                        "Normal generator has a minimum {min} above its maximum {max}."
+                    ));
+                }
+                check_numeric("Normal", data_type)?;
+                Box::new(NormalStrategy::new(
+                    data_type, *mean, *std_dev, *min, *max, seed,
+                ))
+            }
+            FieldStrategySpec::Sequential { start, step, cycle } => {
+                check_numeric("Sequential", data_type)?;
+                Box::new(SequentialStrategy::new(data_type, *start, *step, *cycle))
+            }
+            FieldStrategySpec::Categorical {
+                values,
+                cardinality,
+            } => {
+                if values.is_empty() && *cardinality == 0 {
+                    return Err(
+                        "Categorical generator needs values or a cardinality above 0.".into(),
+                    );
+                } else if values.is_empty() {
+                    Box::new(CategoricalStrategy::random(data_type, *cardinality, seed))
+                } else {
+                    for value in values {
+                        check_category(data_type, value)?;
+                    }
+                    Box::new(CategoricalStrategy::new(values.clone(), seed))
+                }
+            }
+            FieldStrategySpec::Correlated {
+                field,
+                factor,
+                offset,
+                noise,
+            } => {
+                let Some(source_index) = fields.iter().position(|other| other.name == *field)
+                else {
+                    return Err(format!(
+                        "Correlated field {field} must be declared before the fields that depend on it."
+                    ));
+                };
+                check_numeric("Correlated", data_type)?;
+                let source_type = fields[source_index].data_type;
+                if !matches!(source_type, NesType::Int(_) | NesType::Float(_)) {
+                    return Err(format!(
+                        "Correlated field {field} must be numeric, not {}.",
+                        nes_type_string(source_type)
+                    ));
+                }
+                Box::new(CorrelatedStrategy::new(
+                    data_type,
+                    source_index,
+                    *factor,
+                    *offset,
+                    *noise,
+                    seed,
+                ))
+            }
+        };
+        Ok(strategy)
+    }
+}
+
+/// Checks that the values `0..=max` of a `kind` generator are values of `data_type`.
+fn check_fits(kind: &str, data_type: NesType, max: u32) -> Result<(), String> {
+    match data_type {
+        NesType::Int(int_type) => {
+            let (bits, signed) = int_bits(int_type);
+            let type_max = if signed {
+                (1u64 << (bits - 1)) - 1
+            } else {
+                u64::MAX >> (64 - bits)
+            };
+            if u64::from(max) > type_max {
+                return Err(format!(
+                    "{kind} generator produces values up to {max}, which exceed {}.",
+                    nes_type_string(data_type)
+                ));
+            }
+            Ok(())
+        }
+        _ => check_numeric(kind, data_type),
+    }
+}
+
+fn check_numeric(kind: &str, data_type: NesType) -> Result<(), String> {
+    match data_type {
+        NesType::Int(_) | NesType::Float(_) => Ok(()),
+        NesType::Undefined | NesType::Bool | NesType::Char => Err(format!(
+            "{kind} generator requires a numeric field, not {}.",
+            nes_type_string(data_type)
+        )),
+    }
+}
+
+/// Checks that the category `raw` is a value of `data_type`. Unlike `Value::parse` integers out of
+/// the range of the type and chars longer than one character are rejected.
+fn check_category(data_type: NesType, raw: &str) -> Result<(), String> {
+    let invalid = |reason: String| {
+        Err(format!(
+            "Categorical value {raw:?} is not a {}: {reason}",
+            nes_type_string(data_type)
+        ))
+    };
+    let value = match Value::parse(raw, data_type) {
+        Ok(value) => value,
+        Err(err) => return invalid(err),
+    };
+    match (data_type, value) {
+        (NesType::Int(_), Value::Int(int)) if raw.trim().parse::<i128>() != Ok(int) => {
+            invalid("The value is out of range.".into())
+        }
+        (NesType::Char, _) if raw.trim().chars().count() != 1 => {
+            invalid("Expected a single character.".into())
+        }
+        _ => Ok(()),
+    }
+}
+
+impl TryFrom<&Yaml> for FieldStrategySpec {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        let hash = match value {
+            Yaml::String(kind) => return FieldStrategySpec::with_defaults(kind),
+            Yaml::Hash(hash) => hash,
+            _ => {
+                return Err("Failed to parse field generator: Expected a string or mapping.".into())
+            }
+        };
+        let Some(kind) = value["kind"].as_str() else {
+            return Err("Failed to parse field generator: kind is missing.".into());
+        };
+        let mut spec = FieldStrategySpec::with_defaults(kind)?;
+        for (key, param) in hash {
+            let Some(key) = key.as_str() else {
+                return Err(format!(
+                    "Failed to parse {kind} generator: Invalid key {key:?}."
+                ));
+            };
+            if key == "kind" {
+                continue;
+            }
+            let is_known = match &mut spec {
+                FieldStrategySpec::Random { edge_value_ratio } => match key {
+                    "edge_value_ratio" => set(edge_value_ratio, Some(as_ratio(key, param)?)),
+                    _ => false,
+                },
+                FieldStrategySpec::Increment { start } | FieldStrategySpec::TimeStamp { start } => {
+                    match key {
+                        "start" => set(start, as_u32(key, param)?),
+                        _ => false,
+                    }
+                }
+                FieldStrategySpec::Key { min, max } => match key {
+                    "min" => set(min, as_u32(key, param)?),
+                    "max" => set(max, as_u32(key, param)?),
+                    _ => false,
+                },
+                FieldStrategySpec::Zipf { n, exponent } => match key {
+                    "n" => set(n, as_u32(key, param)?),
+                    "exponent" => set(exponent, as_f64(key, param)?),
+                    _ => false,
+                },
+                FieldStrategySpec::Normal {
+                    mean,
+                    std_dev,
+                    min,
+                    max,
+                } => match key {
+                    "mean" => set(mean, as_f64(key, param)?),
+                    "std_dev" => set(std_dev, as_f64(key, param)?),
+                    "min" => set(min, as_f64(key, param)?),
+                    "max" => set(max, as_f64(key, param)?),
+                    _ => false,
+                },
+                FieldStrategySpec::Sequential { start, step, cycle } => match key {
+                    "start" => set(start, as_f64(key, param)?),
+                    "step" => set(step, as_f64(key, param)?),
+                    "cycle" => set(cycle, as_u32(key, param)?.into()),
+                    _ => false,
+                },
+                FieldStrategySpec::Categorical {
+                    values,
+                    cardinality,
+                } => match key {
+                    "values" => set(values, as_strings(key, param)?),
+                    "cardinality" => set(cardinality, as_u32(key, param)?),
+                    _ => false,
+                },
+                FieldStrategySpec::Correlated {
+                    field,
+                    factor,
+                    offset,
+                    noise,
+                } => match key {
+                    "field" => set(field, as_string(key, param)?),
+                    "factor" => set(factor, as_f64(key, param)?),
+                    "offset" => set(offset, as_f64(key, param)?),
+                    "noise" => set(noise, as_f64(key, param)?),
+                    _ => false,
+                },
+            };
+            if !is_known {
+                return Err(format!(
+                    "Failed to parse {kind} generator: Unknown key {key}."
+                ));
+            }
+        }
+        Ok(spec)
+    }
+}
+
+impl Into<Yaml> for &FieldStrategySpec {
+    fn into(self) -> Yaml {
+        let mut map = Hash::new();
+        map.insert(
+            Yaml::String("kind".into()),
+            Yaml::String(self.kind().into()),
+        );
+        let mut insert = |key: &str, value: Yaml| {
+            map.insert(Yaml::String(key.into()), value);
+        };
+        let real = |value: f64| Yaml::Real(value.to_string());
+        match self {
+            FieldStrategySpec::Random { edge_value_ratio } => {
+                if let Some(ratio) = edge_value_ratio {
+                    insert("edge_value_ratio", real(*ratio));
+                }
+            }
+            FieldStrategySpec::Increment { start } | FieldStrategySpec::TimeStamp { start } => {
+                insert("start", Yaml::Integer((*start).into()))
+            }
+            FieldStrategySpec::Key { min, max } => {
+                insert("min", Yaml::Integer((*min).into()));
+                insert("max", Yaml::Integer((*max).into()));
+            }
+            FieldStrategySpec::Zipf { n, exponent } => {
+                insert("n", Yaml::Integer((*n).into()));
+                insert("exponent", real(*exponent));
+            }
+            FieldStrategySpec::Normal {
+                mean,
+                std_dev,
+                min,
+                max,
+            } => {
+                insert("mean", real(*mean));
+                insert("std_dev", real(*std_dev));
+                insert("min", real(*min));
+                insert("max", real(*max));
+            }
+            FieldStrategySpec::Sequential { start, step, cycle } => {
+                insert("start", real(*start));
+                insert("step", real(*step));
+                insert("cycle", Yaml::Integer(*cycle as i64));
+            }
+            FieldStrategySpec::Categorical {
+                values,
+                cardinality,
+            } => {
+                let values = values.iter().cloned().map(Yaml::String).collect();
+                insert("values", Yaml::Array(values));
+                insert("cardinality", Yaml::Integer((*cardinality).into()));
+            }
+            FieldStrategySpec::Correlated {
+                field,
+                factor,
+                offset,
+                noise,
+            } => {
+                insert("field", Yaml::String(field.clone()));
+                insert("factor", real(*factor));
+                insert("offset", real(*offset));
+                insert("noise", real(*noise));
+            }
+        }
+        Yaml::Hash(map)
+    }
+}
+
+/// A field of a logical source and the generator of its values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    pub name: String,
+    pub data_type: NesType,
+    pub generator: FieldStrategySpec,
+}
+
+impl FieldSchema {
+    pub fn new(name: impl Into<String>, data_type: NesType, generator: FieldStrategySpec) -> Self {
+        Self {
+            name: name.into(),
+            data_type,
+            generator,
+        }
+    }
+}
+
+impl TryFrom<&Yaml> for FieldSchema {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        let Some(name) = value["name"].as_str() else {
+            return Err("Failed to parse field: name is missing.".into());
+        };
+        let data_type = YamlNesType::try_from(&value["type"])
+            .map_err(|err| format!("Failed to parse field {name}: {err}"))?;
+        let generator = match &value["generator"] {
+            Yaml::BadValue => FieldStrategySpec::default(),
+            generator => FieldStrategySpec::try_from(generator)
+                .map_err(|err| format!("Failed to parse field {name}: {err}"))?,
+        };
+        Ok(Self::new(name, data_type.into(), generator))
+    }
+}
+
+impl Into<Yaml> for &FieldSchema {
+    fn into(self) -> Yaml {
+        let mut map = Hash::new();
+        map.insert(Yaml::String("name".into()), Yaml::String(self.name.clone()));
+        map.insert(
+            Yaml::String("type".into()),
+            YamlNesType::from(self.data_type).into(),
+        );
+        map.insert(Yaml::String("generator".into()), (&self.generator).into());
+        Yaml::Hash(map)
+    }
+}
+
+/// A logical source with its fields and physical sources. Counts that are not given fall back to
+/// the `TestConfig`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceSchema {
+    pub name: String,
+    pub physical_source_count: Option<u32>,
+    pub record_count: Option<u32>,
+    pub fields: Vec<FieldSchema>,
+}
+
+impl SourceSchema {
+    /// Creates the logical source and its physical sources. Every field of a physical source
+    /// draws from its own seed, derived from `seed` and the position of the source.
+    pub fn source_bundle(
+        &self,
+        seed: u64,
+        source_id: u32,
+        config: &TestConfig,
+    ) -> Result<SourceBundle, String> {
+        let physical_source_count = self
+            .physical_source_count
+            .unwrap_or(config.physical_source_count);
+        let record_count = self.record_count.unwrap_or(config.record_count);
+        let phy_seeds = (0..physical_source_count)
+            .map(|phy_id| physical_source_seed(seed, source_id, phy_id))
+            .collect::<Vec<_>>();
+
+        let mut physical_sources = vec![];
+        for (id, phy_seed) in phy_seeds.iter().enumerate() {
+            let field_gens = self
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(index, field)| {
+                    let field_seed = derive_seed(*phy_seed, index as u64);
+                    let shared_seed = derive_seed(phy_seeds[0], index as u64);
+                    let strategy = field.generator.build(
+                        field.data_type,
+                        &self.fields[..index],
+                        field_seed,
+                        shared_seed,
+                        config,
+                    )?;
+                    Ok(FieldGenerator::with_boxed_strategy(
+                        &field.name,
+                        field.data_type,
+                        strategy,
+                    ))
+                })
+                .collect::<Result<Vec<_>, String>>()
+                .map_err(|err| format!("Invalid schema of {}: {err}", self.name))?;
+            physical_sources.push(PhysicalSource {
+                physical_source_name: format!("{}-{id}", self.name),
+                generator: RecordGenerator {
+                    field_generators: field_gens,
+                    record_count: record_count.into(),
+                },
+            });
+        }
+
+        let logical_source = LogicalSource {
+            source_name: self.name.clone(),
+            fields: self
+                .fields
+                .iter()
+                .map(|field| Field::typed(&field.name, field.data_type))
+                .collect(),
+        };
+        Ok(SourceBundle {
+            logical_source,
+            physical_sources,
+        })
+    }
+}
+
+impl TryFrom<&Yaml> for SourceSchema {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        let Yaml::Hash(hash) = value else {
+            return Err("Failed to parse source: Expected a mapping.".into());
+        };
+        let Some(name) = value["name"].as_str() else {
+            return Err("Failed to parse source: name is missing.".into());
+        };
+        let mut schema = SourceSchema {
+            name: name.to_string(),
+            physical_source_count: None,
+            record_count: None,
+            fields: Vec::new(),
+        };
+        for (key, param) in hash {
+            match key.as_str() {
+                Some("name") => (),
+                Some("physical_source_count") => {
+                    schema.physical_source_count = Some(as_u32("physical_source_count", param)?)
+                }
+                Some("record_count") => schema.record_count = Some(as_u32("record_count", param)?),
+                Some("fields") => {
+                    let Yaml::Array(fields) = param else {
+                        return Err(format!(
+                            "Failed to parse fields of {name}: Expected an array."
+                        ));
+                    };
+                    schema.fields = fields
+                        .iter()
+                        .map(FieldSchema::try_from)
+                        .collect::<Result<_, _>>()
+                        .map_err(|err| format!("Failed to parse source {name}: {err}"))?;
+                }
+                _ => {
+                    return Err(format!(
+                        "Failed to parse source {name}: Unknown key {key:?}."
+                    ))
+                }
+            }
+        }
+        if schema.fields.is_empty() {
+            return Err(format!(
+                "Failed to parse source {name}: fields are missing."
+            ));
+        }
+        Ok(schema)
+    }
+}
+
+impl Into<Yaml> for &SourceSchema {
+    fn into(self) -> Yaml {
+        let mut map = Hash::new();
+        map.insert(Yaml::String("name".into()), Yaml::String(self.name.clone()));
+        if let Some(count) = self.physical_source_count {
+            map.insert(
+                Yaml::String("physical_source_count".into()),
+                Yaml::Integer(count.into()),
+            );
+        }
+        if let Some(count) = self.record_count {
+            map.insert(
+                Yaml::String("record_count".into()),
+                Yaml::Integer(count.into()),
+            );
+        }
+        let fields = self.fields.iter().map(|field| field.into()).collect();
+        map.insert(Yaml::String("fields".into()), Yaml::Array(fields));
+        Yaml::Hash(map)
+    }
+}
+
+fn set<T>(target: &mut T, value: T) -> bool {
+    *target = value;
+    true
+}
+
+fn as_f64(name: &str, value: &Yaml) -> Result<f64, String> {
+    match value {
+        Yaml::Integer(int) => Ok(*int as f64),
+        Yaml::Real(_) => value
+            .as_f64()
+            .ok_or_else(|| format!("Failed to parse {name}: Invalid number.")),
+        _ => Err(format!("Failed to parse {name}: Expected a number.")),
+    }
+}
+
+/// Parses a probability between 0 and 1.
+fn as_ratio(name: &str, value: &Yaml) -> Result<f64, String> {
+    let ratio = as_f64(name, value)?;
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(format!(
+            "Failed to parse {name}: {ratio} is not between 0 and 1."
+        ));
+    }
+    Ok(ratio)
+}
+
+fn as_u32(name: &str, value: &Yaml) -> Result<u32, String> {
+    let Some(int) = value.as_i64() else {
+        return Err(format!("Failed to parse {name}: Expected an integer."));
+    };
+    u32::try_from(int).map_err(|_| format!("Failed to parse {name}: {int} is out of range."))
+}
+
+fn as_string(name: &str, value: &Yaml) -> Result<String, String> {
+    match value {
+        Yaml::String(string) => Ok(string.clone()),
+        Yaml::Integer(int) => Ok(int.to_string()),
+        Yaml::Real(real) => Ok(real.clone()),
+        Yaml::Boolean(bool) => Ok(bool.to_string()),
+        _ => Err(format!("Failed to parse {name}: Expected a value.")),
+    }
+}
+
+fn as_strings(name: &str, value: &Yaml) -> Result<Vec<String>, String> {
+    let Yaml::Array(values) = value else {
+        return Err(format!("Failed to parse {name}: Expected an array."));
+    };
+    values.iter().map(|value| as_string(name, value)).collect()
+}
+
+#[cfg(test)]
+mod source_schema_tests {
+    use yaml_rust2::{Yaml, YamlLoader};
+
+    use nes_types::NesType;
+
+    use crate::config::TestConfig;
+
+    use super::{FieldSchema, FieldStrategySpec, SourceSchema};
+
+    #[test]
+    fn field_generators_round_trip() {
+        let docs = YamlLoader::load_from_str(
+            r#"
+- {name: key, type: INT64, generator: {kind: Zipf, n: 20, exponent: 1.5}}
+- {name: price, type: FLOAT64, generator: {kind: Normal, mean: 10, std_dev: 2.5, min: 0}}
+- {name: status, type: INT8, generator: {kind: Categorical, values: [1, 2, 3]}}
+- {name: tax, type: FLOAT64, generator: {kind: Correlated, field: price, factor: 0.2}}
+- {name: f0, type: UINT32}
+"#,
+        )
+        .unwrap();
+        let fields = docs[0]
+            .as_vec()
+            .unwrap()
+            .iter()
+            .map(FieldSchema::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            fields[0].generator,
+            FieldStrategySpec::Zipf {
+                n: 20,
+                exponent: 1.5
+            }
+        );
+        assert_eq!(fields[4].generator, FieldStrategySpec::default());
+        for field in &fields {
+            let yaml: Yaml = field.into();
+            assert_eq!(FieldSchema::try_from(&yaml).as_ref(), Ok(field));
+        }
+        let unknown = YamlLoader::load_from_str("{kind: Zipf, skew: 2}").unwrap();
+        assert!(FieldStrategySpec::try_from(&unknown[0]).is_err());
+        let ratio = YamlLoader::load_from_str("{kind: Random, edge_value_ratio: 1.5}").unwrap();
+        assert!(FieldStrategySpec::try_from(&ratio[0]).is_err());
+    }
+
+    #[test]
+    fn source_schema() {
+        let docs = YamlLoader::load_from_str(
+            r#"
+name: orders
+record_count: 100
+fields:
+  - {name: ts, type: UINT64, generator: TimeStamp}
+  - {name: key, type: UINT32, generator: {kind: Zipf, n: 10}}
+"#,
+        )
+        .unwrap();
+        let schema = SourceSchema::try_from(&docs[0]).unwrap();
+        assert_eq!(schema.name, "orders");
+        assert_eq!(schema.record_count, Some(100));
+        assert_eq!(schema.physical_source_count, None);
+        assert_eq!(schema.fields.len(), 2);
+        let yaml: Yaml = (&schema).into();
+        assert_eq!(SourceSchema::try_from(&yaml), Ok(schema));
+
+        let unknown = YamlLoader::load_from_str("{name: orders, records: 1, fields: []}").unwrap();
+        assert!(SourceSchema::try_from(&unknown[0]).is_err());
+    }
+
+    #[test]
+    fn shared_time_stamps_share_their_disorder() {
+        let mut config = TestConfig::default();
+        config.time_stamp_disorder.max_jitter = 100;
+        config.time_stamp_disorder.shared_time_stamps = true;
+        let spec = FieldStrategySpec::TimeStamp { start: 1000 };
+        let time_stamps = |seed| {
+            let mut strategy = spec.build(NesType::u64(), &[], seed, 7, &config).unwrap();
+            (0..100)
+                .map(|_| strategy.generate_field())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(time_stamps(1), time_stamps(2));
+    }
+
+    #[test]
+    fn invalid_generators_are_rejected() {
+        let config = TestConfig::default();
+        let build = |spec: FieldStrategySpec, data_type, fields: &[FieldSchema]| {
+            spec.build(data_type, fields, 1, 2, &config).map(|_| ())
+        };
+        let key = |min, max| FieldStrategySpec::Key { min, max };
+        assert!(build(key(5, 5), NesType::u64(), &[]).is_err());
+        assert!(build(key(0, 200), NesType::i8(), &[]).is_err());
+        assert!(build(key(0, 128), NesType::i8(), &[]).is_ok());
+        assert!(build(key(0, 10), NesType::Char, &[]).is_err());
+        let zipf = FieldStrategySpec::Zipf {
+            n: 300,
+            exponent: 1.0,
+        };
+        assert!(build(zipf.clone(), NesType::i8(), &[]).is_err());
+        assert!(build(zipf, NesType::u64(), &[]).is_ok());
+        let normal = FieldStrategySpec::Normal {
+            mean: 0.0,
+            std_dev: 1.0,
+            min: 1.0,
+            max: -1.0,
+        };
+        assert!(build(normal, NesType::f64(), &[]).is_err());
+        let categorical = |values: &[&str], cardinality| FieldStrategySpec::Categorical {
+            values: values.iter().map(|value| value.to_string()).collect(),
+            cardinality,
+        };
+        assert!(build(categorical(&[], 0), NesType::u64(), &[]).is_err());
+        assert!(build(categorical(&["1", "-128"], 0), NesType::i8(), &[]).is_ok());
+        assert!(build(categorical(&["1", "128"], 0), NesType::i8(), &[]).is_err());
+        assert!(build(categorical(&["a", "b"], 0), NesType::Char, &[]).is_ok());
+        assert!(build(categorical(&["ab"], 0), NesType::Char, &[]).is_err());
+        assert!(build(categorical(&["a"], 0), NesType::u64(), &[]).is_err());
+
+        let increment = FieldStrategySpec::Increment { start: 300 };
+        assert!(build(increment.clone(), NesType::i8(), &[]).is_err());
+        assert!(build(increment, NesType::i16(), &[]).is_ok());
+        let time_stamp = FieldStrategySpec::TimeStamp { start: 100 };
+        assert!(build(time_stamp, NesType::Char, &[]).is_err());
+        let sequential = FieldStrategySpec::Sequential {
+            start: 0.0,
+            step: 1.0,
+            cycle: 0,
+        };
+        assert!(build(sequential, NesType::Char, &[]).is_err());
+        let normal = FieldStrategySpec::Normal {
+            mean: 0.0,
+            std_dev: 1.0,
+            min: -1.0,
+            max: 1.0,
+        };
+        assert!(build(normal, NesType::Char, &[]).is_err());
+
+        let flag = FieldSchema {
+            name: "flag".into(),
+            data_type: NesType::Bool,
+            generator: FieldStrategySpec::default(),
+        };
+        let correlated = FieldStrategySpec::Correlated {
+            field: "flag".into(),
+            factor: 1.0,
+            offset: 0.0,
+            noise: 0.0,
+        };
+        assert!(build(correlated.clone(), NesType::f64(), &[flag]).is_err());
+        let numeric = FieldSchema {
+            name: "flag".into(),
+            data_type: NesType::f64(),
+            generator: FieldStrategySpec::default(),
+        };
+        assert!(build(correlated, NesType::Char, &[numeric]).is_err());
+    }
+}
@@ -2,6 +2,7 @@ use nes_type::YamlNesType;
 use yaml_rust2::{Yaml, YamlEmitter};
 
 use crate::config::NetworkConfig;
+use crate::error::AchillesError;
 use crate::nes_opt_config::NesOptConfig;
 use crate::nes_query_comp_config::NesQueryCompilerConfig;
 
@@ -9,9 +10,7 @@ use super::logical_source::LogicalSource;
 use super::physical_source::PhysicalSource;
 use super::stream_gen_builder::StreamGenBuilder;
 use super::yaml::*;
-use std::io::Write;
 use std::{
-    error::Error,
     fs,
     path::{Path, PathBuf},
 };
@@ -45,6 +44,8 @@ impl ToString for NesLogLevel {
 // FIXME: Remove public access from fields
 pub struct StreamGen {
     pub path: PathBuf,
+    /// Directory of the coordinator and worker configs of every cluster, cluster 0 first.
+    pub cluster_paths: Vec<PathBuf>,
     pub override_files: bool,
     pub sources: Vec<SourceBundle>,
     pub worker_log_level: NesLogLevel,
@@ -59,32 +60,51 @@ impl StreamGen {
         StreamGenBuilder::new()
     }
 
-    // FIXME: Use Custom Error type here
-    pub fn generate(&mut self) -> Result<(), Box<dyn Error>> {
+    pub fn generate(&mut self) -> Result<(), AchillesError> {
         // if self.override_files && self.path.exists() {
         //     log::info!("Overriding existing files in path: {:?}", self.path);
         //     fs::remove_dir_all(&self.path)?;
         // }
 
-        let worker_dir_path: PathBuf = self.path.join("workers");
         let data_dir_path: PathBuf = self.path.join("data");
+        let worker_count = self
+            .sources
+            .iter()
+            .map(|source| source.physical_sources.len())
+            .sum();
+        self.network_config
+            .check_ports(worker_count)
+            .map_err(AchillesError::Config)?;
 
         // fs::create_dir(&self.path)?;
         // log::info!("Created dir {:?}", self.path);
-        self.generate_coordinator_config(&self.path);
-
-        fs::create_dir(&worker_dir_path)?;
-        log::info!("Created dir {:?}", worker_dir_path);
-        self.generate_worker_configs(&worker_dir_path);
+        // every cluster gets its own configs with its own ports, all clusters share the data
+        for (cluster_id, cluster_path) in (0..).zip(&self.cluster_paths) {
+            let network_config = self
+                .network_config
+                .cluster(cluster_id)
+                .map_err(AchillesError::Config)?;
+            create_dir(cluster_path, true)?;
+            self.generate_coordinator_config(cluster_path, &network_config)?;
+
+            let worker_dir_path = cluster_path.join("workers");
+            create_dir(&worker_dir_path, false)?;
+            log::info!("Created dir {:?}", worker_dir_path);
+            self.generate_worker_configs(&worker_dir_path, &network_config)?;
+        }
 
-        fs::create_dir(&data_dir_path)?;
+        create_dir(&data_dir_path, false)?;
         log::info!("Created dir {:?}", data_dir_path);
-        self.generate_data(&data_dir_path);
+        self.generate_data(&data_dir_path)?;
 
         Ok(())
     }
 
-    fn generate_coordinator_config(&self, path: &PathBuf) {
+    fn generate_coordinator_config(
+        &self,
+        path: &PathBuf,
+        network_config: &NetworkConfig,
+    ) -> Result<(), AchillesError> {
         let logical_source = self
             .sources
             .iter()
@@ -107,23 +127,23 @@ impl StreamGen {
         let coordinator_config = YamlCoordinatorConfig {
             logLevel: self.coordinator_log_level.to_string(),
             logicalSources: logical_source,
-            coordinatorIp: self.network_config.coord_ip,
-            restPort: self.network_config.coord_rest_port.into(),
-            rpcPort: self.network_config.coord_rpc_port.into(),
+            coordinatorIp: network_config.coord_ip,
+            restPort: network_config.coord_rest_port.into(),
+            rpcPort: network_config.coord_rpc_port.into(),
             opt_config: self.opt_config.clone(),
         };
         let yaml_obj: Yaml = (&coordinator_config).into();
-        let mut out_str = String::new();
-        let mut emitter = YamlEmitter::new(&mut out_str);
-        emitter.dump(&yaml_obj).unwrap();
-        let mut file = fs::File::create(path.join("coordinator.yml"))
-            .expect("coordinator.yml has to be created!");
-        write!(file, "{out_str}").unwrap();
+        let out_str = write_yaml_file(&path.join("coordinator.yml"), &yaml_obj)?;
         log::debug!("Created coordinator.yml:\n{}", out_str);
+        Ok(())
     }
 
     // FIXME: file_path should be canonicalized
-    fn generate_worker_configs(&self, worker_dir_path: &PathBuf) {
+    fn generate_worker_configs(
+        &self,
+        worker_dir_path: &PathBuf,
+        network_config: &NetworkConfig,
+    ) -> Result<(), AchillesError> {
         let mut worker_id = 0;
         for source in self.sources.iter() {
             for phy_source in source.physical_sources.iter() {
@@ -133,6 +153,7 @@ impl StreamGen {
                 ));
                 let file_path = file_path.to_string_lossy().to_string();
                 log::info!("Path: {:?}", file_path);
+                let (rpc_port, data_port) = network_config.worker_ports(worker_id as u16);
                 let worker_config = YamlWorkerConfig {
                     logLevel: self.worker_log_level.to_string(),
                     physicalSources: vec![YamlPhysicalSource {
@@ -145,41 +166,64 @@ impl StreamGen {
                         },
                     }],
                     workerId: worker_id,
+                    coordinatorHost: network_config.coord_ip,
+                    coordinatorPort: network_config.coord_rpc_port.into(),
+                    rpcPort: rpc_port.into(),
+                    dataPort: data_port.into(),
                     query_comp_config: self.query_comp_config.clone(),
                     ..Default::default()
                 };
 
                 let yaml_obj: Yaml = (&worker_config).into();
-                let mut out_str = String::new();
-                let mut emitter = YamlEmitter::new(&mut out_str);
-                emitter.dump(&yaml_obj).unwrap();
-                let mut file =
-                    fs::File::create(worker_dir_path.join(format!("worker-{worker_id}.yml")))
-                        .expect("Worker files have to be created!");
-                write!(file, "{out_str}").unwrap();
+                let worker_path = worker_dir_path.join(format!("worker-{worker_id}.yml"));
+                let out_str = write_yaml_file(&worker_path, &yaml_obj)?;
                 log::debug!("Created worker-{worker_id}.yml:\n{out_str}");
                 worker_id += 1;
             }
         }
+        Ok(())
     }
 
-    fn generate_data(&mut self, data_dir_path: &Path) {
+    fn generate_data(&mut self, data_dir_path: &Path) -> Result<(), AchillesError> {
         for source in self.sources.iter_mut() {
             for phy_source in source.physical_sources.iter_mut() {
                 let file_name = format!("{}.csv", phy_source.physical_source_name);
                 let file_path = data_dir_path.join(&file_name);
+                let csv_err = |err: csv::Error| AchillesError::io(&file_path, err.into());
                 let mut wtr = csv::WriterBuilder::new()
                     .delimiter(b',')
-                    .from_path(file_path)
-                    .expect("Creating CSV Writer Should not fail!");
+                    .from_path(&file_path)
+                    .map_err(csv_err)?;
                 let header = phy_source.generator.generate_header();
-                wtr.write_record(header);
+                wtr.write_record(header).map_err(csv_err)?;
                 for _ in 0..phy_source.generator.record_count {
                     let record = phy_source.generator.generate_record();
-                    wtr.write_record(record);
+                    wtr.write_record(record).map_err(csv_err)?;
                 }
+                wtr.flush()
+                    .map_err(|err| AchillesError::io(&file_path, err))?;
                 log::debug!("Created {file_name}.");
             }
         }
+        Ok(())
     }
 }
+
+fn create_dir(path: &Path, allow_existing: bool) -> Result<(), AchillesError> {
+    let result = match allow_existing {
+        true => fs::create_dir_all(path),
+        false => fs::create_dir(path),
+    };
+    result.map_err(|err| AchillesError::io(path, err))
+}
+
+/// Writes `yaml` to `path` and returns the written string.
+fn write_yaml_file(path: &Path, yaml: &Yaml) -> Result<String, AchillesError> {
+    let mut out_str = String::new();
+    let mut emitter = YamlEmitter::new(&mut out_str);
+    emitter
+        .dump(yaml)
+        .map_err(|err| AchillesError::yaml(path, err))?;
+    fs::write(path, &out_str).map_err(|err| AchillesError::io(path, err))?;
+    Ok(out_str)
+}
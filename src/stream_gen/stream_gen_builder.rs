@@ -1,10 +1,13 @@
 use std::path::PathBuf;
 
 use crate::{
-    config::NetworkConfig, nes_opt_config::NesOptConfig, nes_query_comp_config::NesQueryCompilerConfig
+    config::{NetworkConfig, TestConfig},
+    nes_opt_config::NesOptConfig,
+    nes_query_comp_config::NesQueryCompilerConfig,
 };
 
 use super::{
+    source_schema::SourceSchema,
     stream_gen::{NesLogLevel, StreamGen},
     LogicalSource, PhysicalSource, SourceBundle,
 };
@@ -12,6 +15,7 @@ use super::{
 #[derive(Default)]
 pub struct StreamGenBuilder {
     path: Option<PathBuf>,
+    cluster_paths: Vec<PathBuf>,
     sources: Vec<SourceBundle>,
     worker_log_level: NesLogLevel,
     coordinator_log_level: NesLogLevel,
@@ -33,10 +37,15 @@ impl StreamGenBuilder {
     // FIXME: Add an Error type and don't just panic and validate sources
     pub fn build(self) -> StreamGen {
         let path = self.path.expect("Path should exist!");
+        let cluster_paths = match self.cluster_paths.is_empty() {
+            true => vec![path.clone()],
+            false => self.cluster_paths,
+        };
         let sources = self.sources;
         let override_files = true;
         StreamGen {
             path,
+            cluster_paths,
             sources,
             override_files,
             worker_log_level: self.worker_log_level,
@@ -67,6 +76,13 @@ impl StreamGenBuilder {
         self
     }
 
+    /// Directories of the configs of every cluster, cluster 0 first. Without cluster paths a single
+    /// cluster is generated in the path of the stream gen.
+    pub fn cluster_paths(mut self, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.cluster_paths = paths.into_iter().collect();
+        self
+    }
+
     pub fn worker_log_level(mut self, log_level: NesLogLevel) -> Self {
         self.worker_log_level = log_level;
         self
@@ -92,6 +108,21 @@ impl StreamGenBuilder {
         self
     }
 
+    /// Adds a source for every schema. The data of source `i` is drawn from seeds derived from
+    /// `seed` and `i`.
+    pub fn add_source_schemas(
+        mut self,
+        schemas: &[SourceSchema],
+        seed: u64,
+        config: &TestConfig,
+    ) -> Result<Self, String> {
+        for (source_id, schema) in schemas.iter().enumerate() {
+            let source_bundle = schema.source_bundle(seed, source_id as u32, config)?;
+            self.sources.push(source_bundle);
+        }
+        Ok(self)
+    }
+
     pub fn add_logical_source(self, logical_source: LogicalSource) -> LogicalSourceBuilder {
         LogicalSourceBuilder {
             stream_gen_builder: self,
@@ -1,3 +1,5 @@
+use std::net::Ipv4Addr;
+
 use yaml_rust2::{yaml::Hash, Yaml};
 
 use crate::nes_query_comp_config::NesQueryCompilerConfig;
@@ -21,6 +23,10 @@ pub struct YamlWorkerConfig {
     pub logLevel: String,
     pub physicalSources: Vec<YamlPhysicalSource>,
     pub workerId: i32,
+    pub coordinatorHost: Ipv4Addr,
+    pub coordinatorPort: u32,
+    pub rpcPort: u32,
+    pub dataPort: u32,
     pub query_comp_config: NesQueryCompilerConfig,
 }
 
@@ -30,6 +36,10 @@ impl Default for YamlWorkerConfig {
             logLevel: "LOG_ERROR".to_string(),
             physicalSources: Vec::new(),
             workerId: 0,
+            coordinatorHost: Ipv4Addr::LOCALHOST,
+            coordinatorPort: 4000,
+            rpcPort: 3000,
+            dataPort: 3001,
             query_comp_config: NesQueryCompilerConfig::default(),
         }
     }
@@ -55,6 +65,22 @@ impl Into<Yaml> for &YamlWorkerConfig {
             Yaml::String("workerId".to_string()),
             Yaml::Integer(self.workerId.into()),
         );
+        config_map.insert(
+            Yaml::String("coordinatorHost".to_string()),
+            Yaml::String(self.coordinatorHost.to_string()),
+        );
+        config_map.insert(
+            Yaml::String("coordinatorPort".to_string()),
+            Yaml::Integer(self.coordinatorPort.into()),
+        );
+        config_map.insert(
+            Yaml::String("rpcPort".to_string()),
+            Yaml::Integer(self.rpcPort.into()),
+        );
+        config_map.insert(
+            Yaml::String("dataPort".to_string()),
+            Yaml::Integer(self.dataPort.into()),
+        );
 
         // query Compiler
         config_map.insert(
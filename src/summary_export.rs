@@ -0,0 +1,452 @@
+//! Machine-readable exports of the summary for dashboards and CI: the `SummaryStats` of every test
+//! run and the total as JSON and CSV, and the results of every test case as JUnit XML.
+use std::{fmt::Write, fs, path::Path};
+
+use nes_rust_client::query::stringify::stringify_query;
+
+use crate::{
+    eval::{
+        check_results::{TestCaseResult, TestSetResult},
+        eval_error::EvalError,
+        evaluator::ResultRelation,
+    },
+    summery::{SummaryStats, SummaryStatsEntry},
+    test_case_exec::{TestCaseExec, TestCaseExecStatus, TestSetExec},
+    test_case_gen::query_id::TestCaseId,
+    AchillesError,
+};
+
+/// Writes `summary.json`, `summary.csv` and `junit.xml` to `dir`.
+pub(crate) fn export_summary(
+    dir: &Path,
+    runs: &[(u32, SummaryStats)],
+    total: &SummaryStats,
+    junit: &JUnitReport,
+) -> Result<(), AchillesError> {
+    fs::create_dir_all(dir).map_err(|err| AchillesError::io(dir, err))?;
+    let json_path = dir.join("summary.json");
+    fs::write(&json_path, stats_to_json(runs, total))
+        .map_err(|err| AchillesError::io(&json_path, err))?;
+    write_stats_csv(&dir.join("summary.csv"), runs, total)?;
+    let junit_path = dir.join("junit.xml");
+    fs::write(&junit_path, junit.to_xml()).map_err(|err| AchillesError::io(&junit_path, err))?;
+    Ok(())
+}
+
+// json
+
+/// `{"runs": [{"run": 0, "oracles": {..}, "all": {..}}, ..], "total": {"oracles": {..}, "all": {..}}}`
+pub(crate) fn stats_to_json(runs: &[(u32, SummaryStats)], total: &SummaryStats) -> String {
+    let runs = runs
+        .iter()
+        .map(|(run_id, stats)| format!("{{\"run\": {run_id}, {}}}", stats_json_fields(stats)))
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"runs\": [{}], \"total\": {{{}}}}}\n",
+        runs.join(", "),
+        stats_json_fields(total)
+    )
+}
+
+fn stats_json_fields(stats: &SummaryStats) -> String {
+    let oracles = stats
+        .sorted_entries()
+        .into_iter()
+        .map(|(strategy, entry)| {
+            format!(
+                "{}: {}",
+                json_string(&format!("{strategy:?}")),
+                entry_to_json(entry)
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "\"oracles\": {{{}}}, \"all\": {}",
+        oracles.join(", "),
+        entry_to_json(&stats.totals())
+    )
+}
+
+fn entry_to_json(entry: &SummaryStatsEntry) -> String {
+    let fields = entry_fields(entry)
+        .into_iter()
+        .map(|(name, value)| format!("\"{name}\": {}", value.unwrap_or_else(|| "null".into())))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", fields.join(", "))
+}
+
+fn json_string(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len() + 2);
+    escaped.push('"');
+    for char in str.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            char if char.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", char as u32);
+            }
+            char => escaped.push(char),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// csv
+
+/// One row per oracle and an `All` row for every test run and for the total, whose `run` is
+/// `total`.
+fn write_stats_csv(
+    path: &Path,
+    runs: &[(u32, SummaryStats)],
+    total: &SummaryStats,
+) -> Result<(), AchillesError> {
+    let mut wtr = csv::Writer::from_path(path).map_err(EvalError::from)?;
+    let header = ["run", "oracle"]
+        .into_iter()
+        .chain(entry_fields(&SummaryStatsEntry::default()).map(|(name, _)| name));
+    wtr.write_record(header).map_err(EvalError::from)?;
+    let tables = runs
+        .iter()
+        .map(|(run_id, stats)| (run_id.to_string(), stats))
+        .chain(std::iter::once(("total".to_string(), total)));
+    for (run, stats) in tables {
+        let totals = stats.totals();
+        let rows = stats
+            .sorted_entries()
+            .into_iter()
+            .map(|(strategy, entry)| (format!("{strategy:?}"), entry))
+            .chain(std::iter::once(("All".to_string(), &totals)));
+        for (oracle, entry) in rows {
+            let values = entry_fields(entry).map(|(_, value)| value.unwrap_or_default());
+            let record = [run.clone(), oracle].into_iter().chain(values);
+            wtr.write_record(record).map_err(EvalError::from)?;
+        }
+    }
+    wtr.flush().map_err(|err| AchillesError::io(path, err))?;
+    Ok(())
+}
+
+/// Names and values of the columns of an entry. Rates of empty entries are `None`.
+fn entry_fields(entry: &SummaryStatsEntry) -> [(&'static str, Option<String>); 14] {
+    let rates = entry.success_rates();
+    let rate = |rate: f32| rate.is_finite().then(|| rate.to_string());
+    [
+        ("total", Some(entry.total_count.to_string())),
+        ("success", Some(entry.success_count.to_string())),
+        ("exec_success_rate", rate(rates.exec_success_rate)),
+        ("fail", Some(entry.fail_count.to_string())),
+        ("timeout", Some(entry.timeout_count.to_string())),
+        ("skipped", Some(entry.skipped_count.to_string())),
+        ("startup_fail", Some(entry.startup_fail_count.to_string())),
+        ("total_res", Some(entry.total_res_count.to_string())),
+        ("eval_success_rate", rate(rates.eval_success_rate)),
+        ("equal", Some(entry.equal_count.to_string())),
+        ("reordered", Some(entry.reorder_count.to_string())),
+        ("diff", Some(entry.diff_count.to_string())),
+        ("ref_diff", Some(entry.ref_diff_count.to_string())),
+        (
+            "ref_unsupported",
+            Some(entry.ref_unsupported_count.to_string()),
+        ),
+    ]
+}
+
+// junit
+
+/// JUnit report in which every `TestSetResult` is a testsuite and every test case a testcase.
+#[derive(Debug, Default)]
+pub struct JUnitReport {
+    suites: Vec<JUnitSuite>,
+}
+
+#[derive(Debug)]
+struct JUnitSuite {
+    name: String,
+    cases: Vec<JUnitCase>,
+}
+
+#[derive(Debug)]
+struct JUnitCase {
+    class_name: String,
+    name: String,
+    outcome: JUnitOutcome,
+}
+
+#[derive(Debug, PartialEq)]
+enum JUnitOutcome {
+    Passed,
+    Failure {
+        kind: &'static str,
+        message: String,
+        details: String,
+    },
+    Error {
+        kind: &'static str,
+        message: String,
+    },
+    Skipped,
+}
+
+impl JUnitReport {
+    /// Adds a testsuite per test set result. The execution status of the test cases is taken from
+    /// the `test_set_execs`, test cases that were not executed successfully have no result.
+    pub fn add_run(
+        &mut self,
+        run_id: u32,
+        test_set_execs: &[TestSetExec],
+        test_set_results: &[TestSetResult],
+    ) {
+        for test_set_result in test_set_results {
+            let class_name = format!("{:?}", test_set_result.strategy);
+            let test_set_exec = test_set_execs
+                .iter()
+                .find(|test_set| test_set.id == test_set_result.id);
+            let cases = match test_set_exec {
+                Some(test_set_exec) => std::iter::once(&test_set_exec.origin)
+                    .chain(test_set_exec.others.iter())
+                    .map(|exec| {
+                        let result = test_set_result
+                            .test_cases
+                            .iter()
+                            .find(|result| result.id == exec.id());
+                        JUnitCase {
+                            class_name: class_name.clone(),
+                            name: case_name(exec.id()),
+                            outcome: exec_outcome(exec, result, test_set_result),
+                        }
+                    })
+                    .collect(),
+                None => test_set_result
+                    .test_cases
+                    .iter()
+                    .map(|result| JUnitCase {
+                        class_name: class_name.clone(),
+                        name: case_name(result.id),
+                        outcome: result_outcome(result),
+                    })
+                    .collect(),
+            };
+            self.suites.push(JUnitSuite {
+                name: format!("test-run-{run_id}.test-set-{}", test_set_result.id),
+                cases,
+            });
+        }
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let cases = self.suites.iter().flat_map(|suite| suite.cases.iter());
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"achilles\" {}>",
+            count_attributes(cases)
+        );
+        for suite in &self.suites {
+            let _ = writeln!(
+                xml,
+                "  <testsuite name=\"{}\" {}>",
+                xml_escape(&suite.name),
+                count_attributes(suite.cases.iter())
+            );
+            for case in &suite.cases {
+                let _ = write!(
+                    xml,
+                    "    <testcase classname=\"{}\" name=\"{}\"",
+                    xml_escape(&case.class_name),
+                    xml_escape(&case.name)
+                );
+                match &case.outcome {
+                    JUnitOutcome::Passed => xml.push_str("/>\n"),
+                    JUnitOutcome::Failure {
+                        kind,
+                        message,
+                        details,
+                    } => {
+                        let _ = write!(
+                            xml,
+                            ">\n      <failure type=\"{kind}\" message=\"{}\">{}</failure>\n    </testcase>\n",
+                            xml_escape(message),
+                            xml_escape(details)
+                        );
+                    }
+                    JUnitOutcome::Error { kind, message } => {
+                        let _ = write!(
+                            xml,
+                            ">\n      <error type=\"{kind}\" message=\"{}\"/>\n    </testcase>\n",
+                            xml_escape(message)
+                        );
+                    }
+                    JUnitOutcome::Skipped => xml.push_str(">\n      <skipped/>\n    </testcase>\n"),
+                }
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+/// The name that `--case` of replay and reduce accepts.
+fn case_name(id: TestCaseId) -> String {
+    match id {
+        TestCaseId::Origin => "Origin".into(),
+        TestCaseId::Other(id) => format!("Other{id}"),
+    }
+}
+
+fn exec_outcome(
+    exec: &TestCaseExec,
+    result: Option<&TestCaseResult>,
+    test_set_result: &TestSetResult,
+) -> JUnitOutcome {
+    let query = || stringify_query(exec.query());
+    match &exec.status {
+        TestCaseExecStatus::Success => match (exec.id(), result) {
+            (TestCaseId::Origin, _)
+                if test_set_result
+                    .origin_reference
+                    .as_ref()
+                    .is_some_and(|reference| reference.is_diff()) =>
+            {
+                JUnitOutcome::Failure {
+                    kind: "RefDiff",
+                    message: "Result differs from the reference result.".into(),
+                    details: query(),
+                }
+            }
+            (TestCaseId::Origin, _) => JUnitOutcome::Passed,
+            (_, Some(result)) => result_outcome(result),
+            (_, None) => JUnitOutcome::Error {
+                kind: "NoResult",
+                message: "Result could not be compared with the origin result.".into(),
+            },
+        },
+        TestCaseExecStatus::Failed(reason) => JUnitOutcome::Failure {
+            kind: "Failed",
+            message: reason.clone(),
+            details: query(),
+        },
+        TestCaseExecStatus::TimedOut => JUnitOutcome::Failure {
+            kind: "TimedOut",
+            message: "Query did not stop within the test case timeout.".into(),
+            details: query(),
+        },
+        TestCaseExecStatus::Skipped => JUnitOutcome::Skipped,
+        TestCaseExecStatus::StartupFailed(reason) => JUnitOutcome::Error {
+            kind: "StartupFailed",
+            message: reason.clone(),
+        },
+    }
+}
+
+fn result_outcome(result: &TestCaseResult) -> JUnitOutcome {
+    if result.relation == ResultRelation::Diff {
+        JUnitOutcome::Failure {
+            kind: "Diff",
+            message: "Result differs from the origin result.".into(),
+            details: result.query_string.clone(),
+        }
+    } else if result.reference.as_ref().is_some_and(|r| r.is_diff()) {
+        JUnitOutcome::Failure {
+            kind: "RefDiff",
+            message: "Result differs from the reference result.".into(),
+            details: result.query_string.clone(),
+        }
+    } else {
+        JUnitOutcome::Passed
+    }
+}
+
+fn count_attributes<'a>(cases: impl Iterator<Item = &'a JUnitCase>) -> String {
+    let (mut tests, mut failures, mut errors, mut skipped) = (0, 0, 0, 0);
+    for case in cases {
+        tests += 1;
+        match case.outcome {
+            JUnitOutcome::Passed => (),
+            JUnitOutcome::Failure { .. } => failures += 1,
+            JUnitOutcome::Error { .. } => errors += 1,
+            JUnitOutcome::Skipped => skipped += 1,
+        }
+    }
+    format!("tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" skipped=\"{skipped}\"")
+}
+
+fn xml_escape(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len());
+    for char in str.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // not allowed in XML 1.0
+            char if char.is_control() && !matches!(char, '\n' | '\r' | '\t') => (),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod summary_export_tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        eval::{
+            check_results::{TestCaseResult, TestSetResult},
+            evaluator::ResultRelation,
+        },
+        summery::{SummaryStats, SummaryStatsEntry},
+        test_case_gen::{oracle::QueryGenStrategy, query_id::TestCaseId},
+    };
+
+    use super::{json_string, stats_to_json, JUnitReport};
+
+    #[test]
+    fn json() {
+        let entry = SummaryStatsEntry {
+            total_count: 4,
+            success_count: 3,
+            ..Default::default()
+        };
+        let stats = SummaryStats {
+            stats: HashMap::from([(QueryGenStrategy::AggSum, entry)]),
+        };
+        let json = stats_to_json(&[(0, stats.clone())], &stats);
+        assert!(json.starts_with("{\"runs\": [{\"run\": 0, \"oracles\": {\"AggSum\": {\"total\": 4, \"success\": 3, \"exec_success_rate\": 0.75,"));
+        // no results, so there is no eval success rate
+        assert!(json.contains("\"eval_success_rate\": null"));
+        assert_eq!(json_string("a\"b\\\n"), "\"a\\\"b\\\\\\n\"");
+    }
+
+    #[test]
+    fn junit_without_execs() {
+        let result = |id, relation| TestCaseResult {
+            id: TestCaseId::Other(id),
+            query_string: "Query::from(\"a\") & <b>".into(),
+            relation,
+            reference: None,
+            max_error: None,
+        };
+        let test_set_result = TestSetResult {
+            id: 2,
+            strategy: QueryGenStrategy::Filter,
+            origin_reference: None,
+            test_cases: vec![
+                result(1, ResultRelation::Equal),
+                result(2, ResultRelation::Diff),
+            ],
+        };
+        let mut report = JUnitReport::default();
+        report.add_run(0, &[], &[test_set_result]);
+        let xml = report.to_xml();
+        assert!(xml.contains("<testsuite name=\"test-run-0.test-set-2\" tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"0\">"));
+        assert!(xml.contains("<testcase classname=\"Filter\" name=\"Other1\"/>"));
+        assert!(xml.contains("<failure type=\"Diff\" message=\"Result differs from the origin result.\">Query::from(&quot;a&quot;) &amp; &lt;b&gt;</failure>"));
+    }
+}
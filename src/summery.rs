@@ -1,29 +1,36 @@
-use std::{collections::HashMap, fmt, ops::AddAssign};
+use std::{collections::HashMap, fmt, ops::AddAssign, path::Path};
 
 use crate::{
-    eval::{check_results::read_test_set_results_from_file, evaluator::ResultRelation},
-    test_case_exec::{read_test_set_execs_from_file, TestCaseExecStatus},
+    eval::{
+        check_results::{read_test_set_results_from_file, ReferenceOutcome, TestSetResult},
+        evaluator::ResultRelation,
+    },
+    summary_export::{export_summary, JUnitReport},
+    test_case_exec::{read_test_set_execs_from_file, TestCaseExecStatus, TestSetExec},
     test_case_gen::oracle::QueryGenStrategy,
-    LancerConfig,
+    AchillesError, LancerConfig,
 };
 
 #[derive(Default, Clone)]
-struct SummaryStatsEntry {
+pub(crate) struct SummaryStatsEntry {
     // Exec
-    total_count: u32,
-    success_count: u32,
-    fail_count: u32,
-    timeout_count: u32,
-    skipped_count: u32,
+    pub(crate) total_count: u32,
+    pub(crate) success_count: u32,
+    pub(crate) fail_count: u32,
+    pub(crate) timeout_count: u32,
+    pub(crate) skipped_count: u32,
+    pub(crate) startup_fail_count: u32,
     // Eval
-    total_res_count: u32,
-    equal_count: u32,
-    reorder_count: u32,
-    diff_count: u32,
+    pub(crate) total_res_count: u32,
+    pub(crate) equal_count: u32,
+    pub(crate) reorder_count: u32,
+    pub(crate) diff_count: u32,
+    pub(crate) ref_diff_count: u32,
+    pub(crate) ref_unsupported_count: u32,
 }
 
 impl SummaryStatsEntry {
-    fn success_rates(&self) -> SummerySuccessRate {
+    pub(crate) fn success_rates(&self) -> SummerySuccessRate {
         let exec_success_rate = self.success_count as f32 / self.total_count as f32;
         let eval_success_rate =
             (self.equal_count + self.reorder_count) as f32 / self.total_res_count as f32;
@@ -41,16 +48,19 @@ impl AddAssign for SummaryStatsEntry {
         self.fail_count += rhs.fail_count;
         self.timeout_count += rhs.timeout_count;
         self.skipped_count += rhs.skipped_count;
+        self.startup_fail_count += rhs.startup_fail_count;
         self.total_res_count += rhs.total_res_count;
         self.equal_count += rhs.equal_count;
         self.reorder_count += rhs.reorder_count;
         self.diff_count += rhs.diff_count;
+        self.ref_diff_count += rhs.ref_diff_count;
+        self.ref_unsupported_count += rhs.ref_unsupported_count;
     }
 }
 
-struct SummerySuccessRate {
-    exec_success_rate: f32,
-    eval_success_rate: f32,
+pub(crate) struct SummerySuccessRate {
+    pub(crate) exec_success_rate: f32,
+    pub(crate) eval_success_rate: f32,
 }
 
 struct SummerySuccessRateVariance {
@@ -58,9 +68,9 @@ struct SummerySuccessRateVariance {
     eval_variance: f32,
 }
 
-#[derive(Default)]
-struct SummaryStats {
-    stats: HashMap<QueryGenStrategy, SummaryStatsEntry>,
+#[derive(Default, Clone)]
+pub(crate) struct SummaryStats {
+    pub(crate) stats: HashMap<QueryGenStrategy, SummaryStatsEntry>,
 }
 
 impl SummaryStats {
@@ -73,11 +83,27 @@ impl SummaryStats {
     }
 
     fn total_success_rates(&self) -> SummerySuccessRate {
+        self.totals().success_rates()
+    }
+
+    /// Sum of the entries of all oracles.
+    pub(crate) fn totals(&self) -> SummaryStatsEntry {
         let mut all_totals = SummaryStatsEntry::default();
         for (_, entry) in &self.stats {
             all_totals += entry.clone();
         }
-        all_totals.success_rates()
+        all_totals
+    }
+
+    /// Entries ordered by the name of the oracle.
+    pub(crate) fn sorted_entries(&self) -> Vec<(QueryGenStrategy, &SummaryStatsEntry)> {
+        let mut entries = self
+            .stats
+            .iter()
+            .map(|(strategy, entry)| (*strategy, entry))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(strategy, _)| format!("{strategy:?}"));
+        entries
     }
 }
 
@@ -97,7 +123,7 @@ impl fmt::Display for SummaryStats {
         // header
         writeln!(
             f,
-            "{:<15} | {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} | {:<10} {:<10} {:<10} {:<10} {:<10}",
+            "{:<15} | {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} | {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} {:<10}",
             "Oracle",
             "Total",
             "Success",
@@ -105,20 +131,23 @@ impl fmt::Display for SummaryStats {
             "Fail",
             "Timeout",
             "Skipped",
+            "StartFail",
             "TotalRes",
             "Succ Rate",
             "Equal",
             "Reorder",
-            "Diff"
+            "Diff",
+            "RefDiff",
+            "RefUnsup"
         )?;
-        writeln!(f, "{}", "-".repeat(111))?;
+        writeln!(f, "{}", "-".repeat(144))?;
 
         let mut all_totals = SummaryStatsEntry::default();
 
         for (strategy, entry) in &self.stats {
             writeln!(
                 f,
-                "{:<15} | {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} | {:<10} {:<10} {:<10} {:<10} {:<10}",
+                "{:<15} | {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} | {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} {:<10}",
                 format!("{:?}", strategy),
                 entry.total_count,
                 entry.success_count,
@@ -126,11 +155,14 @@ impl fmt::Display for SummaryStats {
                 entry.fail_count,
                 entry.timeout_count,
                 entry.skipped_count,
+                entry.startup_fail_count,
                 entry.total_res_count,
                 (entry.equal_count + entry.reorder_count) as f32 / entry.total_res_count as f32,
                 entry.equal_count,
                 entry.reorder_count,
-                entry.diff_count
+                entry.diff_count,
+                entry.ref_diff_count,
+                entry.ref_unsupported_count
             )?;
 
             // Accumulate totals for the "all" row
@@ -139,15 +171,18 @@ impl fmt::Display for SummaryStats {
             all_totals.fail_count += entry.fail_count;
             all_totals.timeout_count += entry.timeout_count;
             all_totals.skipped_count += entry.skipped_count;
+            all_totals.startup_fail_count += entry.startup_fail_count;
             all_totals.total_res_count += entry.total_res_count;
             all_totals.equal_count += entry.equal_count;
             all_totals.reorder_count += entry.reorder_count;
             all_totals.diff_count += entry.diff_count;
+            all_totals.ref_diff_count += entry.ref_diff_count;
+            all_totals.ref_unsupported_count += entry.ref_unsupported_count;
         }
 
         writeln!(
             f,
-            "{:<15} | {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} | {:<10} {:<10} {:<10} {:<10} {:<10}",
+            "{:<15} | {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} | {:<10} {:<10} {:<10} {:<10} {:<10} {:<10} {:<10}",
             "All",
             all_totals.total_count,
             all_totals.success_count,
@@ -155,34 +190,53 @@ impl fmt::Display for SummaryStats {
             all_totals.fail_count,
             all_totals.timeout_count,
             all_totals.skipped_count,
+            all_totals.startup_fail_count,
             all_totals.total_res_count,
             (all_totals.equal_count + all_totals.reorder_count) as f32 / all_totals.total_res_count as f32,
             all_totals.equal_count,
             all_totals.reorder_count,
-            all_totals.diff_count
+            all_totals.diff_count,
+            all_totals.ref_diff_count,
+            all_totals.ref_unsupported_count
         )?;
 
         Ok(())
     }
 }
 
-pub fn summary_operation(config: &LancerConfig) {
+/// Prints the summary of every test run and the total. With an `export_dir` the summary is also
+/// written to `summary.json`, `summary.csv` and `junit.xml` in that directory.
+pub fn summary_operation(
+    config: &LancerConfig,
+    export_dir: Option<&Path>,
+) -> Result<(), AchillesError> {
     log::info!("Starting Summary Mode.");
+    let mut run_stats = Vec::new();
     let mut total_stats = SummaryStats::default();
+    let mut junit = JUnitReport::default();
     for run_id in 0..config.test_config.test_run_count {
         println!("---( RUN {run_id} ) ---");
-        let stats = calc_summery(run_id, config);
+        let test_set_execs = read_test_set_execs_from_file(run_id, config)?;
+        let test_set_results = read_test_set_results_from_file(run_id, config)?;
+        let stats = calc_summery(&test_set_execs, &test_set_results);
         println!("{stats}");
-        total_stats += stats;
+        junit.add_run(run_id, &test_set_execs, &test_set_results);
+        total_stats += stats.clone();
+        run_stats.push((run_id, stats));
     }
     println!("---( TOTAL ) ---");
     println!("{total_stats}");
+    if let Some(dir) = export_dir {
+        export_summary(dir, &run_stats, &total_stats, &junit)?;
+        log::info!("Exported the summary to {:?}.", dir);
+    }
+    Ok(())
 }
 
-fn calc_summery(run_id: u32, config: &LancerConfig) -> SummaryStats {
-    let test_set_execs = read_test_set_execs_from_file(run_id, &config);
-    let test_set_results = read_test_set_results_from_file(run_id, &config);
-
+fn calc_summery(
+    test_set_execs: &[TestSetExec],
+    test_set_results: &[TestSetResult],
+) -> SummaryStats {
     let mut sum_stats = SummaryStats::default();
     for test_set_exec in test_set_execs {
         let iter = std::iter::once(&test_set_exec.origin).chain(test_set_exec.others.iter());
@@ -196,6 +250,7 @@ fn calc_summery(run_id: u32, config: &LancerConfig) -> SummaryStats {
                 TestCaseExecStatus::Failed(_) => stats.fail_count += 1,
                 TestCaseExecStatus::TimedOut => stats.timeout_count += 1,
                 TestCaseExecStatus::Skipped => stats.skipped_count += 1,
+                TestCaseExecStatus::StartupFailed(_) => stats.startup_fail_count += 1,
             }
         }
     }
@@ -203,6 +258,7 @@ fn calc_summery(run_id: u32, config: &LancerConfig) -> SummaryStats {
     for test_set_result in test_set_results {
         let strat = test_set_result.strategy;
         let stats = sum_stats.stats.entry(strat).or_default();
+        count_reference(stats, test_set_result.origin_reference.as_ref());
         for res in &test_set_result.test_cases {
             stats.total_res_count += 1;
             match res.relation {
@@ -210,7 +266,16 @@ fn calc_summery(run_id: u32, config: &LancerConfig) -> SummaryStats {
                 ResultRelation::Reordered => stats.reorder_count += 1,
                 ResultRelation::Diff => stats.diff_count += 1,
             }
+            count_reference(stats, res.reference.as_ref());
         }
     }
     sum_stats
 }
+
+fn count_reference(stats: &mut SummaryStatsEntry, reference: Option<&ReferenceOutcome>) {
+    match reference {
+        Some(reference) if reference.is_diff() => stats.ref_diff_count += 1,
+        Some(reference) if reference.is_unsupported() => stats.ref_unsupported_count += 1,
+        _ => {}
+    }
+}
@@ -1,10 +1,11 @@
-use std::{fs, path::Path};
+use std::path::Path;
 
 use nes_rust_client::query::Query;
-use std::io::Write;
-use yaml_rust2::{yaml::Hash, Yaml, YamlEmitter, YamlLoader};
+use yaml_rust2::{yaml::Hash, Yaml};
 
 use crate::{
+    error::AchillesError,
+    yaml_util::{load_yaml_array, store_yaml_array},
     test_case_gen::{oracle::QueryGenStrategy, query_id::TestCaseId, test_case::TestCase},
     LancerConfig,
 };
@@ -29,6 +30,8 @@ pub enum TestCaseExecStatus {
     Failed(String),
     TimedOut,
     Skipped,
+    /// NebulaStream could not be started, so the test case was not executed.
+    StartupFailed(String),
 }
 
 impl TestCaseExec {
@@ -60,9 +63,11 @@ impl Into<Yaml> for &TestCaseExecStatus {
             TestCaseExecStatus::Success => "Success",
             TestCaseExecStatus::Failed(_) => "Failed",
             TestCaseExecStatus::TimedOut => "TimedOut",
+            TestCaseExecStatus::StartupFailed(_) => "StartupFailed",
         };
         map.insert(Yaml::String("status".into()), Yaml::from_str(str));
-        if let TestCaseExecStatus::Failed(reason) = self {
+        if let TestCaseExecStatus::Failed(reason) | TestCaseExecStatus::StartupFailed(reason) = self
+        {
             map.insert(Yaml::String("reason".into()), Yaml::from_str(reason));
         }
         Yaml::Hash(map)
@@ -88,6 +93,15 @@ impl TryFrom<&Yaml> for TestCaseExecStatus {
                 Ok(TestCaseExecStatus::Failed(reason.into()))
             }
             "TimedOut" => Ok(TestCaseExecStatus::TimedOut),
+            "StartupFailed" => {
+                let Yaml::String(ref reason) = value["reason"] else {
+                    return Err(
+                        "Failed to parse QueryExecStatus::StartupFailed. Unable to pase reason."
+                            .into(),
+                    );
+                };
+                Ok(TestCaseExecStatus::StartupFailed(reason.into()))
+            }
             err => Err(format!(
                 "Failed to Parse QueryExecStatus. Unknown state: {err}"
             )),
@@ -151,33 +165,17 @@ impl TryFrom<&Yaml> for TestSetExec {
     }
 }
 
-pub fn read_test_set_execs_from_file(test_run_id: u32, config: &LancerConfig) -> Vec<TestSetExec> {
-    let path = config.path_config.test_set_execs(test_run_id);
-    let content = fs::read_to_string(path).expect("Should have been able to read the file!");
-    let docs = YamlLoader::load_from_str(&content).expect("Should have been able to parse Yaml.");
-    let doc = &docs.first().expect("Should have one element");
-    let Yaml::Array(arr) = doc else {
-        panic!("Should have been able to parse Yaml::Array.")
-    };
-    arr.iter()
-        .map(|yaml_obj| yaml_obj.try_into().expect("Should not fail to parse Yaml."))
-        .collect()
+pub fn read_test_set_execs_from_file(
+    test_run_id: u32,
+    config: &LancerConfig,
+) -> Result<Vec<TestSetExec>, AchillesError> {
+    load_yaml_array(&config.path_config.test_set_execs(test_run_id))
 }
 
 pub fn write_test_set_execs_to_file(
     test_run_id: u32,
     config: &LancerConfig,
     test_case_execs: &[TestSetExec],
-) {
-    let path = config.path_config.test_set_execs(test_run_id);
-    let yaml_test_cases: Vec<Yaml> = test_case_execs
-        .iter()
-        .map(|test_case| test_case.into())
-        .collect();
-    let yaml_arr = Yaml::Array(yaml_test_cases);
-    let mut out_str = String::new();
-    let mut emitter = YamlEmitter::new(&mut out_str);
-    emitter.dump(&yaml_arr).unwrap();
-    let mut file = fs::File::create(path).expect("test_set_execs.yml has to be created!");
-    write!(file, "{out_str}").unwrap();
+) -> Result<(), AchillesError> {
+    store_yaml_array(&config.path_config.test_set_execs(test_run_id), test_case_execs)
 }
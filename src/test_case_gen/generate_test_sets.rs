@@ -1,34 +1,66 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::expr_gen::expr_gen::DataValues;
+use crate::reference::interpreter::{for_each_source_row, source_tables};
+use crate::reference::value::Value;
+use crate::seed::{data_values_seed, seeded_rng, test_run_seed, test_set_seed};
 use crate::stream_schema::StreamSchema;
 use crate::test_case_gen::oracle::QueryGenFactory;
 use crate::LancerConfig;
 use nes_rust_client::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
 
 use super::{
     oracle::{QueryGen, QueryGenStrategy},
     test_case::{TestCase, TestSet},
 };
 
+/// Maximal number of values per field that literals are drawn from.
+const DATA_VALUES_PER_FIELD: usize = 64;
+
 pub fn generate_test_sets(
     test_run_id: u32,
     config: &LancerConfig,
     schema: &StreamSchema,
 ) -> Vec<TestSet> {
-    let query_gen_factory = QueryGenFactory::new(config.test_config.predicate_depth);
+    let mut expr_config = config.test_config.expr_gen_config();
+    expr_config.data_values = load_data_values(test_run_id, config);
+    let query_gen_factory = QueryGenFactory::new(expr_config);
+    let run_seed = test_run_seed(config.test_config.seed, test_run_id);
     log::info!("Started  generate_test_cases:");
     let test_cases = config
         .test_config
         .oracles
         .iter()
         .enumerate()
+        .filter(|(_, strat)| {
+            let has_late_records = config.test_config.time_stamp_disorder.has_late_records();
+            if has_late_records && strat.compares_window_sizes() {
+                log::warn!("Skipping oracle {strat:?}, because the data has late records.");
+                return false;
+            }
+            true
+        })
         .map(|(oracle_id, &strat)| {
             let mut cases = vec![];
             let reps = config.test_config.oracle_reps as usize;
             for rep_id in 0..reps {
                 let test_set_id = (oracle_id * reps + rep_id) as u32;
-                let query_gen = query_gen_factory.create_query_gen(&schema, strat);
-                let case = generate_test_case(test_run_id, test_set_id, config, &*query_gen, strat);
+                let seed = test_set_seed(run_seed, test_set_id);
+                let mut rng = seeded_rng(seed);
+                let query_gen = query_gen_factory.create_query_gen(&schema, strat, &mut rng);
+                let case = generate_test_case(
+                    test_run_id,
+                    test_set_id,
+                    seed,
+                    config,
+                    &*query_gen,
+                    strat,
+                    &mut rng,
+                );
                 cases.push(case);
             }
             cases
@@ -39,12 +71,94 @@ pub fn generate_test_sets(
     test_cases
 }
 
+/// Samples the values of every field of the generated data for the literals of the queries. The
+/// data files are streamed and every field keeps a reservoir sample of `DATA_VALUES_PER_FIELD`
+/// values, so the whole data never has to fit into memory. If the data cannot be read, literals
+/// are not drawn from the data.
+fn load_data_values(test_run_id: u32, config: &LancerConfig) -> DataValues {
+    let mut data_values = DataValues::default();
+    if config.test_config.data_literal_ratio <= 0.0 {
+        return data_values;
+    }
+    let tables = match source_tables(test_run_id, config) {
+        Ok(tables) => tables,
+        Err(err) => {
+            log::warn!("Unable to load data values for literals: {err}");
+            return data_values;
+        }
+    };
+    let run_seed = test_run_seed(config.test_config.seed, test_run_id);
+    let mut rng = seeded_rng(data_values_seed(run_seed));
+    let mut reservoirs: HashMap<&str, Vec<Reservoir>> = tables
+        .iter()
+        .map(|(name, table)| {
+            (
+                name.as_str(),
+                vec![Reservoir::default(); table.fields.len()],
+            )
+        })
+        .collect();
+    let result = for_each_source_row(test_run_id, config, &tables, |source_name, row| {
+        let Some(reservoirs) = reservoirs.get_mut(source_name) else {
+            return;
+        };
+        for (reservoir, value) in reservoirs.iter_mut().zip(row) {
+            reservoir.add(&mut rng, value);
+        }
+    });
+    if let Err(err) = result {
+        log::warn!("Unable to load data values for literals: {err}");
+        return data_values;
+    }
+    // sorted, so the literals do not depend on the iteration order of the map
+    let mut tables = tables.values().collect::<Vec<_>>();
+    tables.sort_by(|lhs, rhs| lhs.source_name.cmp(&rhs.source_name));
+    for table in tables {
+        let Some(reservoirs) = reservoirs.remove(table.source_name.as_str()) else {
+            continue;
+        };
+        for (field, reservoir) in table.fields.iter().zip(reservoirs) {
+            data_values.add(&field.name, field.data_type, reservoir.values);
+        }
+    }
+    data_values
+}
+
+/// Uniform sample of at most `DATA_VALUES_PER_FIELD` values of a stream (Algorithm R).
+#[derive(Debug, Clone, Default)]
+struct Reservoir {
+    seen: usize,
+    values: Vec<String>,
+}
+
+impl Reservoir {
+    fn add(&mut self, rng: &mut impl Rng, value: Value) {
+        self.seen += 1;
+        let index = if self.values.len() < DATA_VALUES_PER_FIELD {
+            self.values.len()
+        } else {
+            rng.gen_range(0..self.seen)
+        };
+        let value = match value {
+            Value::Bool(bool) => bool.to_string(),
+            value => value.to_string(),
+        };
+        match index.cmp(&self.values.len()) {
+            Ordering::Less => self.values[index] = value,
+            Ordering::Equal => self.values.push(value),
+            Ordering::Greater => {}
+        }
+    }
+}
+
 fn generate_test_case(
     test_run_id: u32,
     test_set_id: u32,
+    seed: u64,
     config: &LancerConfig,
     query_gen: &dyn QueryGen,
     strategy: QueryGenStrategy,
+    rng: &mut StdRng,
 ) -> TestSet {
     let origin_path = config
         .path_config
@@ -60,15 +174,58 @@ fn generate_test_case(
                 .result(test_run_id)
                 .join(format!("test-set{test_set_id}-other{other_id}.csv"));
             let other_sink = Sink::csv_file(&other_path, false);
-            let q_other = query_gen.other().sink(other_sink);
+            let q_other = query_gen.other(rng).sink(other_sink);
             TestCase::other(other_id, q_other, PathBuf::from(other_path))
         })
         .collect();
 
     TestSet {
         id: test_set_id,
+        seed,
         strategy,
         origin,
         others,
     }
 }
+
+#[cfg(test)]
+mod generate_test_sets_tests {
+    use crate::{reference::value::Value, seed::seeded_rng};
+
+    use super::{Reservoir, DATA_VALUES_PER_FIELD};
+
+    #[test]
+    fn reservoir_samples_uniformly() {
+        let mut rng = seeded_rng(3);
+        let mut short = Reservoir::default();
+        for i in 0..10 {
+            short.add(&mut rng, Value::Int(i));
+        }
+        let expected = (0..10).map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(short.values, expected);
+
+        // every value of a long stream ends up in the sample with probability 64 / 1000
+        let mut hits = vec![0; 1000];
+        for _ in 0..200 {
+            let mut reservoir = Reservoir::default();
+            for i in 0..1000 {
+                reservoir.add(&mut rng, Value::Int(i));
+            }
+            assert_eq!(reservoir.seen, 1000);
+            assert_eq!(reservoir.values.len(), DATA_VALUES_PER_FIELD);
+            for value in reservoir.values {
+                hits[value.parse::<usize>().unwrap()] += 1;
+            }
+        }
+        let first = hits[..500].iter().sum::<usize>();
+        let last = hits[500..].iter().sum::<usize>();
+        assert!(first.abs_diff(last) < 1000, "{first} vs {last}");
+    }
+
+    #[test]
+    fn reservoir_formats_bools_as_literals() {
+        let mut reservoir = Reservoir::default();
+        reservoir.add(&mut seeded_rng(0), Value::Bool(true));
+        assert_eq!(reservoir.values, ["true"]);
+    }
+}
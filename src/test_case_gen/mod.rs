@@ -3,6 +3,7 @@ pub mod oracle;
 pub mod query_id;
 pub mod test_case;
 pub mod util;
+pub mod window;
 
 // fn query_average() -> (Query, Query) {
 //     unimplemented!();
@@ -1,71 +1,84 @@
 use crate::{
+    expr_gen::expr_gen::ExprGenConfig,
     stream_gen::LogicalSource,
     stream_schema::StreamSchema,
     test_case_gen::util::{
-        generate_predicate, generate_window_descriptor, get_random_field_name, random_source,
+        generate_predicate, generate_window, get_random_field_name, get_ts_field, random_source,
     },
+    test_case_gen::window::Window,
 };
 use nes_rust_client::prelude::*;
+use rand::rngs::StdRng;
 
 use super::QueryGen;
 
 pub struct AggregationMinQueryGen {
     // static values
-    predicate_depth: u32,
+    expr_config: ExprGenConfig,
     // dynamic values
     source: LogicalSource,
-    window_desc: WindowDescriptor,
+    window: Window,
+    ts_field: Field,
     agg_field_name: String,
 }
 
 impl AggregationMinQueryGen {
-    pub fn with_predicate_depth(mut self, depth: u32) -> Self {
-        self.predicate_depth = depth;
+    pub fn with_expr_config(mut self, config: ExprGenConfig) -> Self {
+        self.expr_config = config;
         self
     }
 }
 
 impl QueryGen for AggregationMinQueryGen {
-    fn new(schema: &StreamSchema) -> Self {
-        let source = random_source(&schema);
-        let window_desc = generate_window_descriptor();
-        let agg_field_name = get_random_field_name(&source);
+    fn new(schema: &StreamSchema, rng: &mut StdRng) -> Self {
+        let source = random_source(rng, schema);
+        let window = generate_window(rng);
+        let ts_field = get_ts_field(&source);
+        let agg_field_name = get_random_field_name(rng, &source);
         Self {
-            predicate_depth: 3,
+            expr_config: ExprGenConfig::default(),
             source,
-            window_desc,
+            window,
+            ts_field,
             agg_field_name,
         }
     }
 
     fn origin(&self) -> QueryBuilder {
         let builder = QueryBuilder::from_source(&self.source.source_name);
-        builder
-            .window(self.window_desc.clone())
-            .apply([Aggregation::min(self.agg_field_name.clone())])
+        self.window.aggregate(
+            builder,
+            &self.ts_field,
+            None,
+            [Aggregation::min(self.agg_field_name.clone())],
+        )
     }
 
-    fn other(&self) -> QueryBuilder {
-        let predicate = generate_predicate(self.predicate_depth, &self.source.fields);
+    fn other(&self, rng: &mut StdRng) -> QueryBuilder {
+        let predicate = generate_predicate(rng, &self.expr_config, &self.source.fields);
         let builder = QueryBuilder::from_source(&self.source.source_name);
 
-        let query = builder
-            .clone()
-            .filter(predicate.clone())
-            .window(self.window_desc.clone())
-            .apply([Aggregation::min(self.agg_field_name.clone())]);
-        let query_not = builder
-            .filter(predicate.not())
-            .window(self.window_desc.clone())
-            .apply([Aggregation::min(self.agg_field_name.clone())]);
-        query
-            .union(query_not)
-            .project([
-                Field::from("start").rename("ts"),
-                Field::from("end"),
-                Field::from(self.agg_field_name.clone()),
-            ])
-            .window(self.window_desc.clone())
-            .apply([Aggregation::min(self.agg_field_name.clone())])
+        let query = self.window.aggregate(
+            builder.clone().filter(predicate.clone()),
+            &self.ts_field,
+            None,
+            [Aggregation::min(self.agg_field_name.clone())],
+        );
+        let query_not = self.window.aggregate(
+            builder.filter(predicate.not()),
+            &self.ts_field,
+            None,
+            [Aggregation::min(self.agg_field_name.clone())],
+        );
+        let partials = query.union(query_not).project([
+            Field::from("start").rename("ts"),
+            Field::from("end"),
+            Field::from(self.agg_field_name.clone()),
+        ]);
+        self.window.reaggregate(
+            partials,
+            None,
+            [Aggregation::min(self.agg_field_name.clone())],
+        )
     }
 }
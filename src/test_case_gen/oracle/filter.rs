@@ -1,31 +1,34 @@
 use nes_rust_client::prelude::*;
 
 use crate::{
-    test_case_gen::util::{generate_predicate, random_source}, stream_gen::LogicalSource, stream_schema::StreamSchema,
+    expr_gen::expr_gen::ExprGenConfig,
+    stream_gen::LogicalSource,
+    stream_schema::StreamSchema,
+    test_case_gen::util::{generate_predicate, random_source},
 };
+use rand::rngs::StdRng;
 
 use super::QueryGen;
 
 pub struct FilterQueryGen {
     // static values
-    predicate_depth: u32,
+    expr_config: ExprGenConfig,
     // dynamic values
     source: LogicalSource,
 }
 
 impl FilterQueryGen {
-    pub fn with_predicate_depth(mut self, depth: u32) -> Self {
-        self.predicate_depth = depth;
+    pub fn with_expr_config(mut self, config: ExprGenConfig) -> Self {
+        self.expr_config = config;
         self
     }
 }
 
-
 impl QueryGen for FilterQueryGen {
-    fn new(schema: &StreamSchema) -> Self {
-        let source = random_source(&schema);
+    fn new(schema: &StreamSchema, rng: &mut StdRng) -> Self {
+        let source = random_source(rng, schema);
         Self {
-            predicate_depth: 3,
+            expr_config: ExprGenConfig::default(),
             source,
         }
     }
@@ -34,9 +37,9 @@ impl QueryGen for FilterQueryGen {
         QueryBuilder::from_source(&self.source.source_name)
     }
 
-    fn other(&self) -> QueryBuilder {
+    fn other(&self, rng: &mut StdRng) -> QueryBuilder {
         let builder = QueryBuilder::from_source(&self.source.source_name);
-        let predicate = generate_predicate(self.predicate_depth, &self.source.fields);
+        let predicate = generate_predicate(rng, &self.expr_config, &self.source.fields);
         let query = builder.clone().filter(predicate.clone());
         let query_not = builder.filter(predicate.not());
         query.union(query_not)
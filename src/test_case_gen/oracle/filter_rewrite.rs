@@ -0,0 +1,60 @@
+use nes_rust_client::prelude::*;
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    expr_gen::expr_gen::ExprGenConfig,
+    expr_gen::expr_rewrite::rewrite_logical_expr,
+    seed::seeded_rng,
+    stream_gen::LogicalSource,
+    stream_schema::StreamSchema,
+    test_case_gen::util::{generate_predicate, random_source},
+};
+
+use super::QueryGen;
+
+const MAX_REWRITE_COUNT: u32 = 3;
+
+/// The origin filters with a random predicate and the others filter with semantically equivalent
+/// rewrites of it, see `expr_rewrite`. Ternary logic partitioning is done by `FilterTernary`.
+pub struct FilterRewriteQueryGen {
+    // static values
+    expr_config: ExprGenConfig,
+    // dynamic values
+    source: LogicalSource,
+    /// The predicate depends on `expr_config`, which is set after `new`, so it is generated from
+    /// this seed whenever it is needed.
+    predicate_seed: u64,
+}
+
+impl FilterRewriteQueryGen {
+    pub fn with_expr_config(mut self, config: ExprGenConfig) -> Self {
+        self.expr_config = config;
+        self
+    }
+
+    fn predicate(&self) -> LogicalExpr {
+        let mut rng = seeded_rng(self.predicate_seed);
+        generate_predicate(&mut rng, &self.expr_config, &self.source.fields)
+    }
+}
+
+impl QueryGen for FilterRewriteQueryGen {
+    fn new(schema: &StreamSchema, rng: &mut StdRng) -> Self {
+        let source = random_source(rng, schema);
+        Self {
+            expr_config: ExprGenConfig::default(),
+            source,
+            predicate_seed: rng.gen(),
+        }
+    }
+
+    fn origin(&self) -> QueryBuilder {
+        QueryBuilder::from_source(&self.source.source_name).filter(self.predicate())
+    }
+
+    fn other(&self, rng: &mut StdRng) -> QueryBuilder {
+        let rewrite_count = rng.gen_range(1..=MAX_REWRITE_COUNT);
+        let predicate = rewrite_logical_expr(rng, &self.predicate(), rewrite_count);
+        QueryBuilder::from_source(&self.source.source_name).filter(predicate)
+    }
+}
@@ -0,0 +1,111 @@
+use nes_rust_client::{
+    expression::{
+        binary_expression::{BinaryExpr, BinaryOp},
+        expression::RawExpr,
+    },
+    prelude::*,
+};
+use nes_types::NesType;
+use rand::{rngs::StdRng, seq::IteratorRandom};
+
+use crate::{
+    expr_gen::expr_gen::{generate_raw_expr, ExprGenConfig},
+    stream_gen::LogicalSource,
+    stream_schema::StreamSchema,
+    test_case_gen::util::{generate_predicate, has_literal_literal, random_source},
+};
+
+use super::QueryGen;
+
+/// Ternary logic partitioning: the others are the union of the records where a comparison
+/// `field op expr` is true for `<`, `==` and `>`, and the records where it is unknown, i.e. false
+/// for all three. NebulaStream has no NULL values, but comparisons with NaN are unknown in this
+/// sense, which the `predicate` / `predicate.not()` partition of `Filter` does not cover. Sources
+/// without an integer or float field fall back to that partition.
+pub struct FilterTernaryQueryGen {
+    // static values
+    expr_config: ExprGenConfig,
+    // dynamic values
+    source: LogicalSource,
+}
+
+impl FilterTernaryQueryGen {
+    pub fn with_expr_config(mut self, config: ExprGenConfig) -> Self {
+        self.expr_config = config;
+        self
+    }
+
+    fn generate_operand(&self, rng: &mut StdRng, data_type: NesType) -> RawExpr {
+        let depth = self.expr_config.predicate_depth.saturating_sub(1);
+        loop {
+            let fields = &self.source.fields;
+            let Ok(expr) = generate_raw_expr(rng, depth, fields, data_type, &self.expr_config)
+            else {
+                continue;
+            };
+            if !has_literal_literal(&LogicalExpr(expr.clone())) {
+                break expr;
+            }
+        }
+    }
+}
+
+impl QueryGen for FilterTernaryQueryGen {
+    fn new(schema: &StreamSchema, rng: &mut StdRng) -> Self {
+        let source = random_source(rng, schema);
+        Self {
+            expr_config: ExprGenConfig::default(),
+            source,
+        }
+    }
+
+    fn origin(&self) -> QueryBuilder {
+        QueryBuilder::from_source(&self.source.source_name)
+    }
+
+    fn other(&self, rng: &mut StdRng) -> QueryBuilder {
+        let builder = QueryBuilder::from_source(&self.source.source_name);
+        let field = self
+            .source
+            .fields
+            .iter()
+            .filter(|field| matches!(field.data_type(), NesType::Int(_) | NesType::Float(_)))
+            .choose(rng)
+            .cloned();
+        let Some(field) = field else {
+            let predicate = generate_predicate(rng, &self.expr_config, &self.source.fields);
+            let query = builder.clone().filter(predicate.clone());
+            return query.union(builder.filter(predicate.not()));
+        };
+        let operand = self.generate_operand(rng, field.data_type());
+        let [less, equals, greater] = [BinaryOp::Less, BinaryOp::Equals, BinaryOp::Greater]
+            .map(|operator| binary(RawExpr::Field(field.clone()), operator, operand.clone()));
+        let unknown = [&less, &equals, &greater]
+            .map(|comparison| not(comparison.clone()))
+            .into_iter()
+            .reduce(|lhs, rhs| binary(lhs, BinaryOp::And, rhs))
+            .expect("There are three comparisons.");
+        [less, equals, greater, unknown]
+            .into_iter()
+            .map(|predicate| builder.clone().filter(LogicalExpr(predicate)))
+            .reduce(|query, partition| query.union(partition))
+            .expect("There are four partitions.")
+    }
+}
+
+fn binary(lhs: RawExpr, operator: BinaryOp, rhs: RawExpr) -> RawExpr {
+    RawExpr::Binary(BinaryExpr {
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+        data_type: NesType::Bool,
+        operator,
+    })
+}
+
+fn not(expr: RawExpr) -> RawExpr {
+    RawExpr::Unary(UnaryExpr {
+        expr: Box::new(expr),
+        data_type: NesType::Bool,
+        operator: UnaryOp::Negate,
+    })
+}
@@ -1,35 +1,49 @@
 use crate::expr_gen::expr_gen::generate_raw_expr;
 use crate::{
-    test_case_gen::util::{generate_predicate, random_source},
+    expr_gen::expr_gen::ExprGenConfig,
+    seed::seeded_rng,
     stream_gen::LogicalSource,
     stream_schema::StreamSchema,
+    test_case_gen::util::{generate_predicate, random_source},
 };
 use nes_rust_client::{
     expression::{ArithmeticExpr, Field},
     prelude::*,
 };
+use rand::{rngs::StdRng, Rng};
 
 use super::QueryGen;
 
 pub struct MapQueryGen {
     // static values
-    predicate_depth: u32,
+    expr_config: ExprGenConfig,
     // dynamic values
     source: LogicalSource,
-    expr: ArithmeticExpr,
+    /// The expression depends on `expr_config`, which is set after `new`, so it is generated from
+    /// this seed whenever it is needed.
+    expr_seed: u64,
 }
 
 impl MapQueryGen {
-    pub fn with_predicate_depth(mut self, depth: u32) -> Self {
-        self.predicate_depth = depth;
+    pub fn with_expr_config(mut self, config: ExprGenConfig) -> Self {
+        self.expr_config = config;
         self
     }
+
+    fn expr(&self) -> ArithmeticExpr {
+        let mut rng = seeded_rng(self.expr_seed);
+        generate_arithmetic_expr(&mut rng, &self.source.fields, &self.expr_config)
+    }
 }
 
-fn generate_arithmetic_expr(fields: &[Field]) -> ArithmeticExpr {
+fn generate_arithmetic_expr(
+    rng: &mut StdRng,
+    fields: &[Field],
+    config: &ExprGenConfig,
+) -> ArithmeticExpr {
     // ExprBuilder::field("value").build_arith().unwrap()
     ArithmeticExpr(loop {
-        let Ok(p) = generate_raw_expr(1, &fields, nes_types::NesType::i64()) else {
+        let Ok(p) = generate_raw_expr(rng, 1, fields, nes_types::NesType::i64(), config) else {
             continue;
         };
         break p;
@@ -37,31 +51,29 @@ fn generate_arithmetic_expr(fields: &[Field]) -> ArithmeticExpr {
 }
 
 impl QueryGen for MapQueryGen {
-    fn new(schema: &StreamSchema) -> Self {
-        let source = random_source(schema);
-        let expr = generate_arithmetic_expr(&source.fields);
+    fn new(schema: &StreamSchema, rng: &mut StdRng) -> Self {
+        let source = random_source(rng, schema);
         Self {
-            predicate_depth: 1,
+            expr_config: ExprGenConfig::default(),
             source,
-            expr,
+            expr_seed: rng.gen(),
         }
     }
 
     fn origin(&self) -> QueryBuilder {
         let builder = QueryBuilder::from_source(&self.source.source_name);
-        builder.map("new_value", self.expr.clone())
+        builder.map("new_value", self.expr())
     }
 
-    fn other(&self) -> QueryBuilder {
-        let predicate = generate_predicate(self.predicate_depth, &self.source.fields);
+    fn other(&self, rng: &mut StdRng) -> QueryBuilder {
+        let predicate = generate_predicate(rng, &self.expr_config, &self.source.fields);
+        let expr = self.expr();
         let builder = QueryBuilder::from_source(&self.source.source_name);
         let query = builder
             .clone()
             .filter(predicate.clone())
-            .map("new_value", self.expr.clone());
-        let query_not = builder
-            .filter(predicate.not())
-            .map("new_value", self.expr.clone());
+            .map("new_value", expr.clone());
+        let query_not = builder.filter(predicate.not()).map("new_value", expr);
         query.union(query_not)
     }
 }
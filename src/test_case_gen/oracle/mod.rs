@@ -1,4 +1,6 @@
 pub mod filter;
+pub mod filter_rewrite;
+pub mod filter_ternary;
 pub mod query_gen_factory;
 pub mod map;
 pub mod aggregation_min;
@@ -19,15 +21,20 @@ pub mod window_part_sum;
 pub mod window_part_count;
 pub mod window_part_avg;
 
+pub mod sliding_tumbling;
+
 pub use query_gen_factory::{QueryGenFactory, QueryGenStrategy};
 
 use nes_rust_client::prelude::*;
+use rand::rngs::StdRng;
 use crate::stream_schema::StreamSchema;
 
+/// Generates the queries of a test set. All random decisions are drawn from the passed `rng`, so
+/// a `QueryGen` seeded with the same seed generates the same queries.
 pub trait QueryGen {
-    fn new(schema: &StreamSchema) -> Self
+    fn new(schema: &StreamSchema, rng: &mut StdRng) -> Self
     where
         Self: Sized;
     fn origin(&self) -> QueryBuilder;
-    fn other(&self) -> QueryBuilder;
+    fn other(&self, rng: &mut StdRng) -> QueryBuilder;
 }
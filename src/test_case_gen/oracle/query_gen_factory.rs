@@ -1,5 +1,9 @@
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
 use yaml_rust2::Yaml;
 
+use crate::expr_gen::expr_gen::ExprGenConfig;
 use crate::stream_schema::StreamSchema;
 
 use super::aggregation_avg::AggregationAvgQueryGen;
@@ -8,12 +12,15 @@ use super::aggregation_max::AggregationMaxQueryGen;
 use super::aggregation_min::AggregationMinQueryGen;
 use super::aggregation_sum::AggregationSumQueryGen;
 use super::filter::FilterQueryGen;
+use super::filter_rewrite::FilterRewriteQueryGen;
+use super::filter_ternary::FilterTernaryQueryGen;
 use super::key_aggregation_avg::KeyAggregationAvgQueryGen;
 use super::key_aggregation_count::KeyAggregationCountQueryGen;
 use super::key_aggregation_max::KeyAggregationMaxQueryGen;
 use super::key_aggregation_min::KeyAggregationMinQueryGen;
 use super::key_aggregation_sum::KeyAggregationSumQueryGen;
 use super::map::MapQueryGen;
+use super::sliding_tumbling::SlidingTumblingQueryGen;
 use super::window_part_avg::WindowPartAverageQueryGen;
 use super::window_part_count::WindowPartCountQueryGen;
 use super::window_part_max::WindowPartMaxQueryGen;
@@ -21,9 +28,14 @@ use super::window_part_min::WindowPartMinQueryGen;
 use super::window_part_sum::WindowPartSumQueryGen;
 use super::QueryGen;
 
+// TODO: Add windowed join oracles over two logical sources, i.e. the join equals the union of the
+// joins over a predicate partition of one side, and joins commute up to the column order. The
+// `QueryBuilder` of nes-rust-client has no join operator yet, so these queries cannot be built.
 #[derive(Hash, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum QueryGenStrategy {
     Filter,
+    FilterRewrite,
+    FilterTernary,
     Map,
     AggMin,
     AggMax,
@@ -40,12 +52,58 @@ pub enum QueryGenStrategy {
     WinPartSum,
     WinPartCount,
     WinPartAvg,
+    SlidingTumbling,
+}
+
+impl QueryGenStrategy {
+    /// Returns if the oracle compares windows of different sizes or over shifted time stamps. Such
+    /// windows drop different late records, so the oracle is only sound if the data has no records
+    /// beyond the allowed lateness.
+    pub fn compares_window_sizes(&self) -> bool {
+        matches!(
+            self,
+            QueryGenStrategy::WinPartMin
+                | QueryGenStrategy::WinPartMax
+                | QueryGenStrategy::WinPartSum
+                | QueryGenStrategy::WinPartCount
+                | QueryGenStrategy::WinPartAvg
+                | QueryGenStrategy::SlidingTumbling
+        )
+    }
+
+    /// Fields that every logical source needs for the queries of the oracle.
+    pub fn required_fields(&self) -> &'static [&'static str] {
+        match self {
+            QueryGenStrategy::Filter
+            | QueryGenStrategy::FilterRewrite
+            | QueryGenStrategy::FilterTernary
+            | QueryGenStrategy::Map => &[],
+            QueryGenStrategy::AggMin
+            | QueryGenStrategy::AggMax
+            | QueryGenStrategy::AggSum
+            | QueryGenStrategy::AggCount
+            | QueryGenStrategy::AggAvg
+            | QueryGenStrategy::WinPartMin
+            | QueryGenStrategy::WinPartMax
+            | QueryGenStrategy::WinPartSum
+            | QueryGenStrategy::WinPartCount
+            | QueryGenStrategy::WinPartAvg
+            | QueryGenStrategy::SlidingTumbling => &["ts"],
+            QueryGenStrategy::KeyAggMin
+            | QueryGenStrategy::KeyAggMax
+            | QueryGenStrategy::KeyAggSum
+            | QueryGenStrategy::KeyAggCount
+            | QueryGenStrategy::KeyAggAvg => &["ts", "key"],
+        }
+    }
 }
 
 impl Into<Yaml> for &QueryGenStrategy {
     fn into(self) -> Yaml {
         let str = match self {
             QueryGenStrategy::Filter => "Filter",
+            QueryGenStrategy::FilterRewrite => "FilterRewrite",
+            QueryGenStrategy::FilterTernary => "FilterTernary",
             QueryGenStrategy::Map => "Map",
             QueryGenStrategy::AggMin => "AggMin",
             QueryGenStrategy::AggMax => "AggMax",
@@ -62,6 +120,7 @@ impl Into<Yaml> for &QueryGenStrategy {
             QueryGenStrategy::WinPartSum => "WinPartSum",
             QueryGenStrategy::WinPartCount => "WinPartCount",
             QueryGenStrategy::WinPartAvg => "WinPartAvg",
+            QueryGenStrategy::SlidingTumbling => "SlidingTumbling",
         };
         Yaml::String(str.to_string())
     }
@@ -72,88 +131,115 @@ impl TryFrom<&Yaml> for QueryGenStrategy {
 
     fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
         if let Yaml::String(s) = value {
-            match s.as_str() {
-                "Filter" => Ok(QueryGenStrategy::Filter),
-                "Map" => Ok(QueryGenStrategy::Map),
-                "AggMin" => Ok(QueryGenStrategy::AggMin),
-                "AggMax" => Ok(QueryGenStrategy::AggMax),
-                "AggSum" => Ok(QueryGenStrategy::AggSum),
-                "AggCount" => Ok(QueryGenStrategy::AggCount),
-                "AggAvg" => Ok(QueryGenStrategy::AggAvg),
-                "KeyAggMin" => Ok(QueryGenStrategy::KeyAggMin),
-                "KeyAggMax" => Ok(QueryGenStrategy::KeyAggMax),
-                "KeyAggSum" => Ok(QueryGenStrategy::KeyAggSum),
-                "KeyAggCount" => Ok(QueryGenStrategy::KeyAggCount),
-                "KeyAggAvg" => Ok(QueryGenStrategy::KeyAggAvg),
-                "WinPartMin" => Ok(QueryGenStrategy::WinPartMin),
-                "WinPartMax" => Ok(QueryGenStrategy::WinPartMax),
-                "WinPartSum" => Ok(QueryGenStrategy::WinPartSum),
-                "WinPartCount" => Ok(QueryGenStrategy::WinPartCount),
-                "WinPartAvg" => Ok(QueryGenStrategy::WinPartAvg),
-                _ => Err(format!("Unknown strategy: {}", s)),
-            }
+            s.parse()
         } else {
             Err("Expected a YAML string".to_string())
         }
     }
 }
 
+impl FromStr for QueryGenStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Filter" => Ok(QueryGenStrategy::Filter),
+            "FilterRewrite" => Ok(QueryGenStrategy::FilterRewrite),
+            "FilterTernary" => Ok(QueryGenStrategy::FilterTernary),
+            "Map" => Ok(QueryGenStrategy::Map),
+            "AggMin" => Ok(QueryGenStrategy::AggMin),
+            "AggMax" => Ok(QueryGenStrategy::AggMax),
+            "AggSum" => Ok(QueryGenStrategy::AggSum),
+            "AggCount" => Ok(QueryGenStrategy::AggCount),
+            "AggAvg" => Ok(QueryGenStrategy::AggAvg),
+            "KeyAggMin" => Ok(QueryGenStrategy::KeyAggMin),
+            "KeyAggMax" => Ok(QueryGenStrategy::KeyAggMax),
+            "KeyAggSum" => Ok(QueryGenStrategy::KeyAggSum),
+            "KeyAggCount" => Ok(QueryGenStrategy::KeyAggCount),
+            "KeyAggAvg" => Ok(QueryGenStrategy::KeyAggAvg),
+            "WinPartMin" => Ok(QueryGenStrategy::WinPartMin),
+            "WinPartMax" => Ok(QueryGenStrategy::WinPartMax),
+            "WinPartSum" => Ok(QueryGenStrategy::WinPartSum),
+            "WinPartCount" => Ok(QueryGenStrategy::WinPartCount),
+            "WinPartAvg" => Ok(QueryGenStrategy::WinPartAvg),
+            "SlidingTumbling" => Ok(QueryGenStrategy::SlidingTumbling),
+            _ => Err(format!("Unknown strategy: {}", s)),
+        }
+    }
+}
+
 pub struct QueryGenFactory {
-    predicate_depth: u32,
+    expr_config: ExprGenConfig,
 }
 
 impl QueryGenFactory {
-    pub fn new(predicate_depth: u32) -> Self {
-        Self { predicate_depth }
+    pub fn new(expr_config: ExprGenConfig) -> Self {
+        Self { expr_config }
     }
 
     pub fn create_query_gen(
         &self,
         schema: &StreamSchema,
         strat: QueryGenStrategy,
+        rng: &mut StdRng,
     ) -> Box<dyn QueryGen> {
         match strat {
-            QueryGenStrategy::Filter => {
-                Box::new(FilterQueryGen::new(schema).with_predicate_depth(self.predicate_depth))
-            }
+            QueryGenStrategy::Filter => Box::new(
+                FilterQueryGen::new(schema, rng).with_expr_config(self.expr_config.clone()),
+            ),
+            QueryGenStrategy::FilterRewrite => Box::new(
+                FilterRewriteQueryGen::new(schema, rng).with_expr_config(self.expr_config.clone()),
+            ),
+            QueryGenStrategy::FilterTernary => Box::new(
+                FilterTernaryQueryGen::new(schema, rng).with_expr_config(self.expr_config.clone()),
+            ),
             QueryGenStrategy::Map => {
-                Box::new(MapQueryGen::new(schema).with_predicate_depth(self.predicate_depth))
+                Box::new(MapQueryGen::new(schema, rng).with_expr_config(self.expr_config.clone()))
             }
             QueryGenStrategy::AggMin => Box::new(
-                AggregationMinQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                AggregationMinQueryGen::new(schema, rng).with_expr_config(self.expr_config.clone()),
             ),
             QueryGenStrategy::AggMax => Box::new(
-                AggregationMaxQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                AggregationMaxQueryGen::new(schema, rng).with_expr_config(self.expr_config.clone()),
             ),
             QueryGenStrategy::AggSum => Box::new(
-                AggregationSumQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                AggregationSumQueryGen::new(schema, rng).with_expr_config(self.expr_config.clone()),
             ),
             QueryGenStrategy::AggCount => Box::new(
-                AggregationCountQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                AggregationCountQueryGen::new(schema, rng)
+                    .with_expr_config(self.expr_config.clone()),
             ),
             QueryGenStrategy::AggAvg => Box::new(
-                AggregationAvgQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                AggregationAvgQueryGen::new(schema, rng).with_expr_config(self.expr_config.clone()),
             ),
             QueryGenStrategy::KeyAggMin => Box::new(
-                KeyAggregationMinQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                KeyAggregationMinQueryGen::new(schema, rng)
+                    .with_expr_config(self.expr_config.clone()),
             ),
             QueryGenStrategy::KeyAggMax => Box::new(
-                KeyAggregationMaxQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                KeyAggregationMaxQueryGen::new(schema, rng)
+                    .with_expr_config(self.expr_config.clone()),
             ),
             QueryGenStrategy::KeyAggSum => Box::new(
-                KeyAggregationSumQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                KeyAggregationSumQueryGen::new(schema, rng)
+                    .with_expr_config(self.expr_config.clone()),
             ),
             QueryGenStrategy::KeyAggCount => Box::new(
-                KeyAggregationCountQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                KeyAggregationCountQueryGen::new(schema, rng)
+                    .with_expr_config(self.expr_config.clone()),
             ),
             QueryGenStrategy::KeyAggAvg => Box::new(
-                KeyAggregationAvgQueryGen::new(schema).with_predicate_depth(self.predicate_depth),
+                KeyAggregationAvgQueryGen::new(schema, rng)
+                    .with_expr_config(self.expr_config.clone()),
             ),
-            QueryGenStrategy::WinPartMin => Box::new(WindowPartMinQueryGen::new(schema)),
-            QueryGenStrategy::WinPartMax => Box::new(WindowPartMaxQueryGen::new(schema)),
-            QueryGenStrategy::WinPartSum => Box::new(WindowPartSumQueryGen::new(schema)),
-            QueryGenStrategy::WinPartCount => Box::new(WindowPartCountQueryGen::new(schema)),
-            QueryGenStrategy::WinPartAvg => Box::new(WindowPartAverageQueryGen::new(schema)),
+            QueryGenStrategy::WinPartMin => Box::new(WindowPartMinQueryGen::new(schema, rng)),
+            QueryGenStrategy::WinPartMax => Box::new(WindowPartMaxQueryGen::new(schema, rng)),
+            QueryGenStrategy::WinPartSum => Box::new(WindowPartSumQueryGen::new(schema, rng)),
+            QueryGenStrategy::WinPartCount => Box::new(WindowPartCountQueryGen::new(schema, rng)),
+            QueryGenStrategy::WinPartAvg => Box::new(WindowPartAverageQueryGen::new(schema, rng)),
+            QueryGenStrategy::SlidingTumbling => {
+                Box::new(SlidingTumblingQueryGen::new(schema, rng))
+            }
         }
     }
 }
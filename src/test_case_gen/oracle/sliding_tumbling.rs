@@ -0,0 +1,69 @@
+use crate::{
+    stream_gen::LogicalSource,
+    stream_schema::StreamSchema,
+    test_case_gen::util::{get_random_field_name, get_ts_field, random_source},
+    test_case_gen::window::Window,
+};
+use nes_rust_client::prelude::*;
+use rand::{rngs::StdRng, Rng};
+
+use super::QueryGen;
+
+/// A sliding window whose slide equals its size is the tumbling window of the same size.
+pub struct SlidingTumblingQueryGen {
+    // dynamic values
+    source: LogicalSource,
+    size: u32,
+    ts_field: Field,
+    key: Option<String>,
+    aggregation: Aggregation,
+}
+
+impl QueryGen for SlidingTumblingQueryGen {
+    fn new(schema: &StreamSchema, rng: &mut StdRng) -> Self {
+        let source = random_source(rng, schema);
+        let size = rng.gen_range(200..20_000);
+        let ts_field = get_ts_field(&source);
+        let has_key = source.fields.iter().any(|field| field.name() == "key");
+        let key = (has_key && rng.gen_bool(0.5)).then(|| "key".to_string());
+        let field_name = get_random_field_name(rng, &source);
+        let aggregation = match rng.gen_range(0..5) {
+            0 => Aggregation::min(field_name),
+            1 => Aggregation::max(field_name),
+            2 => Aggregation::sum(field_name),
+            3 => Aggregation::average(field_name),
+            _ => Aggregation::count(),
+        };
+        Self {
+            source,
+            size,
+            ts_field,
+            key,
+            aggregation,
+        }
+    }
+
+    fn origin(&self) -> QueryBuilder {
+        let window = Window::Tumbling { size: self.size };
+        self.aggregate(window)
+    }
+
+    fn other(&self, _rng: &mut StdRng) -> QueryBuilder {
+        let window = Window::Sliding {
+            size: self.size,
+            slide: self.size,
+        };
+        self.aggregate(window)
+    }
+}
+
+impl SlidingTumblingQueryGen {
+    fn aggregate(&self, window: Window) -> QueryBuilder {
+        window.aggregate(
+            QueryBuilder::from_source(&self.source.source_name),
+            &self.ts_field,
+            self.key.as_deref(),
+            [self.aggregation.clone()],
+        )
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
     },
 };
 use nes_rust_client::prelude::*;
+use rand::rngs::StdRng;
 
 use super::QueryGen;
 
@@ -18,10 +19,10 @@ pub struct WindowPartAverageQueryGen {
 }
 
 impl QueryGen for WindowPartAverageQueryGen {
-    fn new(schema: &StreamSchema) -> Self {
-        let source = random_source(&schema);
-        let outer_window = generate_outer_window();
-        let agg_field_name = get_random_field_name(&source);
+    fn new(schema: &StreamSchema, rng: &mut StdRng) -> Self {
+        let source = random_source(rng, schema);
+        let outer_window = generate_outer_window(rng);
+        let agg_field_name = get_random_field_name(rng, &source);
         Self {
             source,
             outer_window,
@@ -36,8 +37,8 @@ impl QueryGen for WindowPartAverageQueryGen {
             .apply([Aggregation::average(self.agg_field_name.clone())])
     }
 
-    fn other(&self) -> QueryBuilder {
-        let inner_window = generate_inner_window(&self.outer_window);
+    fn other(&self, rng: &mut StdRng) -> QueryBuilder {
+        let inner_window = generate_inner_window(rng, &self.outer_window);
         let union_expr = ExprBuilder::field("sum")
             .div(ExprBuilder::field("count"))
             .build_arith()
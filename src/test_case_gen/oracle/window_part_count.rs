@@ -6,6 +6,7 @@ use crate::{
     },
 };
 use nes_rust_client::prelude::*;
+use rand::rngs::StdRng;
 
 use super::QueryGen;
 
@@ -18,10 +19,10 @@ pub struct WindowPartCountQueryGen {
 }
 
 impl QueryGen for WindowPartCountQueryGen {
-    fn new(schema: &StreamSchema) -> Self {
-        let source = random_source(&schema);
-        let outer_window = generate_outer_window();
-        let agg_field_name = get_random_field_name(&source);
+    fn new(schema: &StreamSchema, rng: &mut StdRng) -> Self {
+        let source = random_source(rng, schema);
+        let outer_window = generate_outer_window(rng);
+        let agg_field_name = get_random_field_name(rng, &source);
         Self {
             source,
             outer_window,
@@ -36,8 +37,8 @@ impl QueryGen for WindowPartCountQueryGen {
             .apply([Aggregation::count()])
     }
 
-    fn other(&self) -> QueryBuilder {
-        let inner_window = generate_inner_window(&self.outer_window);
+    fn other(&self, rng: &mut StdRng) -> QueryBuilder {
+        let inner_window = generate_inner_window(rng, &self.outer_window);
         QueryBuilder::from_source(&self.source.source_name)
             .window(inner_window.clone())
             .apply([Aggregation::count()])
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
 use yaml_rust2::Yaml;
 
@@ -33,6 +33,14 @@ impl TryFrom<&Yaml> for TestCaseId {
         let Yaml::String(str) = value else {
             return Err("Failed to parse TestCase: Expected Yaml::String.".into());
         };
+        str.parse()
+    }
+}
+
+impl FromStr for TestCaseId {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
         if str == "Origin" {
             return Ok(TestCaseId::Origin);
         }
@@ -1,14 +1,13 @@
-use std::io::Write;
-use std::{
-    fs,
-    path::{self, Path, PathBuf},
-};
+use std::path::{self, Path, PathBuf};
 
 use nes_rust_client::query::{stringify::stringify_query, Query};
-use yaml_rust2::YamlLoader;
-use yaml_rust2::{yaml::Hash, Yaml, YamlEmitter};
+use yaml_rust2::{yaml::Hash, Yaml};
 
-use crate::LancerConfig;
+use crate::{
+    error::AchillesError,
+    yaml_util::{load_yaml_array, store_yaml_array},
+    LancerConfig,
+};
 
 use super::oracle::QueryGenStrategy;
 use super::query_id::TestCaseId;
@@ -17,6 +16,8 @@ use super::query_id::TestCaseId;
 #[derive(Debug, Clone)]
 pub struct TestSet {
     pub id: u32,
+    /// Seed the queries of this test set were generated with.
+    pub seed: u64,
     pub strategy: QueryGenStrategy,
     pub origin: TestCase,
     pub others: Vec<TestCase>,
@@ -71,38 +72,27 @@ impl TestCase {
 
 // Yaml and that jazz
 /// Writes the `TestCase`s to the in `LancerConfig` specified location.
-pub fn write_test_sets_to_file(test_run_id: u32, config: &LancerConfig, test_cases: &[TestSet]) {
-    let path = config.path_config.test_sets(test_run_id);
-    let yaml_test_cases: Vec<Yaml> = test_cases
-        .iter()
-        .map(|test_case| test_case.into())
-        .collect();
-    let yaml_arr = Yaml::Array(yaml_test_cases);
-    let mut out_str = String::new();
-    let mut emitter = YamlEmitter::new(&mut out_str);
-    emitter.dump(&yaml_arr).unwrap();
-    let mut file = fs::File::create(path).expect("coordinator.yml has to be created!");
-    write!(file, "{out_str}").unwrap();
+pub fn write_test_sets_to_file(
+    test_run_id: u32,
+    config: &LancerConfig,
+    test_cases: &[TestSet],
+) -> Result<(), AchillesError> {
+    store_yaml_array(&config.path_config.test_sets(test_run_id), test_cases)
 }
 
 /// Reads the `TestCase`s to the in `LancerConfig` specified location.
-pub fn read_test_sets_to_file(test_run_id: u32, config: &LancerConfig) -> Vec<TestSet> {
-    let path = config.path_config.test_sets(test_run_id);
-    let content = fs::read_to_string(path).expect("Should have been able to read the file!");
-    let docs = YamlLoader::load_from_str(&content).expect("Should have been able to parse Yaml.");
-    let doc = &docs.first().expect("Should have one element");
-    let Yaml::Array(arr) = doc else {
-        panic!("Should have been able to parse Yaml::Array.")
-    };
-    arr.iter()
-        .map(|yaml_obj| yaml_obj.try_into().expect("Should not fail to parse Yaml."))
-        .collect()
+pub fn read_test_sets_to_file(
+    test_run_id: u32,
+    config: &LancerConfig,
+) -> Result<Vec<TestSet>, AchillesError> {
+    load_yaml_array(&config.path_config.test_sets(test_run_id))
 }
 
 impl Into<Yaml> for &TestSet {
     fn into(self) -> Yaml {
         let mut map: Hash = Hash::new();
         map.insert(Yaml::String("id".into()), Yaml::Integer(self.id as i64));
+        map.insert(Yaml::String("seed".into()), Yaml::Integer(self.seed as i64));
         map.insert(Yaml::String("strategy".into()), (&self.strategy).into());
         map.insert(Yaml::String("origin".into()), (&self.origin).into());
         let others: Vec<Yaml> = self.others.iter().map(|props| props.into()).collect();
@@ -117,6 +107,9 @@ impl TryFrom<&Yaml> for TestSet {
         let Some(id) = value["id"].as_i64() else {
             return Err("Should be able to parse id field.".to_string());
         };
+        let Some(seed) = value["seed"].as_i64() else {
+            return Err("Should be able to parse seed field.".to_string());
+        };
         let strategy = (&value["strategy"]).try_into()?;
         let origin = (&value["origin"]).try_into()?;
         let Yaml::Array(arr) = &value["others"] else {
@@ -128,6 +121,7 @@ impl TryFrom<&Yaml> for TestSet {
             .collect::<Result<Vec<_>, Self::Error>>()?;
         Ok(Self {
             id: id as u32,
+            seed: seed as u64,
             strategy,
             origin,
             others,
@@ -188,7 +182,7 @@ mod yaml_tests {
     };
     use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
 
-    use crate::test_case_gen::test_case::TestCase;
+    use crate::test_case_gen::{oracle::QueryGenStrategy, test_case::TestCase};
 
     use super::TestSet;
 
@@ -263,6 +257,8 @@ mod yaml_tests {
             .collect();
         let test_set = TestSet {
             id: 42,
+            seed: u64::MAX,
+            strategy: QueryGenStrategy::Filter,
             origin,
             others,
         };
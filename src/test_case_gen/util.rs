@@ -1,4 +1,4 @@
-use crate::expr_gen::expr_gen::generate_logical_expr;
+use crate::expr_gen::expr_gen::{generate_logical_expr, ExprGenConfig};
 use nes_rust_client::expression::binary_expression::BinaryExpr;
 use nes_rust_client::expression::expression::RawExpr;
 use nes_rust_client::expression::Field;
@@ -14,7 +14,9 @@ use rand::Rng;
 use crate::stream_gen::LogicalSource;
 use crate::stream_schema::StreamSchema;
 
-fn has_literal_literal(logical_expr: &LogicalExpr) -> bool {
+use super::window::Window;
+
+pub fn has_literal_literal(logical_expr: &LogicalExpr) -> bool {
     let parents = logical_expr.0.leaf_parents();
     for expr in parents {
         let RawExpr::Binary(BinaryExpr { lhs, rhs, .. }) = expr else {
@@ -45,9 +47,13 @@ fn is_literal(logical_expr: &LogicalExpr) -> bool {
     false
 }
 
-pub fn generate_predicate(depth: u32, fields: &[Field]) -> LogicalExpr {
+pub fn generate_predicate(
+    rng: &mut impl Rng,
+    config: &ExprGenConfig,
+    fields: &[Field],
+) -> LogicalExpr {
     loop {
-        let Ok(p) = generate_logical_expr(depth, &fields) else {
+        let Ok(p) = generate_logical_expr(rng, config.predicate_depth, fields, config) else {
             continue;
         };
         if has_literal_literal(&p) || is_literal(&p) || contains_boolean_literal(&p) {
@@ -61,28 +67,38 @@ pub fn generate_predicate(depth: u32, fields: &[Field]) -> LogicalExpr {
     }
 }
 
-pub fn random_source(schema: &StreamSchema) -> LogicalSource {
-    use rand::seq::SliceRandom;
-    schema
-        .logical_sources
-        .choose(&mut rand::thread_rng())
-        .unwrap()
-        .clone()
+pub fn random_source(rng: &mut impl Rng, schema: &StreamSchema) -> LogicalSource {
+    schema.logical_sources.choose(rng).unwrap().clone()
 }
 
-// TODO: Actually implement this function!!!
-pub fn generate_window_descriptor() -> WindowDescriptor {
-    let mut rng = rand::thread_rng();
-    let dur = rng.gen_range(200..20_000);
-    WindowDescriptor::TumblingWindow {
-        duration: query::time::Duration::from_milliseconds(dur),
-        time_character: query::time::TimeCharacteristic::EventTime {
-            field_name: "ts".to_string(),
-            unit: query::time::TimeUnit::Milliseconds,
-        },
+// TODO: Assign the watermark strategy with `TimeStampDisorder::allowed_lateness` to the generated
+// windows. nes-rust-client cannot assign watermarks yet, so the allowed lateness has to match the
+// default of NebulaStream and oracles that compare window sizes are skipped if records are late.
+/// Generates a tumbling window or a sliding window of up to 4 slides.
+pub fn generate_window(rng: &mut impl Rng) -> Window {
+    if rng.gen_bool(0.5) {
+        Window::Tumbling {
+            size: rng.gen_range(200..20_000),
+        }
+    } else {
+        let slide = rng.gen_range(200..5_000);
+        Window::Sliding {
+            size: slide * rng.gen_range(1..=4),
+            slide,
+        }
     }
 }
 
+/// returns the `ts` field of the source with its type
+pub fn get_ts_field(source: &LogicalSource) -> Field {
+    source
+        .fields
+        .iter()
+        .find(|field| field.name() == "ts")
+        .expect("Windowed oracles require a ts field.")
+        .clone()
+}
+
 fn get_window_dur(window: &WindowDescriptor) -> Option<u32> {
     match window {
         WindowDescriptor::TumblingWindow {
@@ -94,8 +110,7 @@ fn get_window_dur(window: &WindowDescriptor) -> Option<u32> {
 
 const BASE_DUR: u32 = 2 * 3 * 4 * 5;
 
-pub fn generate_outer_window() -> WindowDescriptor {
-    let mut rng = rand::thread_rng();
+pub fn generate_outer_window(rng: &mut impl Rng) -> WindowDescriptor {
     let outer_dur = rng.gen_range(20..=200) * BASE_DUR;
     WindowDescriptor::TumblingWindow {
         duration: query::time::Duration::from_milliseconds(outer_dur),
@@ -106,11 +121,13 @@ pub fn generate_outer_window() -> WindowDescriptor {
     }
 }
 
-pub fn generate_inner_window(outer_window: &WindowDescriptor) -> WindowDescriptor {
-    let mut rng = rand::thread_rng();
+pub fn generate_inner_window(
+    rng: &mut impl Rng,
+    outer_window: &WindowDescriptor,
+) -> WindowDescriptor {
     let outer_dur = get_window_dur(outer_window).expect("Window must have a duration");
     let divisor = [1, 2, 3, 4, 5]
-        .choose(&mut rng)
+        .choose(rng)
         .copied()
         .expect("Must find Divisor");
     let inner_dur = outer_dur / divisor;
@@ -124,14 +141,13 @@ pub fn generate_inner_window(outer_window: &WindowDescriptor) -> WindowDescripto
 }
 
 /// returns a random field that is not the ts
-pub fn get_random_field_name(source: &LogicalSource) -> String {
+pub fn get_random_field_name(rng: &mut impl Rng, source: &LogicalSource) -> String {
     use rand::seq::IteratorRandom;
-    let mut rng = rand::thread_rng();
     let field = source
         .fields
         .iter()
         .filter(|field| field.name() != "ts" && field.name() != "key")
-        .choose(&mut rng)
+        .choose(rng)
         .expect("Expect to get random field.");
     field.name().to_string()
 }
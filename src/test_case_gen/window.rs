@@ -0,0 +1,239 @@
+//! Event time windows of the generated queries. nes-rust-client only offers tumbling windows, so
+//! sliding windows are lowered to unions of tumbling windows over shifted time stamps.
+use nes_rust_client::expression::binary_expression::{BinaryExpr, BinaryOp};
+use nes_rust_client::expression::expression::RawExpr;
+use nes_rust_client::expression::literal::Literal;
+use nes_rust_client::expression::{ArithmeticExpr, Field, LogicalExpr};
+use nes_rust_client::prelude::*;
+use nes_rust_client::query;
+use nes_rust_client::query::window::window_descriptor::WindowDescriptor;
+use nes_types::{IntType, NesType};
+
+const BOUND_TYPE: NesType = NesType::Int(IntType::Unsigned64);
+
+/// Window over the `ts` field in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Tumbling {
+        size: u32,
+    },
+    /// Windows of `size` that start every `slide`. `size` must be a multiple of `slide`.
+    Sliding {
+        size: u32,
+        slide: u32,
+    },
+}
+
+impl Window {
+    pub fn size(&self) -> u32 {
+        match self {
+            Window::Tumbling { size } | Window::Sliding { size, .. } => *size,
+        }
+    }
+
+    /// Aggregates `input` per window and optionally per `key`. Like `QueryBuilder::window` the
+    /// result has the fields `start`, `end`, the key and the aggregations.
+    ///
+    /// The `i`th of the `size / slide` branches of a sliding window shifts the time stamps by
+    /// `size - i * slide`, so its tumbling windows of `size` start at `i * slide` modulo `size`,
+    /// and shifts `start` and `end` back. Windows that would start before 0 are dropped, so
+    /// `ts_field` must not be negative.
+    pub fn aggregate<const N: usize>(
+        &self,
+        input: QueryBuilder,
+        ts_field: &Field,
+        key: Option<&str>,
+        aggregations: [Aggregation; N],
+    ) -> QueryBuilder {
+        let (size, slide) = match *self {
+            Window::Tumbling { size } => return apply(input, size, key, aggregations),
+            Window::Sliding { size, slide } => (size, slide),
+        };
+        let ts_type = ts_field.data_type();
+        (0..size / slide)
+            .map(|i| {
+                let offset = size - i * slide;
+                let shifted_ts = binary(
+                    RawExpr::Field(ts_field.clone()),
+                    BinaryOp::Add,
+                    literal(offset, ts_type),
+                    ts_type,
+                );
+                let shifted = input.clone().map("ts", ArithmeticExpr(shifted_ts));
+                let complete = binary(
+                    bound("start"),
+                    BinaryOp::GreaterEquals,
+                    literal(offset, BOUND_TYPE),
+                    NesType::Bool,
+                );
+                apply(shifted, size, key, aggregations.clone())
+                    .filter(LogicalExpr(complete))
+                    .map("start", shift_back("start", offset))
+                    .map("end", shift_back("end", offset))
+            })
+            .reduce(|query, branch| query.union(branch))
+            .expect("A sliding window must not be smaller than its slide.")
+    }
+
+    /// Aggregates partial results of `aggregate` whose `start` is renamed to `ts` per window they
+    /// belong to. Sliding windows are aggregated by their start, which is a multiple of `slide`.
+    pub fn reaggregate<const N: usize>(
+        &self,
+        input: QueryBuilder,
+        key: Option<&str>,
+        aggregations: [Aggregation; N],
+    ) -> QueryBuilder {
+        match *self {
+            Window::Tumbling { size } => apply(input, size, key, aggregations),
+            Window::Sliding { size, slide } => {
+                let end = binary(
+                    bound("start"),
+                    BinaryOp::Add,
+                    literal(size, BOUND_TYPE),
+                    BOUND_TYPE,
+                );
+                apply(input, slide, key, aggregations).map("end", ArithmeticExpr(end))
+            }
+        }
+    }
+}
+
+pub fn tumbling_window(size: u32) -> WindowDescriptor {
+    WindowDescriptor::TumblingWindow {
+        duration: query::time::Duration::from_milliseconds(size),
+        time_character: query::time::TimeCharacteristic::EventTime {
+            field_name: "ts".to_string(),
+            unit: query::time::TimeUnit::Milliseconds,
+        },
+    }
+}
+
+fn apply<const N: usize>(
+    input: QueryBuilder,
+    size: u32,
+    key: Option<&str>,
+    aggregations: [Aggregation; N],
+) -> QueryBuilder {
+    let windowed = input.window(tumbling_window(size));
+    match key {
+        Some(key) => windowed.by_key(key).apply(aggregations),
+        None => windowed.apply(aggregations),
+    }
+}
+
+fn shift_back(name: &str, offset: u32) -> ArithmeticExpr {
+    ArithmeticExpr(binary(
+        bound(name),
+        BinaryOp::Subtract,
+        literal(offset, BOUND_TYPE),
+        BOUND_TYPE,
+    ))
+}
+
+fn bound(name: &str) -> RawExpr {
+    RawExpr::Field(Field::typed(name, BOUND_TYPE))
+}
+
+fn literal(value: u32, data_type: NesType) -> RawExpr {
+    RawExpr::Literal(Literal::typed(value.to_string(), data_type))
+}
+
+fn binary(lhs: RawExpr, operator: BinaryOp, rhs: RawExpr, data_type: NesType) -> RawExpr {
+    RawExpr::Binary(BinaryExpr {
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+        data_type,
+        operator,
+    })
+}
+
+#[cfg(test)]
+mod window_tests {
+    use std::collections::HashMap;
+
+    use nes_rust_client::expression::Field;
+    use nes_rust_client::prelude::{Aggregation, QueryBuilder, Sink};
+    use nes_types::{IntType, NesType};
+
+    use crate::reference::interpreter::{execute, RefField, Table};
+    use crate::reference::plan::RefOperator;
+    use crate::reference::value::Value;
+
+    use super::Window;
+
+    const TS: NesType = NesType::Int(IntType::Unsigned32);
+    const X: NesType = NesType::Int(IntType::Signed64);
+
+    fn run(window: Window, key: Option<&str>) -> Vec<Vec<Value>> {
+        let rows = [
+            (0, 1, 5),
+            (50, 2, -3),
+            (120, 1, 7),
+            (150, 1, 1),
+            (230, 2, 4),
+        ]
+        .into_iter()
+        .map(|(ts, key, x)| vec![Value::Int(ts), Value::Int(key), Value::Int(x)])
+        .collect();
+        let field = |name: &str, data_type| RefField {
+            name: name.into(),
+            data_type,
+        };
+        let table = Table {
+            source_name: "src".into(),
+            fields: vec![field("ts", TS), field("key", X), field("x", X)],
+            rows,
+        };
+        let query = window
+            .aggregate(
+                QueryBuilder::from_source("src"),
+                &Field::typed("ts", TS),
+                key,
+                [Aggregation::sum("x".to_string())],
+            )
+            .sink(Sink::csv_file("out.csv", false));
+        let plan = RefOperator::try_from(&query).unwrap();
+        let sources = HashMap::from([("src".to_string(), table)]);
+        let mut rows = execute(&plan, &sources).unwrap().rows;
+        rows.sort_by_key(|row| {
+            row.iter()
+                .map(|value| value.as_i128().unwrap())
+                .collect::<Vec<_>>()
+        });
+        rows
+    }
+
+    fn ints(rows: &[&[i128]]) -> Vec<Vec<Value>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&value| Value::Int(value)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn sliding_window_with_slide_equal_to_size_is_tumbling() {
+        for key in [None, Some("key")] {
+            let tumbling = run(Window::Tumbling { size: 100 }, key);
+            let sliding = run(
+                Window::Sliding {
+                    size: 100,
+                    slide: 100,
+                },
+                key,
+            );
+            assert_eq!(sliding, tumbling);
+        }
+    }
+
+    #[test]
+    fn sliding_windows_overlap() {
+        let result = run(
+            Window::Sliding {
+                size: 200,
+                slide: 100,
+            },
+            None,
+        );
+        let expected = ints(&[&[0, 200, 10], &[100, 300, 12], &[200, 400, 4]]);
+        assert_eq!(result, expected);
+    }
+}
@@ -1,8 +1,10 @@
-use std::{fs, io::Write, path::Path};
+use std::{fs, path::Path};
 
 use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
 
-pub fn store_yaml_array<T>(path: &Path, values: &[T])
+use crate::error::AchillesError;
+
+pub fn store_yaml_array<T>(path: &Path, values: &[T]) -> Result<(), AchillesError>
 where
     for<'a> &'a T: Into<Yaml>,
 {
@@ -10,25 +12,32 @@ where
     let yaml_arr = Yaml::Array(yaml_test_cases);
     let mut out_str = String::new();
     let mut emitter = YamlEmitter::new(&mut out_str);
-    emitter.dump(&yaml_arr).unwrap();
-    let mut file = fs::File::create(path).expect("coordinator.yml has to be created!");
-    write!(file, "{out_str}").unwrap();
+    emitter
+        .dump(&yaml_arr)
+        .map_err(|err| AchillesError::yaml(path, err))?;
+    fs::write(path, out_str).map_err(|err| AchillesError::io(path, err))
+}
+
+/// Reads the first yaml document in `path`.
+pub fn load_yaml_doc(path: &Path) -> Result<Yaml, AchillesError> {
+    let content = fs::read_to_string(path).map_err(|err| AchillesError::io(path, err))?;
+    let docs = YamlLoader::load_from_str(&content).map_err(|err| AchillesError::yaml(path, err))?;
+    docs.into_iter()
+        .next()
+        .ok_or_else(|| AchillesError::yaml(path, "Yaml doc should exist!"))
 }
 
-pub fn load_yaml_array<T>(path: &Path) -> Result<Vec<T>, String>
+pub fn load_yaml_array<T>(path: &Path) -> Result<Vec<T>, AchillesError>
 where
     for<'a> T: TryFrom<&'a Yaml, Error = String>,
 {
-    let content = fs::read_to_string(path).expect("Should have been able to read the file!");
-    let docs = YamlLoader::load_from_str(&content).expect("Should have been able to parse Yaml.");
-    let Some(doc) = &docs.first() else {
-        return Err(format!("Yaml doc should exist!"));
-    };
-    let Yaml::Array(arr) = doc else {
-        return Err(format!("Should have been able to parse Yaml::Array."));
+    let Yaml::Array(arr) = load_yaml_doc(path)? else {
+        return Err(AchillesError::yaml(
+            path,
+            "Should have been able to parse Yaml::Array.",
+        ));
     };
     arr.iter()
-        .map(|yaml_obj| T::try_from(yaml_obj))
-        .collect::<Result<Vec<T>, String>>()
+        .map(|yaml_obj| T::try_from(yaml_obj).map_err(|err| AchillesError::yaml(path, err)))
+        .collect()
 }
-
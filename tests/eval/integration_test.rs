@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use achilles::eval::evaluator::*;
+use achilles::eval::{diff_report::diff_files, evaluator::*, tolerance::Tolerance};
 
 macro_rules! eval_test {
     ($($name:ident: ($other_path:expr, $expected:expr),)*) => {
@@ -22,4 +22,151 @@ eval_test!{
     lines_missing_test: ("./tests/eval/assets/default_lines_missing.csv", ResultRelation::Diff),
     lines_double_test: ("./tests/eval/assets/default_lines_double.csv", ResultRelation::Diff),
     missing_and_double_test: ("./tests/eval/assets/default_missing_and_double.csv", ResultRelation::Diff),
+    value_changed_test: ("./tests/eval/assets/default_value_changed.csv", ResultRelation::Diff),
+    columns_permuted_test: ("./tests/eval/assets/default_columns_permuted.csv", ResultRelation::Reordered),
+}
+
+#[test]
+fn columns_renamed() {
+    let default_path = Path::new("./tests/eval/assets/default.csv");
+    let other_path = Path::new("./tests/eval/assets/default_columns_renamed.csv");
+    let err = compare_files(default_path, other_path).unwrap_err().to_string();
+    assert!(err.contains("ts is missing in the other result"), "{err}");
+    assert!(err.contains("start is missing in the origin result"), "{err}");
+
+    let options = CompareOptions {
+        aliases: ColumnAliases::from_iter([("start", "ts")]),
+        ..Default::default()
+    };
+    let res = compare_files_with(default_path, other_path, &options).unwrap();
+    assert_eq!(ResultRelation::Equal, res.relation);
+    assert_eq!(None, res.max_error);
+}
+
+#[test]
+fn diff_missing_and_double() {
+    let default_path = Path::new("./tests/eval/assets/default.csv");
+    let other_path = Path::new("./tests/eval/assets/default_missing_and_double.csv");
+    let diff = diff_files(default_path, other_path, &CompareOptions::default()).unwrap();
+    assert_eq!(diff.missing, vec![
+        vec!["5", "8912120879298085137", "8306436399312939728"],
+        vec!["9", "3000629464555624686", "-564384158816576237"],
+        vec!["12", "-8937184432160593850", "3517000156326659898"],
+    ]);
+    assert_eq!(diff.missing_count, 3);
+    assert_eq!(diff.extra_count, 3);
+    assert_eq!(diff.changed_count, 0);
+}
+
+#[test]
+fn diff_value_changed() {
+    let default_path = Path::new("./tests/eval/assets/default.csv");
+    let other_path = Path::new("./tests/eval/assets/default_value_changed.csv");
+    let diff = diff_files(default_path, other_path, &CompareOptions::default()).unwrap();
+    assert_eq!((diff.missing_count, diff.extra_count, diff.changed_count), (0, 0, 1));
+    let columns = &diff.changed[0].columns;
+    assert_eq!(columns.len(), 1);
+    assert_eq!(columns[0].column, "test$value");
+    assert_eq!(columns[0].delta, Some(4.0));
+}
+
+#[test]
+fn diff_aligns_columns() {
+    let default_path = Path::new("./tests/eval/assets/default.csv");
+    let permuted_path = Path::new("./tests/eval/assets/default_columns_permuted.csv");
+    let diff = diff_files(default_path, permuted_path, &CompareOptions::default()).unwrap();
+    assert_eq!((diff.missing_count, diff.extra_count, diff.changed_count), (0, 0, 0));
+
+    let renamed_path = Path::new("./tests/eval/assets/default_columns_renamed.csv");
+    assert!(diff_files(default_path, renamed_path, &CompareOptions::default()).is_err());
+    let options = CompareOptions {
+        aliases: ColumnAliases::from_iter([("start", "ts")]),
+        ..Default::default()
+    };
+    let diff = diff_files(default_path, renamed_path, &options).unwrap();
+    assert_eq!((diff.missing_count, diff.extra_count, diff.changed_count), (0, 0, 0));
+}
+
+#[test]
+fn diff_counts_rows_beyond_the_candidates() {
+    let dir = std::env::temp_dir().join(format!("achilles-diff-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let write = |name: &str, rows: std::ops::Range<u32>| {
+        let path = dir.join(name);
+        let mut csv = String::from("test$ts:INTEGER(64 bits),test$value:INTEGER(64 bits)\n");
+        for row in rows {
+            csv.push_str(&format!("{row},{row}\n"));
+        }
+        std::fs::write(&path, csv).unwrap();
+        path
+    };
+    let origin_path = write("origin.csv", 0..3000);
+    let other_path = write("other.csv", 1000..5000);
+    let diff = diff_files(&origin_path, &other_path, &CompareOptions::default()).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!((diff.missing_count, diff.extra_count, diff.changed_count), (1000, 2000, 0));
+    assert_eq!((diff.missing.len(), diff.extra.len()), (100, 100));
+}
+
+#[test]
+fn float_tolerance() {
+    let origin_path = Path::new("./tests/eval/assets/floats.csv");
+    let other_path = Path::new("./tests/eval/assets/floats_rounded.csv");
+    let res = compare_files_with(origin_path, other_path, &CompareOptions::default()).unwrap();
+    assert_eq!(ResultRelation::Equal, res.relation);
+    let max_error = res.max_error.unwrap();
+    assert!(max_error.absolute > 1e-5 && max_error.absolute < 1e-3);
+    assert!(max_error.relative < 1e-12);
+    assert_eq!(max_error.ulps, 839);
+
+    let strict = CompareOptions {
+        tolerance: Tolerance {
+            absolute: 0.0,
+            relative: 0.0,
+            ulps: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let res = compare_files_with(origin_path, other_path, &strict).unwrap();
+    assert_eq!(ResultRelation::Diff, res.relation);
+    assert_eq!(None, res.max_error);
+}
+
+#[test]
+fn float_tolerance_reordered() {
+    let origin_path = Path::new("./tests/eval/assets/floats.csv");
+    let other_path = Path::new("./tests/eval/assets/floats_rounded_reordered.csv");
+    let res = compare_files_with(origin_path, other_path, &CompareOptions::default()).unwrap();
+    assert_eq!(ResultRelation::Reordered, res.relation);
+    assert_eq!(res.max_error.unwrap().ulps, 839);
+
+    let rounded_path = Path::new("./tests/eval/assets/floats_rounded.csv");
+    let res = compare_files_with(rounded_path, other_path, &CompareOptions::default()).unwrap();
+    assert_eq!(ResultRelation::Reordered, res.relation);
+    assert_eq!(res.max_error.unwrap().ulps, 0);
+}
+
+#[test]
+fn float_keys_sorted_by_value() {
+    let origin_path = Path::new("./tests/eval/assets/float_keys.csv");
+    let other_path = Path::new("./tests/eval/assets/float_keys_reordered.csv");
+    let res = compare_files_with(origin_path, other_path, &CompareOptions::default()).unwrap();
+    assert_eq!(ResultRelation::Reordered, res.relation);
+    assert!(res.max_error.unwrap().absolute < 1e-9);
+
+    let diff = diff_files(origin_path, other_path, &CompareOptions::default()).unwrap();
+    assert_eq!((diff.missing_count, diff.extra_count, diff.changed_count), (0, 0, 0));
+
+    let strict = CompareOptions {
+        tolerance: Tolerance {
+            absolute: 0.0,
+            relative: 0.0,
+            ulps: 0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let res = compare_files_with(origin_path, other_path, &strict).unwrap();
+    assert_eq!(ResultRelation::Diff, res.relation);
 }
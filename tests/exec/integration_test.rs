@@ -0,0 +1,188 @@
+use std::{collections::HashMap, fs, time::Duration};
+
+use achilles::{
+    cluster::fake_cluster::{FakeCluster, FakeOutcome},
+    process_test_case::process_test_sets::{
+        process_single_test_case_with, process_test_set_on_cluster, process_test_sets_with,
+    },
+    reference::{
+        interpreter::{RefField, Table},
+        value::Value,
+    },
+    test_case_exec::TestCaseExecStatus,
+    test_case_gen::{
+        oracle::QueryGenStrategy,
+        query_id::TestCaseId,
+        test_case::{TestCase, TestSet},
+    },
+    LancerConfig,
+};
+use nes_rust_client::prelude::*;
+use nes_types::NesType;
+
+/// Config with short timeouts and its own directory for every test.
+fn test_config(name: &str) -> LancerConfig {
+    let mut config = LancerConfig::default();
+    config.path_config.base = std::env::temp_dir().join(format!("achilles-exec-{name}"));
+    config.test_case_timeout = Duration::from_millis(200);
+    let readiness = &mut config.runner_config.readiness;
+    readiness.startup_timeout = Duration::from_millis(100);
+    readiness.initial_backoff = Duration::from_millis(1);
+    readiness.max_backoff = Duration::from_millis(10);
+    fs::create_dir_all(config.path_config.result(0)).unwrap();
+    config
+}
+
+fn test_case(config: &LancerConfig, id: TestCaseId) -> TestCase {
+    let path = config.path_config.result(0).join(format!("{id}.csv"));
+    let query = QueryBuilder::from_source("numbers").sink(Sink::csv_file(&path, false));
+    TestCase::new(id, query, path)
+}
+
+fn test_set(config: &LancerConfig, id: u32, other_count: u32) -> TestSet {
+    TestSet {
+        id,
+        seed: 0,
+        strategy: QueryGenStrategy::Filter,
+        origin: test_case(config, TestCaseId::Origin),
+        others: (0..other_count)
+            .map(|other_id| test_case(config, TestCaseId::Other(other_id)))
+            .collect(),
+    }
+}
+
+#[tokio::test]
+async fn crash_restarts_cluster() {
+    let config = test_config("crash");
+    let clusters = FakeCluster::default().with_responder(|test_case| match test_case.id {
+        TestCaseId::Origin => FakeOutcome::Crash,
+        TestCaseId::Other(_) => FakeOutcome::Stop,
+    });
+    let exec = process_test_set_on_cluster(&clusters, 0, test_set(&config, 0, 2), &config, 0).await;
+    assert!(
+        matches!(exec.origin.status, TestCaseExecStatus::Failed(ref reason) if reason.starts_with("NES crashed"))
+    );
+    assert!(exec
+        .others
+        .iter()
+        .all(|other| other.status == TestCaseExecStatus::Success));
+    assert_eq!(clusters.startup_count(), 2);
+}
+
+#[tokio::test]
+async fn health_check_error_fails_test_case() {
+    let config = test_config("health");
+    let clusters = FakeCluster::default().with_responder(|test_case| match test_case.id {
+        TestCaseId::Origin => FakeOutcome::HealthCheckError,
+        TestCaseId::Other(_) => FakeOutcome::Stop,
+    });
+    let exec = process_test_set_on_cluster(&clusters, 0, test_set(&config, 0, 1), &config, 0).await;
+    assert!(
+        matches!(exec.origin.status, TestCaseExecStatus::Failed(ref reason) if reason.contains("Simulated health check error."))
+    );
+    assert_eq!(exec.others[0].status, TestCaseExecStatus::Success);
+    assert_eq!(clusters.startup_count(), 2);
+}
+
+#[tokio::test]
+async fn hang_times_out() {
+    let config = test_config("hang");
+    let clusters = FakeCluster::default().with_responder(|test_case| match test_case.id {
+        TestCaseId::Other(0) => FakeOutcome::Hang,
+        _ => FakeOutcome::Stop,
+    });
+    let exec = process_test_set_on_cluster(&clusters, 0, test_set(&config, 0, 2), &config, 0).await;
+    assert_eq!(exec.origin.status, TestCaseExecStatus::Success);
+    assert_eq!(exec.others[0].status, TestCaseExecStatus::TimedOut);
+    assert_eq!(exec.others[1].status, TestCaseExecStatus::Success);
+    assert_eq!(clusters.startup_count(), 2);
+}
+
+#[tokio::test]
+async fn registration_failure() {
+    let config = test_config("registration");
+    let clusters = FakeCluster::default().with_responder(|test_case| match test_case.id {
+        TestCaseId::Origin => FakeOutcome::RegistrationFailure("Invalid query.".into()),
+        _ => FakeOutcome::Stop,
+    });
+    let exec = process_test_set_on_cluster(&clusters, 0, test_set(&config, 0, 1), &config, 0).await;
+    assert_eq!(
+        exec.origin.status,
+        TestCaseExecStatus::Failed("Unable to register query: Invalid query.".into())
+    );
+    assert_eq!(exec.others[0].status, TestCaseExecStatus::Success);
+}
+
+#[tokio::test]
+async fn startup_failure_skips_test_set() {
+    let config = test_config("startup");
+    let clusters = FakeCluster::default().with_startup_failures(1);
+    let test_sets = vec![test_set(&config, 0, 1), test_set(&config, 1, 1)];
+    let execs = process_test_sets_with(&clusters, 0, &config, test_sets).await;
+    assert!(matches!(
+        execs[0].origin.status,
+        TestCaseExecStatus::StartupFailed(_)
+    ));
+    assert!(matches!(
+        execs[0].others[0].status,
+        TestCaseExecStatus::StartupFailed(_)
+    ));
+    assert_eq!(execs[1].origin.status, TestCaseExecStatus::Success);
+}
+
+#[tokio::test]
+async fn writes_reference_result() {
+    let config = test_config("reference");
+    let table = Table {
+        source_name: "numbers".into(),
+        fields: vec![RefField {
+            name: "value".into(),
+            data_type: NesType::i64(),
+        }],
+        rows: vec![vec![Value::Int(1)], vec![Value::Int(2)]],
+    };
+    let clusters =
+        FakeCluster::default().with_reference_sources(HashMap::from([("numbers".into(), table)]));
+    let test_case = test_case(&config, TestCaseId::Origin);
+    let exec = process_single_test_case_with(&clusters, 0, test_case, &config).await;
+    assert_eq!(exec.status, TestCaseExecStatus::Success);
+    let result = fs::read_to_string(exec.result_path()).unwrap();
+    assert_eq!(result, "numbers$value:INTEGER(64 bits)\n1\n2\n");
+}
+
+#[tokio::test]
+async fn resume_skips_checkpointed_test_sets() {
+    let mut config = test_config("resume");
+    let test_sets = |config: &LancerConfig, ids: &[u32]| {
+        ids.iter()
+            .map(|&id| test_set(config, id, 1))
+            .collect::<Vec<_>>()
+    };
+    let clusters = FakeCluster::default();
+    let execs = process_test_sets_with(&clusters, 0, &config, test_sets(&config, &[0, 1])).await;
+    assert_eq!(execs.len(), 2);
+
+    // checkpointed test sets are not executed again, the others are, and fail
+    config.resume = true;
+    let failing = FakeCluster::default()
+        .with_responder(|_| FakeOutcome::RegistrationFailure("Invalid query.".into()));
+    let execs = process_test_sets_with(&failing, 0, &config, test_sets(&config, &[0, 1, 2])).await;
+    let ids = execs.iter().map(|exec| exec.id).collect::<Vec<_>>();
+    assert_eq!(ids, [0, 1, 2]);
+    assert_eq!(execs[0].origin.status, TestCaseExecStatus::Success);
+    assert_eq!(execs[1].others[0].status, TestCaseExecStatus::Success);
+    assert!(matches!(
+        execs[2].origin.status,
+        TestCaseExecStatus::Failed(_)
+    ));
+
+    // without resume the checkpoints are cleared and every test set is executed
+    config.resume = false;
+    let failing = FakeCluster::default()
+        .with_responder(|_| FakeOutcome::RegistrationFailure("Invalid query.".into()));
+    let execs = process_test_sets_with(&failing, 0, &config, test_sets(&config, &[0])).await;
+    assert!(matches!(
+        execs[0].origin.status,
+        TestCaseExecStatus::Failed(_)
+    ));
+}
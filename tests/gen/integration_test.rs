@@ -0,0 +1,57 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use achilles::{
+    generate_files, generate_test_sets, stream_schema::read_stream_schema_from_file,
+    test_case_gen::test_case::write_test_sets_to_file, LancerConfig,
+};
+
+/// Runs the StreamGen and QueryGen stages of test run 0 from scratch and returns the content of
+/// every generated file by its path relative to the base directory.
+fn generate(config: &LancerConfig) -> BTreeMap<PathBuf, Vec<u8>> {
+    let base = config.path_config.base();
+    let _ = fs::remove_dir_all(base);
+    fs::create_dir_all(config.path_config.result(0)).unwrap();
+    generate_files(0, config).unwrap();
+    let schema = read_stream_schema_from_file(0, config).unwrap();
+    let test_sets = generate_test_sets(0, config, &schema);
+    write_test_sets_to_file(0, config, &test_sets).unwrap();
+    let mut files = BTreeMap::new();
+    read_files(base, base, &mut files);
+    fs::remove_dir_all(base).unwrap();
+    files
+}
+
+fn read_files(base: &Path, dir: &Path, files: &mut BTreeMap<PathBuf, Vec<u8>>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            read_files(base, &path, files);
+        } else {
+            let relative = path.strip_prefix(base).unwrap().to_path_buf();
+            files.insert(relative, fs::read(&path).unwrap());
+        }
+    }
+}
+
+#[test]
+fn same_seed_generates_identical_files() {
+    let mut config = LancerConfig::default();
+    config.path_config.base = std::env::temp_dir().join("achilles-gen-deterministic");
+    config.test_config.seed = u64::MAX - 1;
+    config.test_config.record_count = 200;
+    config.test_config.oracle_reps = 2;
+
+    let files = generate(&config);
+    assert!(files
+        .keys()
+        .any(|path| path.extension() == Some("csv".as_ref())));
+    assert!(files.len() > 2);
+    assert_eq!(generate(&config), files);
+
+    config.test_config.seed = 1;
+    assert_ne!(generate(&config), files);
+}
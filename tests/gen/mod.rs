@@ -0,0 +1 @@
+pub mod integration_test;
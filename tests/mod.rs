@@ -1 +1,3 @@
 pub mod eval;
+pub mod exec;
+pub mod gen;